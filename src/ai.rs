@@ -0,0 +1,427 @@
+// 五道方搜索引擎：迭代加深的极大极小搜索 + alpha-beta 剪枝
+//
+// 五道方的行棋方并不严格交替（额外落子、连续吃子），所以这里不用负极大值写法，
+// 而是始终从根节点玩家的视角打分，轮到根节点玩家时取最大，轮到对手时取最小。
+
+use crate::i18n::{tr, trf};
+use crate::{Board, Cell, GameAction, GameEvent, GamePhase, Player, RewardPattern};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// 胜负分值，减去层数使得更快的胜利得分更高
+pub const WIN_SCORE: i32 = 100_000;
+const MAX_DEPTH: u32 = 64;
+
+/// 搜索限制
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchLimits {
+    /// 每步的搜索时间，None 表示不限时
+    pub movetime: Option<Duration>,
+    /// 最大搜索深度，None 表示不限深度
+    pub depth: Option<u32>,
+}
+
+/// 每完成一层迭代输出的搜索信息
+#[derive(Debug, Clone)]
+pub struct SearchInfo {
+    /// 完成的深度
+    pub depth: u32,
+    /// 最佳动作的分值，从当前行棋方的视角计算
+    pub score: i32,
+    /// 已搜索的节点数
+    pub nodes: u64,
+    /// 已用时间
+    pub elapsed: Duration,
+    /// 主要变例
+    pub pv: Vec<GameAction>,
+}
+
+/// 搜索结果
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// 最佳动作，无棋可走时为 None
+    pub best: Option<GameAction>,
+    /// 最佳动作的分值，从当前行棋方的视角计算
+    pub score: i32,
+    /// 完成的深度
+    pub depth: u32,
+    /// 已搜索的节点数
+    pub nodes: u64,
+    /// 主要变例
+    pub pv: Vec<GameAction>,
+}
+
+struct SearchContext<'a> {
+    root_player: Player,
+    deadline: Option<Instant>,
+    stop: &'a AtomicBool,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl SearchContext<'_> {
+    fn should_stop(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+        // 每 1024 个节点检查一次时间
+        if self.nodes.is_multiple_of(1024) {
+            let timed_out = self.deadline.is_some_and(|d| Instant::now() >= d);
+            if timed_out || self.stop.load(Ordering::Relaxed) {
+                self.aborted = true;
+            }
+        }
+        self.aborted
+    }
+}
+
+/// 在给定局面上搜索最佳动作，每完成一层调用一次 on_info
+pub fn search(
+    board: &Board,
+    limits: SearchLimits,
+    stop: &AtomicBool,
+    mut on_info: impl FnMut(&SearchInfo),
+) -> SearchResult {
+    let start = Instant::now();
+
+    // 搜索时不需要棋谱和事件，清空以减少复制开销
+    let mut root = board.clone();
+    root.game_record.clear();
+    root.set_event_recording(false);
+
+    let mut ctx = SearchContext {
+        root_player: root.current_player,
+        deadline: limits.movetime.map(|t| start + t),
+        stop,
+        nodes: 0,
+        aborted: false,
+    };
+
+    let mut result = SearchResult {
+        best: root.legal_actions().into_iter().next(),
+        score: evaluate(&root, ctx.root_player),
+        depth: 0,
+        nodes: 0,
+        pv: Vec::new(),
+    };
+
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    for depth in 1..=max_depth {
+        let mut pv = Vec::new();
+        let score = minimax(
+            &root,
+            depth,
+            0,
+            -WIN_SCORE - 1,
+            WIN_SCORE + 1,
+            &result.pv,
+            &mut pv,
+            &mut ctx,
+        );
+
+        // 被中断的迭代结果不可靠，保留上一层的结果
+        if ctx.aborted {
+            break;
+        }
+
+        result.score = score;
+        result.depth = depth;
+        result.best = pv.first().cloned().or(result.best);
+        result.pv = pv;
+        result.nodes = ctx.nodes;
+
+        on_info(&SearchInfo {
+            depth,
+            score,
+            nodes: ctx.nodes,
+            elapsed: start.elapsed(),
+            pv: result.pv.clone(),
+        });
+
+        // 已经找到必胜或必败，无需继续加深
+        if is_decisive(score) {
+            break;
+        }
+    }
+
+    result.nodes = ctx.nodes;
+    result
+}
+
+/// 分析模式中的一个候选动作
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// 候选动作
+    pub action: GameAction,
+    /// 分值，从当前行棋方的视角计算
+    pub score: i32,
+    /// 该动作之后的主要变例，第一个为动作本身
+    pub pv: Vec<GameAction>,
+}
+
+/// 分析模式：对根节点的每个动作都用完整窗口搜索，得到所有候选动作的准确分值。
+/// 每完成一层调用一次 on_info，候选按分值从高到低排列，分值从当前行棋方的视角计算
+pub fn analyze(
+    board: &Board,
+    limits: SearchLimits,
+    stop: &AtomicBool,
+    mut on_info: impl FnMut(u32, u64, &[Candidate]),
+) {
+    let start = Instant::now();
+
+    let mut root = board.clone();
+    root.game_record.clear();
+    root.set_event_recording(false);
+    if root.check_winner().is_some() {
+        return;
+    }
+
+    let mut ctx = SearchContext {
+        root_player: root.current_player,
+        deadline: limits.movetime.map(|t| start + t),
+        stop,
+        nodes: 0,
+        aborted: false,
+    };
+
+    let mut candidates: Vec<Candidate> = root
+        .legal_actions()
+        .into_iter()
+        .map(|action| Candidate {
+            action,
+            score: 0,
+            pv: Vec::new(),
+        })
+        .collect();
+
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    for depth in 1..=max_depth {
+        for candidate in candidates.iter_mut() {
+            let mut child = root.clone();
+            let mut child_pv = Vec::new();
+            let prev_pv = candidate.pv.get(1..).unwrap_or(&[]).to_vec();
+
+            let score = match child.apply_action(&candidate.action) {
+                Ok(_) => minimax(
+                    &child,
+                    depth - 1,
+                    1,
+                    -WIN_SCORE - 1,
+                    WIN_SCORE + 1,
+                    &prev_pv,
+                    &mut child_pv,
+                    &mut ctx,
+                ),
+                Err(_) => terminal_score(root.current_player.opponent(), ctx.root_player, 1),
+            };
+
+            // 被中断的迭代结果不可靠，保留上一层的结果
+            if ctx.aborted {
+                return;
+            }
+
+            candidate.score = score;
+            candidate.pv = std::iter::once(candidate.action.clone())
+                .chain(child_pv)
+                .collect();
+        }
+
+        candidates.sort_by_key(|c| -c.score);
+        on_info(depth, ctx.nodes, &candidates);
+
+        // 所有候选都已分出胜负，无需继续加深
+        if candidates.iter().all(|c| is_decisive(c.score)) {
+            break;
+        }
+    }
+}
+
+/// 分值是否表示必胜或必败
+pub fn is_decisive(score: i32) -> bool {
+    score.abs() >= WIN_SCORE - MAX_DEPTH as i32
+}
+
+#[allow(clippy::too_many_arguments)]
+fn minimax(
+    board: &Board,
+    depth: u32,
+    ply: u32,
+    mut alpha: i32,
+    mut beta: i32,
+    prev_pv: &[GameAction],
+    pv: &mut Vec<GameAction>,
+    ctx: &mut SearchContext,
+) -> i32 {
+    ctx.nodes += 1;
+    if ctx.should_stop() {
+        return 0;
+    }
+
+    if let Some(winner) = board.check_winner() {
+        return terminal_score(winner, ctx.root_player, ply);
+    }
+
+    let mut actions = board.legal_actions();
+    if depth == 0 || actions.is_empty() {
+        return evaluate(board, ctx.root_player);
+    }
+
+    // 上一轮主变例的动作优先搜索
+    if let Some(i) = prev_pv.first().and_then(|first| actions.iter().position(|a| a == first)) {
+        actions.swap(0, i);
+    }
+
+    let maximizing = board.current_player == ctx.root_player;
+    let mut best = if maximizing { i32::MIN } else { i32::MAX };
+
+    for (i, action) in actions.iter().enumerate() {
+        let mut child = board.clone();
+        let mut child_pv = Vec::new();
+        let child_prev = if i == 0 && !prev_pv.is_empty() {
+            &prev_pv[1..]
+        } else {
+            &[]
+        };
+
+        // 合法动作执行失败只有一种情况：该动作导致对方无法走棋，行动方判负
+        let score = match child.apply_action(action) {
+            Ok(_) => minimax(
+                &child,
+                depth - 1,
+                ply + 1,
+                alpha,
+                beta,
+                child_prev,
+                &mut child_pv,
+                ctx,
+            ),
+            Err(_) => terminal_score(board.current_player.opponent(), ctx.root_player, ply + 1),
+        };
+
+        if ctx.aborted {
+            return 0;
+        }
+
+        let improved = if maximizing { score > best } else { score < best };
+        if improved {
+            best = score;
+            pv.clear();
+            pv.push(action.clone());
+            pv.extend(child_pv);
+        }
+
+        if maximizing {
+            alpha = alpha.max(score);
+        } else {
+            beta = beta.min(score);
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+fn terminal_score(winner: Player, root_player: Player, ply: u32) -> i32 {
+    if winner == root_player {
+        WIN_SCORE - ply as i32
+    } else {
+        -WIN_SCORE + ply as i32
+    }
+}
+
+/// 静态评估，从 player 的视角打分
+pub fn evaluate(board: &Board, player: Player) -> i32 {
+    let opponent = player.opponent();
+    let mut score = 0;
+
+    // 棋子数量
+    score += (board.player_pieces(player).len() as i32
+        - board.player_pieces(opponent).len() as i32)
+        * 100;
+
+    // 受保护的棋子
+    let protected = |p: Player| board.reward_pieces.get(&p).map_or(0, |s| s.len()) as i32;
+    score += (protected(player) - protected(opponent)) * 15;
+
+    // 待执行的吃子
+    let captures = |p: Player| board.capture_remaining.get(&p).copied().unwrap_or(0) as i32;
+    score += (captures(player) - captures(opponent)) * 90;
+
+    // 额外落子机会
+    if board.extra_moves > 0 {
+        let bonus = board.extra_moves as i32 * 60;
+        score += if board.current_player == player { bonus } else { -bonus };
+    }
+
+    // 走子阶段的机动性
+    if board.phase == GamePhase::Movement {
+        let mobility = |p: Player| {
+            let mut count = 0;
+            for (r, c) in board.player_pieces(p) {
+                let neighbors = [
+                    (r.wrapping_sub(1), c),
+                    (r + 1, c),
+                    (r, c.wrapping_sub(1)),
+                    (r, c + 1),
+                ];
+                for (nr, nc) in neighbors {
+                    if Board::is_valid_pos(nr, nc) && board.grid[nr][nc] == Cell::Empty {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+        score += (mobility(player) - mobility(opponent)) * 5;
+    }
+
+    score
+}
+
+/// 用一句话说明动作的效果，供界面提示使用，例如 "形成成方[位置:(1,1)]，+1次额外落子"
+pub fn explain(board: &Board, action: &GameAction) -> String {
+    let mover = board.current_player;
+    let opponent = mover.opponent();
+    let mut after = board.clone();
+    after.take_events();
+    let result = after.apply_action(action);
+
+    let mut parts = Vec::new();
+    let bonus = if board.phase == GamePhase::Placement {
+        "explain.formed_extra"
+    } else {
+        "explain.formed_capture"
+    };
+    for event in after.take_events() {
+        if let GameEvent::PatternFormed { pattern, reward, .. } = event {
+            parts.push(trf(bonus, &[&pattern, &reward]));
+        }
+    }
+
+    // 对方差一子（一步）即可形成的模式被破坏
+    let remaining: Vec<RewardPattern> = after
+        .threats(opponent)
+        .into_iter()
+        .map(|t| t.pattern)
+        .collect();
+    for threat in board.threats(opponent) {
+        if threat.needed.len() == 1 && !remaining.contains(&threat.pattern) {
+            parts.push(trf("explain.blocks", &[&threat.pattern]));
+        }
+    }
+
+    if result.is_err() {
+        parts.push(tr("explain.lost").to_string());
+    } else if after.check_winner() == Some(mover) {
+        parts.push(tr("explain.wins").to_string());
+    } else if after.phase != board.phase {
+        parts.push(trf("explain.enters", &[&after.phase]));
+    }
+
+    parts
+        .into_iter()
+        .reduce(|text, part| trf("list.join", &[&text, &part]))
+        .unwrap_or_default()
+}
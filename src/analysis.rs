@@ -0,0 +1,118 @@
+// 分析模式：后台线程持续分析界面上显示的局面
+//
+// 界面每帧把显示的局面交给 Analyzer，局面变化时立即中断旧的搜索并开始新的，
+// 界面线程只做非阻塞的收发，不会被搜索拖慢。
+
+use crate::ai::{self, Candidate, SearchLimits};
+use crate::{Board, Player};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+/// 一次分析的最新结果
+#[derive(Debug, Clone)]
+pub struct AnalysisUpdate {
+    /// 分析的局面哈希
+    pub hash: u64,
+    /// 分值所属的行棋方
+    pub side: Player,
+    /// 已完成的搜索深度
+    pub depth: u32,
+    /// 已搜索的节点数
+    pub nodes: u64,
+    /// 候选动作，按分值从高到低排列
+    pub candidates: Vec<Candidate>,
+}
+
+impl AnalysisUpdate {
+    /// 从黑方视角的分值，供评估条使用
+    pub fn black_score(&self) -> Option<i32> {
+        let best = self.candidates.first()?.score;
+        Some(match self.side {
+            Player::Black => best,
+            Player::White => -best,
+        })
+    }
+}
+
+/// 后台分析线程的句柄，局面变化时重新开始分析，销毁时停止线程
+pub struct Analyzer {
+    positions: Sender<(u64, Board)>,
+    updates: Receiver<AnalysisUpdate>,
+    stop: Arc<AtomicBool>,
+    current: Option<u64>,
+    latest: Option<AnalysisUpdate>,
+}
+
+impl Analyzer {
+    /// 启动分析线程
+    pub fn spawn() -> Self {
+        let (positions, position_rx) = mpsc::channel::<(u64, Board)>();
+        let (update_tx, updates) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        thread::spawn(move || {
+            while let Ok(mut next) = position_rx.recv() {
+                thread_stop.store(false, Ordering::Relaxed);
+                // 只分析最新的局面
+                while let Ok(newer) = position_rx.try_recv() {
+                    next = newer;
+                }
+
+                let (hash, board) = next;
+                let side = board.current_player;
+                ai::analyze(&board, SearchLimits::default(), &thread_stop, |depth, nodes, candidates| {
+                    let update = AnalysisUpdate {
+                        hash,
+                        side,
+                        depth,
+                        nodes,
+                        candidates: candidates.to_vec(),
+                    };
+                    if update_tx.send(update).is_err() {
+                        thread_stop.store(true, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+
+        Analyzer {
+            positions,
+            updates,
+            stop,
+            current: None,
+            latest: None,
+        }
+    }
+
+    /// 设置要分析的局面，与正在分析的局面相同时什么也不做
+    pub fn set_position(&mut self, board: &Board) {
+        let hash = board.position_hash();
+        if self.current == Some(hash) {
+            return;
+        }
+        self.stop.store(true, Ordering::Relaxed);
+        if self.positions.send((hash, board.clone())).is_ok() {
+            self.current = Some(hash);
+            self.latest = None;
+        }
+    }
+
+    /// 收取分析结果，返回当前局面的最新结果
+    pub fn poll(&mut self) -> Option<&AnalysisUpdate> {
+        while let Ok(update) = self.updates.try_recv() {
+            if Some(update.hash) == self.current {
+                self.latest = Some(update);
+            }
+        }
+        self.latest.as_ref()
+    }
+}
+
+impl Drop for Analyzer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
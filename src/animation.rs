@@ -0,0 +1,145 @@
+// 棋盘动画
+//
+// 根据动作产生的事件播放简短的动画：落子从上方落下、移动的棋子从起点滑到终点、
+// 被吃的棋子淡出、新形成的奖励模式所在格子闪烁。同一次更新中的多个事件依次播放，
+// 比如电脑连续落子和吃子时先看到落子再看到吃子。
+
+use std::fmt;
+
+use wudaofang::i18n::tr;
+use wudaofang::{GameEvent, Player};
+
+// 动画速度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationSpeed {
+    Off,
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl AnimationSpeed {
+    pub const ALL: [AnimationSpeed; 4] = [
+        AnimationSpeed::Off,
+        AnimationSpeed::Slow,
+        AnimationSpeed::Normal,
+        AnimationSpeed::Fast,
+    ];
+
+    // 动画时长的倍数，关闭时为 None
+    fn scale(self) -> Option<f32> {
+        match self {
+            AnimationSpeed::Off => None,
+            AnimationSpeed::Slow => Some(2.0),
+            AnimationSpeed::Normal => Some(1.0),
+            AnimationSpeed::Fast => Some(0.5),
+        }
+    }
+}
+
+impl fmt::Display for AnimationSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key = match self {
+            AnimationSpeed::Off => "animation.off",
+            AnimationSpeed::Slow => "animation.slow",
+            AnimationSpeed::Normal => "animation.normal",
+            AnimationSpeed::Fast => "animation.fast",
+        };
+        write!(f, "{}", tr(key))
+    }
+}
+
+// 一段动画
+#[derive(Debug)]
+pub enum Effect {
+    Drop { pos: (usize, usize), player: Player },
+    Slide { from: (usize, usize), to: (usize, usize), player: Player },
+    // player 为被吃掉的一方
+    Fade { pos: (usize, usize), player: Player },
+    Flash { cells: Vec<(usize, usize)> },
+}
+
+impl Effect {
+    // 正常速度下的时长（秒）
+    fn duration(&self) -> f32 {
+        match self {
+            Effect::Drop { .. } => 0.25,
+            Effect::Slide { .. } => 0.3,
+            Effect::Fade { .. } => 0.4,
+            Effect::Flash { .. } => 0.9,
+        }
+    }
+
+    fn from_event(event: &GameEvent) -> Option<Effect> {
+        match event {
+            GameEvent::PiecePlaced { player, pos } => Some(Effect::Drop { pos: *pos, player: *player }),
+            GameEvent::PieceMoved { player, from, to } => Some(Effect::Slide { from: *from, to: *to, player: *player }),
+            GameEvent::PieceCaptured { player, pos } => Some(Effect::Fade { pos: *pos, player: player.opponent() }),
+            GameEvent::PatternFormed { pattern, .. } => Some(Effect::Flash { cells: pattern.cells() }),
+            _ => None,
+        }
+    }
+}
+
+struct Active {
+    effect: Effect,
+    start: f32,
+    duration: f32,
+}
+
+// 正在播放的动画
+pub struct Animator {
+    speed: AnimationSpeed,
+    active: Vec<Active>,
+}
+
+impl Animator {
+    pub fn new(speed: AnimationSpeed) -> Self {
+        Self { speed, active: Vec::new() }
+    }
+
+    pub fn speed(&self) -> AnimationSpeed {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: AnimationSpeed) {
+        self.speed = speed;
+        if speed == AnimationSpeed::Off {
+            self.active.clear();
+        }
+    }
+
+    // 为事件安排动画，接在尚未播放完的动画之后依次开始
+    pub fn push_events(&mut self, events: &[GameEvent], time: f32) {
+        let Some(scale) = self.speed.scale() else {
+            return;
+        };
+        let mut start = self.active.iter().map(|a| a.start + a.duration).fold(time, f32::max);
+        let mut previous = start;
+        for effect in events.iter().filter_map(Effect::from_event) {
+            let duration = effect.duration() * scale;
+            // 模式闪烁与形成它的落子或移动同时开始，不推迟后面的动画
+            if let Effect::Flash { .. } = effect {
+                self.active.push(Active { effect, start: previous, duration });
+                continue;
+            }
+            previous = start;
+            start += duration;
+            self.active.push(Active { effect, start: previous, duration });
+        }
+    }
+
+    // 丢弃已播放完的动画
+    pub fn prune(&mut self, time: f32) {
+        self.active.retain(|a| time < a.start + a.duration);
+    }
+
+    // 当前的动画及其进度，进度从 0 到 1，尚未开始的动画进度为 0
+    pub fn effects(&self, time: f32) -> Vec<(&Effect, f32)> {
+        self.active
+            .iter()
+            .map(|a| (&a.effect, ((time - a.start) / a.duration).clamp(0.0, 1.0)))
+            .filter(|(_, progress)| *progress < 1.0)
+            .collect()
+    }
+}
@@ -0,0 +1,80 @@
+// 自动存档
+//
+// 每个动作之后把进行中的对局以局面快照格式写入本地数据目录的 autosave.json，
+// 下次启动时可以继续；对局结束后把棋谱归档到 archive/game-<unix 时间>.json，并删除自动存档。
+
+use crate::savegame::{self, Snapshot};
+use crate::i18n::{tr, trf};
+use crate::Board;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const AUTOSAVE_FILE: &str = "autosave.json";
+const ARCHIVE_DIR: &str = "archive";
+
+/// 本地数据目录，例如 Linux 下的 ~/.local/share/wudaofang
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("wudaofang"))
+}
+
+fn autosave_path() -> Result<PathBuf, String> {
+    data_dir()
+        .map(|dir| dir.join(AUTOSAVE_FILE))
+        .ok_or_else(|| tr("io.no_data_dir").to_string())
+}
+
+/// 保存进行中的对局，先写临时文件再替换，避免写到一半时崩溃损坏存档
+pub fn save(board: &Board) -> Result<(), String> {
+    let path = autosave_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| trf("io.create_failed", &[&dir.display(), &e]))?;
+    }
+    let json =
+        serde_json::to_string(&Snapshot::from_board(board)).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| trf("io.write_failed", &[&tmp.display(), &e]))?;
+    fs::rename(&tmp, &path).map_err(|e| trf("io.write_failed", &[&path.display(), &e]))
+}
+
+/// 读取上次未完成的对局，没有自动存档时返回 None
+pub fn load() -> Option<Result<Board, String>> {
+    let path = autosave_path().ok()?;
+    if !path.exists() {
+        return None;
+    }
+    Some(savegame::load(&path).map(|(board, _)| board))
+}
+
+/// 删除自动存档（对局结束或开始新对局时）
+pub fn clear() {
+    if let Ok(path) = autosave_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// 已结束对局的归档目录
+pub fn archive_dir() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(ARCHIVE_DIR))
+}
+
+/// 归档已结束的对局，返回归档文件路径
+pub fn archive(board: &Board) -> Result<PathBuf, String> {
+    let dir = archive_dir().ok_or_else(|| tr("io.no_data_dir").to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| trf("io.create_failed", &[&dir.display(), &e]))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // 同一秒内结束多局时加序号区分
+    let mut path = dir.join(format!("game-{}.json", timestamp));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("game-{}-{}.json", timestamp, n));
+        n += 1;
+    }
+
+    savegame::save(&path, board, savegame::SaveKind::Record)?;
+    Ok(path)
+}
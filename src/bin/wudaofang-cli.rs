@@ -0,0 +1,20 @@
+// 不含图形界面的命令行程序，子命令与 wudaofang 相同
+
+use wudaofang::cli;
+use wudaofang::i18n::{self, tr};
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+    if let Err(e) = i18n::init_from_args(&mut args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    match cli::run_command(&args[1..]) {
+        Some(code) => std::process::exit(code),
+        None => {
+            eprintln!("{}", tr("cli.usage"));
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+// 开局库
+//
+// 落子阶段基本决定了胜负，开局库汇总棋谱和自我对弈中落子阶段前若干步的着法及胜负。
+// 局面按对称规范化后存储，着法也换算到规范方向并合并等价着法，查询时再换算回来。
+//
+// 文件格式（小端）：
+//   "WDFB" 版本(u8) 局面数(u32)
+//   每个局面：规范局面哈希(u64) 着法数(u8)，每个着法：格子(u8, 行*5+列) 局数(u32) 黑胜(u32) 白胜(u32)
+//
+// 用法:
+//   book build [--out 文件] [--depth N] [--min-games N] [--db 数据库] [--selfplay N] [--movetime 毫秒] [棋谱文件或目录...]
+//   book show  [--book 文件] [局面字符串 | startpos [moves ...] | 棋谱文件]
+
+use crate::ai::{self, SearchLimits};
+use crate::symmetry::{inverse, transform_cell};
+use crate::i18n::{tr, trf};
+use crate::{Board, GameAction, GamePhase, Player, autosave, fnv1a};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+const MAGIC: &[u8; 4] = b"WDFB";
+const VERSION: u8 = 1;
+
+/// 默认收录落子阶段的前多少步
+pub const BOOK_DEPTH: usize = 12;
+/// 引擎只采用至少出现过这么多局的着法
+pub const BOOK_MIN_GAMES: u32 = 3;
+// 自我对弈开头随机落子的最大步数，使对局多样化
+const SELFPLAY_RANDOM_PLIES: usize = 4;
+// 自我对弈超过这么多步判和
+const SELFPLAY_MAX_ACTIONS: usize = 1000;
+
+/// 开局库中某一局面下的一个着法及其战绩
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookMove {
+    /// 落子位置
+    pub pos: (usize, usize),
+    /// 出现的局数
+    pub games: u32,
+    /// 黑方胜局数
+    pub black_wins: u32,
+    /// 白方胜局数
+    pub white_wins: u32,
+}
+
+impl BookMove {
+    /// player 一方的得分率，和棋算半局
+    pub fn score(&self, player: Player) -> f32 {
+        let wins = match player {
+            Player::Black => self.black_wins,
+            Player::White => self.white_wins,
+        };
+        let draws = self.games - self.black_wins - self.white_wins;
+        (wins as f32 + 0.5 * draws as f32) / self.games.max(1) as f32
+    }
+}
+
+/// 开局库：局面（对称规范化后的哈希）到着法统计的映射
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    entries: HashMap<u64, Vec<BookMove>>,
+}
+
+/// 默认开局库放在本地数据目录下
+pub fn default_path() -> PathBuf {
+    autosave::data_dir().map_or(PathBuf::from("book.bin"), |dir| dir.join("book.bin"))
+}
+
+impl Book {
+    /// 收录的局面数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否没有收录任何局面
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 收录一局棋谱的前 depth 步落子
+    pub fn add_game(&mut self, record: &[GameAction], winner: Option<Player>, depth: usize) {
+        let mut board = Board::new();
+        for action in record.iter().filter(|a| a.is_play()).take(depth) {
+            let GameAction::Place { pos, .. } = action else {
+                break;
+            };
+            if board.phase != GamePhase::Placement {
+                break;
+            }
+
+            let (key, sym) = board.canonical();
+            // 规范局面自身对称时，等价的着法统一记为格子编号最小的一个
+            let canonical = Board::from_position_string(&key).expect("规范局面字符串应能还原");
+            let pos = canonical
+                .stabilizer()
+                .into_iter()
+                .map(|s| transform_cell(s, transform_cell(sym, *pos)))
+                .min()
+                .expect("至少有恒等变换");
+
+            let moves = self.entries.entry(fnv1a(&key)).or_default();
+            let index = match moves.iter().position(|m| m.pos == pos) {
+                Some(i) => i,
+                None => {
+                    moves.push(BookMove {
+                        pos,
+                        games: 0,
+                        black_wins: 0,
+                        white_wins: 0,
+                    });
+                    moves.len() - 1
+                }
+            };
+            let entry = &mut moves[index];
+            entry.games += 1;
+            match winner {
+                Some(Player::Black) => entry.black_wins += 1,
+                Some(Player::White) => entry.white_wins += 1,
+                None => {}
+            }
+
+            if board.apply_action(action).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// 去掉出现次数太少的着法，没有着法的局面一并去掉
+    pub fn prune(&mut self, min_games: u32) {
+        for moves in self.entries.values_mut() {
+            moves.retain(|m| m.games >= min_games);
+        }
+        self.entries.retain(|_, moves| !moves.is_empty());
+    }
+
+    /// 当前局面的开局库着法（已换算到局面的方向），按局数从多到少排列
+    pub fn moves(&self, board: &Board) -> Vec<BookMove> {
+        if board.phase != GamePhase::Placement || board.check_winner().is_some() {
+            return Vec::new();
+        }
+        let (key, sym) = board.canonical();
+        let Some(moves) = self.entries.get(&fnv1a(&key)) else {
+            return Vec::new();
+        };
+        let back = inverse(sym);
+        let mut moves: Vec<BookMove> = moves
+            .iter()
+            .map(|m| BookMove {
+                pos: transform_cell(back, m.pos),
+                ..*m
+            })
+            // 哈希碰撞时着法可能不合法
+            .filter(|m| board.grid[m.pos.0][m.pos.1] == crate::Cell::Empty)
+            .collect();
+        moves.sort_by(|a, b| b.games.cmp(&a.games).then(a.pos.cmp(&b.pos)));
+        moves
+    }
+
+    /// 引擎采用的开局库着法：在足够多局中出现过的着法里选得分率最高的（按局数修正）
+    pub fn best_move(&self, board: &Board) -> Option<GameAction> {
+        let player = board.current_player;
+        let rated = |m: &BookMove| (m.score(player) * m.games as f32 + 1.0) / (m.games as f32 + 2.0);
+        self.moves(board)
+            .into_iter()
+            .filter(|m| m.games >= BOOK_MIN_GAMES)
+            .max_by(|a, b| rated(a).total_cmp(&rated(b)))
+            .map(|m| GameAction::Place { player, pos: m.pos })
+    }
+
+    /// 保存为二进制文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut keys: Vec<&u64> = self.entries.keys().collect();
+        keys.sort();
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+        for key in keys {
+            let moves = &self.entries[key];
+            bytes.extend_from_slice(&key.to_le_bytes());
+            bytes.push(moves.len() as u8);
+            for m in moves {
+                bytes.push((m.pos.0 * 5 + m.pos.1) as u8);
+                bytes.extend_from_slice(&m.games.to_le_bytes());
+                bytes.extend_from_slice(&m.black_wins.to_le_bytes());
+                bytes.extend_from_slice(&m.white_wins.to_le_bytes());
+            }
+        }
+
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| trf("io.create_failed", &[&dir.display(), &e]))?;
+        }
+        fs::write(path, bytes).map_err(|e| trf("io.write_failed", &[&path.display(), &e]))
+    }
+
+    /// 从 save 写出的文件读取开局库
+    pub fn load(path: &Path) -> Result<Book, String> {
+        let err = tr("book.corrupt");
+        let bytes = fs::read(path).map_err(|e| trf("io.read_failed", &[&path.display(), &e]))?;
+
+        let mut rest = bytes.as_slice();
+        let mut take = |n: usize| -> Result<&[u8], String> {
+            if rest.len() < n {
+                return Err(err.to_string());
+            }
+            let (head, tail) = rest.split_at(n);
+            rest = tail;
+            Ok(head)
+        };
+        let u32_at = |b: &[u8]| u32::from_le_bytes(b.try_into().expect("长度为 4"));
+
+        if take(4)? != MAGIC {
+            return Err(trf("book.not_a_book", &[&path.display()]));
+        }
+        let version = take(1)?[0];
+        if version != VERSION {
+            return Err(trf("book.unsupported_version", &[&version]));
+        }
+
+        let count = u32_at(take(4)?);
+        let mut entries = HashMap::new();
+        for _ in 0..count {
+            let key = u64::from_le_bytes(take(8)?.try_into().expect("长度为 8"));
+            let n = take(1)?[0];
+            let mut moves = Vec::with_capacity(n as usize);
+            for _ in 0..n {
+                let cell = take(1)?[0] as usize;
+                if cell >= 25 {
+                    return Err(err.to_string());
+                }
+                moves.push(BookMove {
+                    pos: (cell / 5, cell % 5),
+                    games: u32_at(take(4)?),
+                    black_wins: u32_at(take(4)?),
+                    white_wins: u32_at(take(4)?),
+                });
+            }
+            entries.insert(key, moves);
+        }
+        Ok(Book { entries })
+    }
+}
+
+/// 内置引擎自我对弈一局，开头随机落几步使对局多样化
+pub fn self_play(movetime: Duration) -> Vec<GameAction> {
+    let random_plies = rand::random::<usize>() % (SELFPLAY_RANDOM_PLIES + 1);
+    let limits = SearchLimits {
+        movetime: Some(movetime),
+        depth: None,
+    };
+    let stop = AtomicBool::new(false);
+
+    let mut board = Board::new();
+    for ply in 0..SELFPLAY_MAX_ACTIONS {
+        if board.check_winner().is_some() {
+            break;
+        }
+        let actions = board.legal_actions();
+        if actions.is_empty() {
+            break;
+        }
+        let action = if ply < random_plies {
+            actions[rand::random::<usize>() % actions.len()].clone()
+        } else {
+            ai::search(&board, limits, &stop, |_| {})
+                .best
+                .unwrap_or_else(|| actions[0].clone())
+        };
+        // 合法动作执行失败时行动方判负，对局结束
+        if board.apply_action(&action).is_err() {
+            break;
+        }
+    }
+    board.game_record
+}
+
+/// 命令行入口：`wudaofang book <子命令> ...`
+#[cfg(feature = "cli")]
+pub fn run_book(args: &[String]) -> Result<(), String> {
+    use crate::db;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    let usage = tr("book.usage");
+    let Some((command, args)) = args.split_first() else {
+        return Err(usage.to_string());
+    };
+    let (mut options, paths) = db::parse_options(args, &[])?;
+    let mut number = |name: &str, default: usize| -> Result<usize, String> {
+        options
+            .remove(name)
+            .map_or(Ok(default), |v| v.parse().map_err(|_| trf("arg.integer", &[&name])))
+    };
+
+    match command.as_str() {
+        "build" => {
+            let depth = number("depth", BOOK_DEPTH)?;
+            let min_games = number("min-games", 1)? as u32;
+            let selfplay = number("selfplay", 0)?;
+            let movetime = Duration::from_millis(number("movetime", 20)? as u64);
+            let out = options.remove("out").map_or_else(default_path, PathBuf::from);
+
+            let mut records = Vec::new();
+            let mut files = Vec::new();
+            for path in &paths {
+                db::collect_files(Path::new(path), &mut files);
+            }
+            for file in &files {
+                match db::read_record(file) {
+                    Ok(record) => records.push(record),
+                    Err(e) => eprintln!("{}", trf("import.skipped", &[&file.display(), &e])),
+                }
+            }
+            if let Some(path) = options.remove("db") {
+                let conn = db::open(Path::new(&path))?;
+                records.extend(db::all_records(&conn)?);
+            }
+
+            // 自我对弈在所有核心上并行进行
+            if selfplay > 0 {
+                let done = AtomicUsize::new(0);
+                let games = Mutex::new(Vec::new());
+                let threads = thread::available_parallelism().map_or(1, |n| n.get()).min(selfplay);
+                thread::scope(|scope| {
+                    for _ in 0..threads {
+                        scope.spawn(|| {
+                            while done.fetch_add(1, Ordering::Relaxed) < selfplay {
+                                let record = self_play(movetime);
+                                let mut games = games.lock().unwrap();
+                                games.push(record);
+                                eprint!("\r{}", trf("book.selfplay_progress", &[&games.len(), &selfplay]));
+                            }
+                        });
+                    }
+                });
+                eprintln!();
+                records.extend(games.into_inner().unwrap());
+            }
+
+            // 同一局可能既在棋谱目录中又在数据库中，只收录一次
+            let mut seen = HashSet::new();
+            records.retain(|record| seen.insert(fnv1a(&serde_json::to_string(record).unwrap_or_default())));
+
+            let mut book = Book::default();
+            let mut used = 0;
+            for record in &records {
+                match db::summarize(record) {
+                    Ok(summary) => {
+                        book.add_game(record, summary.winner, depth);
+                        used += 1;
+                    }
+                    Err(e) => eprintln!("{}", trf("book.skipped_invalid", &[&e])),
+                }
+            }
+            book.prune(min_games);
+            book.save(&out)?;
+            println!("{}", trf("book.built", &[&used, &book.len(), &out.display()]));
+        }
+        "show" => {
+            let path = options.remove("book").map_or_else(default_path, PathBuf::from);
+            let book = Book::load(&path)?;
+            let args = if paths.is_empty() { vec!["startpos".to_string()] } else { paths };
+            let board = db::position_from_args(&args, options.remove("step"))?;
+
+            let moves = book.moves(&board);
+            println!("{}", trf("position.label", &[&board.to_position_string()]));
+            if moves.is_empty() {
+                println!("{}", tr("book.not_found"));
+                return Ok(());
+            }
+            for m in &moves {
+                let percent = |n: u32| format!("{:>5.1}", n as f32 * 100.0 / m.games as f32);
+                println!(
+                    "{}",
+                    trf(
+                        "book.move_line",
+                        &[&m.pos.0, &m.pos.1, &format!("{:>6}", m.games), &percent(m.black_wins), &percent(m.white_wins)]
+                    )
+                );
+            }
+            if let Some(action) = book.best_move(&board) {
+                println!("{}", trf("book.engine_choice", &[&action.notation()]));
+            }
+        }
+        _ => return Err(usage.to_string()),
+    }
+    Ok(())
+}
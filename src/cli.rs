@@ -0,0 +1,530 @@
+// 命令行前端
+//
+// 用法:
+//   play [--ai black|white|both] [--movetime 毫秒] [--save 文件]   终端对局，默认双方都由人操作
+//   replay <文件> [--step N] [--auto 毫秒]                         逐步查看棋谱
+//   validate <文件>...                                             检查棋谱是否合法并给出结果
+//   convert <输入> <输出> [--to record|snapshot|text]              转换棋谱格式
+//   analyze <文件> [--movetime 毫秒] [--depth N]                   逐步分析棋谱，标出疑问手和坏手
+//   perft [--depth N] [--divide] [局面字符串 | startpos [moves ...]] 统计动作树的叶子数，用于检查走法生成
+//
+// 文件可以是棋谱、局面快照，或者文本格式：一行 "position startpos moves ..."（与引擎协议相同）。
+
+use crate::ai::{self, SearchLimits};
+use crate::i18n::{tr, trf};
+use crate::savegame::{self, SaveKind};
+use crate::{Board, GameAction, GameEvent, GamePhase, GameReplayer, Player, RULES, book, db, format_score, parse_move, protocol, server, tui};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 引擎每步的默认思考时间
+const PLAY_MOVETIME: u64 = 1000;
+// 逐步分析的默认思考时间
+const ANALYZE_MOVETIME: u64 = 300;
+// 比最佳动作差多少分记为疑问手、坏手（一子为 100 分）
+const DUBIOUS_LOSS: i32 = 100;
+const BLUNDER_LOSS: i32 = 300;
+
+/// 执行子命令（`args[0]` 为子命令名）并返回进程退出码，不是子命令时返回 None
+pub fn run_command(args: &[String]) -> Option<i32> {
+    let result = match args.first().map(String::as_str)? {
+        "engine" => {
+            // 默认使用本地数据目录下的开局库（如果有），--book 指定其他文件，--no-book 不使用
+            let book = if args.iter().any(|a| a == "--no-book") {
+                None
+            } else {
+                match args.iter().position(|a| a == "--book") {
+                    Some(i) => match args.get(i + 1).map(|p| book::Book::load(Path::new(p))) {
+                        Some(Ok(book)) => Some(book),
+                        Some(Err(e)) => {
+                            eprintln!("{}", e);
+                            None
+                        }
+                        None => {
+                            eprintln!("{}", tr("arg.book_path"));
+                            None
+                        }
+                    },
+                    None => book::Book::load(&book::default_path()).ok(),
+                }
+            };
+            protocol::run_engine(io::stdin().lock(), io::stdout(), book);
+            Ok(())
+        }
+        "arena" => protocol::run_arena(&args[1..]),
+        "server" => server::run_server(&args[1..]),
+        command @ ("play" | "replay" | "validate" | "convert" | "analyze" | "perft") => {
+            if let Err(e) = run(command, &args[1..]) {
+                eprintln!("{}", e);
+                return Some(1);
+            }
+            Ok(())
+        }
+        "tui" => tui::run_tui(&args[1..]),
+        "book" => book::run_book(&args[1..]),
+        "db" => db::run_db(&args[1..]),
+        _ => return None,
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+    }
+    Some(0)
+}
+
+/// 执行 play、replay、validate 等子命令，command 为子命令名，args 为其后的参数
+pub fn run(command: &str, args: &[String]) -> Result<(), String> {
+    let (mut options, paths) = db::parse_options(args, &["divide"])?;
+    let mut number = |name: &str| -> Result<Option<u64>, String> {
+        options
+            .remove(name)
+            .map(|v| v.parse().map_err(|_| trf("arg.integer", &[&name])))
+            .transpose()
+    };
+
+    match command {
+        "play" => {
+            let movetime = Duration::from_millis(number("movetime")?.unwrap_or(PLAY_MOVETIME));
+            let ai = match options.remove("ai").as_deref() {
+                None | Some("none") => vec![],
+                Some("black") => vec![Player::Black],
+                Some("white") => vec![Player::White],
+                Some("both") => vec![Player::Black, Player::White],
+                Some(other) => return Err(trf("cli.bad_ai", &[&other])),
+            };
+            play(&ai, movetime, options.remove("save"))
+        }
+        "replay" => {
+            let [path] = paths.as_slice() else {
+                return Err(tr("cli.usage_replay").to_string());
+            };
+            let step = number("step")?.unwrap_or(0) as usize;
+            let auto = number("auto")?.map(Duration::from_millis);
+            replay(&load(Path::new(path))?, step, auto)
+        }
+        "validate" => {
+            if paths.is_empty() {
+                return Err(tr("cli.usage_validate").to_string());
+            }
+            validate(&paths)
+        }
+        "convert" => {
+            let [input, output] = paths.as_slice() else {
+                return Err(tr("cli.usage_convert").to_string());
+            };
+            let board = load(Path::new(input))?;
+            let output = Path::new(output);
+            match options.remove("to").as_deref() {
+                None | Some("record") => savegame::save(output, &board, SaveKind::Record)?,
+                Some("snapshot") => savegame::save(output, &board, SaveKind::Snapshot)?,
+                Some("text") => fs::write(output, format!("{}\n", to_text(&board)))
+                    .map_err(|e| trf("io.write_failed", &[&output.display(), &e]))?,
+                Some(other) => return Err(trf("cli.bad_format", &[&other])),
+            }
+            println!("{}", trf("cli.converted", &[&input, &output.display()]));
+            Ok(())
+        }
+        "analyze" => {
+            let [path] = paths.as_slice() else {
+                return Err(tr("cli.usage_analyze").to_string());
+            };
+            let limits = SearchLimits {
+                depth: number("depth")?.map(|d| d as u32),
+                movetime: Some(Duration::from_millis(number("movetime")?.unwrap_or(ANALYZE_MOVETIME))),
+            };
+            analyze(&load(Path::new(path))?, limits)
+        }
+        "perft" => {
+            let depth = number("depth")?.unwrap_or(3) as u32;
+            let divide = options.contains_key("divide");
+            let args = if paths.is_empty() { vec!["startpos".to_string()] } else { paths };
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let board = protocol::parse_position(&args)?;
+            run_perft(&board, depth, divide);
+            Ok(())
+        }
+        _ => Err(trf("engine.unknown_command", &[&command])),
+    }
+}
+
+// 读取棋谱、局面快照或文本格式的对局
+fn load(path: &Path) -> Result<Board, String> {
+    let text = fs::read_to_string(path).map_err(|e| trf("io.read_failed", &[&path.display(), &e]))?;
+    let text = text.trim();
+    if text.starts_with('[') || text.starts_with('{') {
+        return savegame::load(path).map(|(board, _)| board);
+    }
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let args = tokens.strip_prefix(&["position"]).unwrap_or(&tokens);
+    protocol::parse_position(args).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// 文本格式：能从开局重放出来的对局写成 startpos moves ...，否则只写局面字符串
+fn to_text(board: &Board) -> String {
+    let replayed = savegame::board_from_record(&board.game_record);
+    if replayed.is_ok_and(|b| b.to_position_string() == board.to_position_string()) {
+        let moves: Vec<String> = board.game_record.iter().filter(|a| a.is_play()).map(GameAction::notation).collect();
+        if moves.is_empty() {
+            "position startpos".to_string()
+        } else {
+            format!("position startpos moves {}", moves.join(" "))
+        }
+    } else {
+        format!("position {}", board.to_position_string())
+    }
+}
+
+fn search(board: &Board, movetime: Duration) -> Option<GameAction> {
+    let limits = SearchLimits {
+        movetime: Some(movetime),
+        depth: None,
+    };
+    ai::search(board, limits, &AtomicBool::new(false), |_| {}).best
+}
+
+// 终端对局，ai 中的一方由内置引擎（以及开局库）走棋
+fn play(ai: &[Player], movetime: Duration, save: Option<String>) -> Result<(), String> {
+    println!("\n{}", tr("cli.welcome"));
+    println!("{}", tr("cli.rules_title"));
+    for (i, rule) in RULES.iter().enumerate() {
+        println!("{}. {}", i + 1, tr(rule));
+    }
+    println!("{}", tr("cli.play_keys"));
+    println!("================================\n");
+
+    let book = book::Book::load(&book::default_path()).ok();
+    let mut board = Board::new();
+
+    loop {
+        print_board(&board);
+
+        if let Some(winner) = board.check_winner() {
+            println!("\n{}", tr("cli.game_over"));
+            println!("{}", trf("cli.winner", &[&winner]));
+            break;
+        }
+        if board.legal_actions().is_empty() {
+            println!("\n{}", trf("cli.no_moves", &[&board.current_player]));
+            break;
+        }
+
+        let player = board.current_player;
+        let action = if ai.contains(&player) {
+            let action = book
+                .as_ref()
+                .and_then(|b| b.best_move(&board))
+                .or_else(|| search(&board, movetime))
+                .ok_or(tr("cli.engine_no_action"))?;
+            println!("{}", trf("cli.engine_move", &[&player, &action.notation()]));
+            action
+        } else {
+            print_game_status(&board);
+            let input = read_input(&trf("cli.input_prompt", &[&player]));
+            match input.as_str() {
+                "f" => {
+                    println!("{}", trf("cli.resigned", &[&player]));
+                    board.record_resign(player);
+                    continue;
+                }
+                "q" => break,
+                "?" => {
+                    if let Some(action) = search(&board, Duration::from_millis(800)) {
+                        let explanation = ai::explain(&board, &action);
+                        println!("{}", trf("cli.hint", &[&action.notation(), &explanation]));
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            // 走子阶段也接受旧的 "原行,原列 新行,新列" 格式
+            let parsed = match parse_move(&input) {
+                Ok((from, to)) => Ok(GameAction::Move { player, from, to }),
+                Err(_) => board.parse_action(&input),
+            };
+            match parsed {
+                Ok(action) => action,
+                Err(e) => {
+                    println!("{}", trf("cli.bad_input", &[&e]));
+                    continue;
+                }
+            }
+        };
+
+        let legal = board.legal_actions().contains(&action);
+        match board.apply_action(&action) {
+            Ok(_) => {
+                for event in board.take_events() {
+                    match event {
+                        GameEvent::PatternFormed { player, pattern, .. } => {
+                            println!("{}", trf("cli.formed", &[&player, &pattern]));
+                        }
+                        GameEvent::CaptureSkipped { player, remaining } => {
+                            println!("{}", trf("capture.skipped", &[&player, &remaining]));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // 合法动作执行失败时局面已经改变，行动方判负
+            Err(e) if legal => {
+                print_board(&board);
+                println!("\n{}", trf("cli.lost_by_error", &[&e, &player.opponent()]));
+                break;
+            }
+            Err(e) => println!("{}", trf("cli.action_failed", &[&e])),
+        }
+    }
+
+    let path = match save {
+        Some(path) => path,
+        None if read_input(tr("cli.ask_save")).eq_ignore_ascii_case("y") => "wudao_game_record.json".to_string(),
+        None => return Ok(()),
+    };
+    savegame::save(Path::new(&path), &board, SaveKind::Record)?;
+    println!("{}", trf("cli.saved", &[&path]));
+    Ok(())
+}
+
+// 打印棋盘
+fn print_board(board: &Board) {
+    println!("\n  0 1 2 3 4  {}", tr("cli.col_axis"));
+    for (i, row) in board.grid.iter().enumerate() {
+        print!("{} ", i); // 行坐标
+        for cell in row {
+            print!("{} ", cell);
+        }
+        println!();
+    }
+    println!("{}", tr("cli.row_axis"));
+}
+
+// 打印游戏状态和输入提示
+fn print_game_status(board: &Board) {
+    println!("\n{}", tr("cli.status_title"));
+    println!("{}", trf("cli.status_phase", &[&board.phase]));
+    println!("{}", trf("cli.status_player", &[&board.current_player]));
+
+    match board.phase {
+        GamePhase::Placement => {
+            if board.extra_moves > 0 {
+                println!("{}", trf("cli.status_extra", &[&board.extra_moves]));
+            }
+            println!("{}", tr("cli.prompt_place"));
+        }
+        GamePhase::Capture => {
+            println!("{}", trf("cli.status_capture", &[&board.capture_remaining(board.current_player)]));
+            println!("{}", tr("cli.prompt_capture"));
+            println!("{}", tr("cli.note_capture"));
+        }
+        GamePhase::Movement => {
+            println!("{}", tr("cli.prompt_move"));
+            println!("{}", tr("cli.note_move"));
+        }
+    }
+}
+
+// 打印一步的内容：动作及其附带的奖励等记录
+fn print_step(number: usize, total: usize, step: &[GameAction]) {
+    let texts: Vec<String> = step
+        .iter()
+        .map(|a| match a {
+            GameAction::Reward { pattern, .. } => trf("record.formed", &[pattern]),
+            GameAction::Clock { elapsed_ms, .. } => {
+                trf("record.elapsed", &[&format!("{:.1}", *elapsed_ms as f32 / 1000.0)])
+            }
+            GameAction::Timeout { .. } => tr("reason.timeout").to_string(),
+            GameAction::Resign { .. } => tr("reason.resign").to_string(),
+            _ => a.notation(),
+        })
+        .collect();
+    let player = step.first().map_or(String::new(), |a| a.player().to_string());
+    println!("\n{}", trf("cli.step", &[&number, &total, &player, &texts.join("  ")]));
+}
+
+fn replay(board: &Board, step: usize, auto: Option<Duration>) -> Result<(), String> {
+    let mut replayer = GameReplayer::new(board.game_record.clone());
+    let total = replayer.steps().len();
+    let mut current = step.min(total);
+
+    loop {
+        replayer.seek(current);
+        if current > 0 {
+            print_step(current, total, replayer.steps()[current - 1]);
+        } else {
+            println!("\n{}", trf("cli.replay_start", &[&total]));
+        }
+        print_board(replayer.get_current_board());
+
+        if let Some(delay) = auto {
+            if current >= total {
+                break;
+            }
+            thread::sleep(delay);
+            current += 1;
+            continue;
+        }
+
+        let input = read_input(tr("cli.replay_prompt"));
+        match input.as_str() {
+            "q" => break,
+            "b" => current = current.saturating_sub(1),
+            "" if current >= total => break,
+            "" => current += 1,
+            n => match n.parse::<usize>() {
+                Ok(n) => current = n.min(total),
+                Err(_) => println!("{}", trf("cli.unrecognized", &[&n])),
+            },
+        }
+    }
+
+    if let Some(winner) = replayer.get_current_board().check_winner() {
+        println!("\n{}", trf("cli.winner", &[&winner]));
+    }
+    Ok(())
+}
+
+fn validate(paths: &[String]) -> Result<(), String> {
+    let mut invalid = 0;
+    for path in paths {
+        let result = load(Path::new(path)).and_then(|board| {
+            // 局面快照可能不是从开局下出来的，只检查能否读取
+            if board.game_record.is_empty() {
+                return Ok(tr("cli.valid_no_record").to_string());
+            }
+            let summary = db::summarize(&board.game_record)?;
+            let result = match summary.winner {
+                Some(winner) => trf("cli.won_by", &[&winner, &db::reason_text(summary.reason)]),
+                None => tr("db.undecided").to_string(),
+            };
+            Ok(trf("cli.summary", &[&summary.length, &result]))
+        });
+        match result {
+            Ok(text) => println!("{}: {}", path, trf("cli.valid", &[&text])),
+            Err(e) => {
+                println!("{}: {}", path, trf("cli.invalid", &[&e]));
+                invalid += 1;
+            }
+        }
+    }
+    if invalid > 0 {
+        return Err(trf("cli.invalid_count", &[&invalid]));
+    }
+    Ok(())
+}
+
+// 逐步分析：每一步前的局面搜索所有候选动作，比较实际动作和最佳动作的分值
+fn analyze(board: &Board, limits: SearchLimits) -> Result<(), String> {
+    let record: Vec<GameAction> = board.game_record.iter().filter(|a| a.is_play()).cloned().collect();
+    if record.is_empty() {
+        return Err(tr("cli.nothing_to_analyze").to_string());
+    }
+
+    let stop = AtomicBool::new(false);
+    let mut position = Board::new();
+    let mut dubious = [0, 0];
+    let mut blunders = [0, 0];
+
+    for (i, action) in record.iter().enumerate() {
+        let player = position.current_player;
+        let mut candidates = Vec::new();
+        ai::analyze(&position, limits, &stop, |_, _, c| candidates = c.to_vec());
+
+        let black = |score: i32| if player == Player::Black { score } else { -score };
+        let played = candidates.iter().find(|c| &c.action == action);
+        let (mark, best) = match (candidates.first(), played) {
+            (Some(best), Some(played)) => {
+                let loss = best.score - played.score;
+                // 由胜转和或由和转负都算坏手
+                let lost_win = ai::is_decisive(best.score) && best.score > 0 && !(ai::is_decisive(played.score) && played.score > 0);
+                let index = if player == Player::Black { 0 } else { 1 };
+                let mark = if lost_win || loss >= BLUNDER_LOSS {
+                    blunders[index] += 1;
+                    "??"
+                } else if loss >= DUBIOUS_LOSS {
+                    dubious[index] += 1;
+                    "?"
+                } else {
+                    ""
+                };
+                let best_text = if best.action == *action {
+                    String::new()
+                } else {
+                    trf("cli.best", &[&best.action.notation(), &format_score(black(best.score))])
+                };
+                (mark, format!("{:<10} {}", format_score(black(played.score)), best_text))
+            }
+            _ => ("", String::new()),
+        };
+        println!("{:>4}. {} {:<10} {:<3} {}", i + 1, player, action.notation(), mark, best);
+
+        if position.apply_action(action).is_err() {
+            break;
+        }
+    }
+
+    println!("\n{}", trf("cli.mistakes", &[&Player::Black, &dubious[0], &blunders[0]]));
+    println!("{}", trf("cli.mistakes", &[&Player::White, &dubious[1], &blunders[1]]));
+    Ok(())
+}
+
+/// 从 board 出发 depth 层动作树的叶子数。执行失败的动作（行动方判负）结束对局，
+/// 与已分胜负的局面一样不再展开
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if board.check_winner().is_some() {
+        return 0;
+    }
+    board
+        .legal_actions()
+        .iter()
+        .map(|action| {
+            let mut child = board.clone();
+            match child.apply_action(action) {
+                Ok(_) => perft(&child, depth - 1),
+                Err(_) => u64::from(depth == 1),
+            }
+        })
+        .sum()
+}
+
+fn run_perft(board: &Board, depth: u32, divide: bool) {
+    let mut root = board.clone();
+    root.game_record.clear();
+    root.set_event_recording(false);
+    let start = Instant::now();
+
+    let nodes = if divide && depth > 0 {
+        let mut total = 0;
+        for action in root.legal_actions() {
+            let mut child = root.clone();
+            let count = match child.apply_action(&action) {
+                Ok(_) => perft(&child, depth - 1),
+                Err(_) => u64::from(depth == 1),
+            };
+            println!("{:<12} {}", action.notation(), count);
+            total += count;
+        }
+        total
+    } else {
+        perft(&root, depth)
+    };
+
+    let elapsed = start.elapsed();
+    let seconds = format!("{:.3}", elapsed.as_secs_f64());
+    let speed = format!("{:.0}", nodes as f64 / elapsed.as_secs_f64().max(1e-9));
+    println!("{}", trf("cli.perft", &[&depth, &nodes, &seconds, &speed]));
+}
+
+// 读取用户输入
+fn read_input(prompt: &str) -> String {
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
@@ -0,0 +1,409 @@
+// 对局计时
+//
+// 时钟跟随 Board 的当前行棋方：额外落子和连续吃子期间行棋方不变，时钟不切换，
+// 也不加秒、不重置读秒；只有行棋方换人时才结算上一方的用时。
+
+use crate::i18n::{tr, trf};
+use crate::{Board, GameAction, Player};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// 时间控制方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeControl {
+    /// 不计时
+    Unlimited,
+    /// 包干：用完即负
+    SuddenDeath {
+        /// 基本用时
+        main: Duration,
+    },
+    /// 加秒（费舍尔制）：每回合结束后加 increment
+    Increment {
+        /// 基本用时
+        main: Duration,
+        /// 每回合结束后增加的时间
+        increment: Duration,
+    },
+    /// 读秒：基本用时用完后，每回合须在 period 内完成，超时消耗一次读秒，次数用完判负
+    ByoYomi {
+        /// 基本用时
+        main: Duration,
+        /// 每次读秒的时长
+        period: Duration,
+        /// 读秒次数
+        periods: u32,
+    },
+}
+
+impl TimeControl {
+    /// 界面上可选的预设
+    pub const PRESETS: [TimeControl; 4] = [
+        TimeControl::Unlimited,
+        TimeControl::SuddenDeath {
+            main: Duration::from_secs(10 * 60),
+        },
+        TimeControl::Increment {
+            main: Duration::from_secs(5 * 60),
+            increment: Duration::from_secs(5),
+        },
+        TimeControl::ByoYomi {
+            main: Duration::from_secs(5 * 60),
+            period: Duration::from_secs(30),
+            periods: 3,
+        },
+    ];
+
+    fn main_time(&self) -> Duration {
+        match *self {
+            TimeControl::Unlimited => Duration::ZERO,
+            TimeControl::SuddenDeath { main }
+            | TimeControl::Increment { main, .. }
+            | TimeControl::ByoYomi { main, .. } => main,
+        }
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeControl::Unlimited => write!(f, "{}", tr("clock.unlimited")),
+            TimeControl::SuddenDeath { main } => {
+                write!(f, "{}", trf("clock.sudden_death", &[&format_duration(*main)]))
+            }
+            TimeControl::Increment { main, increment } => write!(
+                f,
+                "{}",
+                trf("clock.increment", &[&format_duration(*main), &increment.as_secs()])
+            ),
+            TimeControl::ByoYomi {
+                main,
+                period,
+                periods,
+            } => write!(
+                f,
+                "{}",
+                trf("clock.byoyomi", &[&format_duration(*main), periods, &period.as_secs()])
+            ),
+        }
+    }
+}
+
+/// 某一方在某一时刻的剩余时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockState {
+    /// 基本用时的剩余时间
+    pub main: Duration,
+    /// 剩余的读秒次数
+    pub periods: u32,
+    /// 读秒阶段当前这次读秒的剩余时间
+    pub period_left: Option<Duration>,
+    /// 是否已超时
+    pub flagged: bool,
+}
+
+/// 双方的对局时钟，所有方法都显式传入当前时刻，便于暂停和测试
+pub struct GameClock {
+    control: TimeControl,
+    main: HashMap<Player, Duration>,
+    periods: HashMap<Player, u32>,
+    turn: Player,
+    turn_start: Instant,
+    action_start: Instant,
+    stopped_at: Option<Instant>,
+}
+
+impl GameClock {
+    /// 创建时钟，从 now 开始为 first 计时
+    pub fn new(control: TimeControl, first: Player, now: Instant) -> Self {
+        let periods = match control {
+            TimeControl::ByoYomi { periods, .. } => periods,
+            _ => 0,
+        };
+        GameClock {
+            control,
+            main: [Player::Black, Player::White]
+                .into_iter()
+                .map(|p| (p, control.main_time()))
+                .collect(),
+            periods: [Player::Black, Player::White]
+                .into_iter()
+                .map(|p| (p, periods))
+                .collect(),
+            turn: first,
+            turn_start: now,
+            action_start: now,
+            stopped_at: None,
+        }
+    }
+
+    /// 时间控制方式
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// 是否正在计时（没有停止或暂停）
+    pub fn is_running(&self) -> bool {
+        self.stopped_at.is_none()
+    }
+
+    /// 停止计时（对局结束或暂停时）
+    pub fn stop(&mut self, now: Instant) {
+        if self.stopped_at.is_none() {
+            self.stopped_at = Some(now);
+        }
+    }
+
+    /// 从暂停中恢复计时，暂停期间的时间不计入任何一方
+    pub fn resume(&mut self, now: Instant) {
+        if let Some(at) = self.stopped_at.take() {
+            let paused = now.saturating_duration_since(at);
+            self.turn_start += paused;
+            self.action_start += paused;
+        }
+    }
+
+    fn elapsed(&self, since: Instant, now: Instant) -> Duration {
+        self.stopped_at.unwrap_or(now).saturating_duration_since(since)
+    }
+
+    // 计算 player 在本回合用时 elapsed 后的剩余时间
+    fn spend(&self, player: Player, elapsed: Duration) -> ClockState {
+        let main = self.main[&player];
+        let periods = self.periods[&player];
+
+        match self.control {
+            TimeControl::Unlimited => ClockState {
+                main,
+                periods,
+                period_left: None,
+                flagged: false,
+            },
+            TimeControl::SuddenDeath { .. } | TimeControl::Increment { .. } => ClockState {
+                main: main.saturating_sub(elapsed),
+                periods,
+                period_left: None,
+                flagged: elapsed >= main,
+            },
+            TimeControl::ByoYomi { period, .. } => {
+                if elapsed < main {
+                    return ClockState {
+                        main: main - elapsed,
+                        periods,
+                        period_left: None,
+                        flagged: false,
+                    };
+                }
+                let overflow = elapsed - main;
+                let used = (overflow.as_millis() / period.as_millis().max(1)) as u32;
+                let left = periods.saturating_sub(used);
+                ClockState {
+                    main: Duration::ZERO,
+                    periods: left,
+                    period_left: (left > 0).then(|| {
+                        period - Duration::from_millis((overflow.as_millis() % period.as_millis().max(1)) as u64)
+                    }),
+                    flagged: left == 0,
+                }
+            }
+        }
+    }
+
+    /// 某一方的当前剩余时间
+    pub fn state(&self, player: Player, now: Instant) -> ClockState {
+        let elapsed = if player == self.turn {
+            self.elapsed(self.turn_start, now)
+        } else {
+            Duration::ZERO
+        };
+        self.spend(player, elapsed)
+    }
+
+    /// 超时的一方
+    pub fn flagged(&self, now: Instant) -> Option<Player> {
+        self.state(self.turn, now).flagged.then_some(self.turn)
+    }
+
+    /// 每个动作执行后调用：把该动作的用时写入棋谱，行棋方换人时结算并切换时钟
+    pub fn on_action(&mut self, board: &mut Board, actor: Player, now: Instant) {
+        if !self.is_running() {
+            return;
+        }
+
+        let elapsed = self.elapsed(self.action_start, now);
+        board.record_action(GameAction::Clock {
+            player: actor,
+            elapsed_ms: elapsed.as_millis() as u64,
+        });
+        self.action_start = now;
+
+        if board.current_player != self.turn {
+            let turn_elapsed = self.elapsed(self.turn_start, now);
+            let state = self.spend(self.turn, turn_elapsed);
+            let mut main = state.main;
+            if let TimeControl::Increment { increment, .. } = self.control {
+                main += increment;
+            }
+            self.main.insert(self.turn, main);
+            self.periods.insert(self.turn, state.periods);
+
+            self.turn = board.current_player;
+            self.turn_start = now;
+        }
+    }
+}
+
+/// 格式化为 分:秒
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+impl ClockState {
+    /// 时钟显示文本
+    pub fn display(&self, control: TimeControl) -> String {
+        match (control, self.period_left) {
+            (TimeControl::Unlimited, _) => "--:--".to_string(),
+            (_, Some(left)) => trf("clock.in_byoyomi", &[&format_duration(left), &self.periods]),
+            (TimeControl::ByoYomi { .. }, None) => {
+                trf("clock.main_and_periods", &[&format_duration(self.main), &self.periods])
+            }
+            _ => format_duration(self.main),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    // 黑方落下一子，行棋方换成白方
+    fn black_moves(clock: &mut GameClock, board: &mut Board, now: Instant) {
+        let action = board.legal_actions()[0].clone();
+        board.apply_action(&action).unwrap();
+        assert_eq!(board.current_player, Player::White);
+        clock.on_action(board, Player::Black, now);
+    }
+
+    #[test]
+    fn sudden_death_flags_when_main_time_runs_out() {
+        let t0 = Instant::now();
+        let clock = GameClock::new(TimeControl::SuddenDeath { main: secs(60) }, Player::Black, t0);
+
+        assert_eq!(clock.state(Player::Black, t0 + secs(25)).main, secs(35));
+        assert_eq!(clock.state(Player::White, t0 + secs(25)).main, secs(60));
+        assert_eq!(clock.flagged(t0 + secs(59)), None);
+        assert_eq!(clock.flagged(t0 + secs(60)), Some(Player::Black));
+        let state = clock.state(Player::Black, t0 + secs(90));
+        assert_eq!(state.main, Duration::ZERO);
+        assert!(state.flagged);
+    }
+
+    #[test]
+    fn increment_is_added_when_the_turn_passes() {
+        let t0 = Instant::now();
+        let control = TimeControl::Increment {
+            main: secs(60),
+            increment: secs(5),
+        };
+        let mut clock = GameClock::new(control, Player::Black, t0);
+        let mut board = Board::new();
+
+        black_moves(&mut clock, &mut board, t0 + secs(10));
+        assert_eq!(
+            board.game_record.last(),
+            Some(&GameAction::Clock {
+                player: Player::Black,
+                elapsed_ms: 10_000
+            })
+        );
+        assert_eq!(clock.state(Player::Black, t0 + secs(30)).main, secs(55));
+        assert_eq!(clock.state(Player::White, t0 + secs(30)).main, secs(40));
+    }
+
+    #[test]
+    fn byoyomi_consumes_periods_after_main_time() {
+        let t0 = Instant::now();
+        let control = TimeControl::ByoYomi {
+            main: secs(10),
+            period: secs(30),
+            periods: 3,
+        };
+        let clock = GameClock::new(control, Player::Black, t0);
+
+        let state = clock.spend(Player::Black, secs(5));
+        assert_eq!((state.main, state.periods, state.period_left), (secs(5), 3, None));
+
+        let state = clock.spend(Player::Black, secs(10));
+        assert_eq!((state.main, state.periods, state.period_left), (Duration::ZERO, 3, Some(secs(30))));
+
+        // 超出基本用时 35 秒：用掉一次读秒，当前读秒还剩 25 秒
+        let state = clock.spend(Player::Black, secs(45));
+        assert_eq!((state.periods, state.period_left, state.flagged), (2, Some(secs(25)), false));
+
+        // 差一毫秒用完最后一次读秒
+        let state = clock.spend(Player::Black, secs(100) - Duration::from_millis(1));
+        assert_eq!(state.periods, 1);
+        assert_eq!(state.period_left, Some(Duration::from_millis(1)));
+        assert!(!state.flagged);
+
+        let state = clock.spend(Player::Black, secs(100));
+        assert_eq!((state.periods, state.period_left, state.flagged), (0, None, true));
+    }
+
+    #[test]
+    fn byoyomi_period_resets_each_turn() {
+        let t0 = Instant::now();
+        let control = TimeControl::ByoYomi {
+            main: secs(10),
+            period: secs(30),
+            periods: 3,
+        };
+        let mut clock = GameClock::new(control, Player::Black, t0);
+        let mut board = Board::new();
+
+        black_moves(&mut clock, &mut board, t0 + secs(45));
+        let state = clock.state(Player::Black, t0 + secs(50));
+        assert_eq!((state.main, state.periods, state.period_left), (Duration::ZERO, 2, Some(secs(30))));
+
+        // 再次轮到黑方时从完整的一次读秒开始计时
+        let action = board.legal_actions()[0].clone();
+        board.apply_action(&action).unwrap();
+        clock.on_action(&mut board, Player::White, t0 + secs(60));
+        let state = clock.state(Player::Black, t0 + secs(80));
+        assert_eq!((state.periods, state.period_left), (2, Some(secs(10))));
+    }
+
+    #[test]
+    fn paused_time_is_not_charged() {
+        let t0 = Instant::now();
+        let mut clock = GameClock::new(TimeControl::SuddenDeath { main: secs(60) }, Player::Black, t0);
+        let mut board = Board::new();
+
+        clock.stop(t0 + secs(10));
+        assert!(!clock.is_running());
+        assert_eq!(clock.state(Player::Black, t0 + secs(40)).main, secs(50));
+        assert_eq!(clock.flagged(t0 + secs(600)), None);
+
+        clock.resume(t0 + secs(40));
+        assert!(clock.is_running());
+        assert_eq!(clock.state(Player::Black, t0 + secs(50)).main, secs(40));
+
+        black_moves(&mut clock, &mut board, t0 + secs(50));
+        assert_eq!(
+            board.game_record.last(),
+            Some(&GameAction::Clock {
+                player: Player::Black,
+                elapsed_ms: 20_000
+            })
+        );
+        assert_eq!(clock.state(Player::Black, t0 + secs(70)).main, secs(40));
+        assert_eq!(clock.state(Player::White, t0 + secs(70)).main, secs(40));
+    }
+}
@@ -0,0 +1,817 @@
+// 本地对局数据库
+//
+// 把棋谱文件导入 SQLite，导入时重放棋谱得出胜负、结束方式、步数、到达的阶段和形成的奖励模式，
+// 之后可以按这些字段查询并统计。
+//
+// 用法:
+//   db import [--db 文件] [--black 名字] [--white 名字] [棋谱文件或目录...]   不指定路径时导入归档目录
+//   db query  [--db 文件] [--player 名字] [--winner black|white|none] [--reason 结束方式]
+//             [--pattern square|tri|tetra|row|col|dragon] [--min-length N] [--max-length N]
+//             [--phase placement|capture|movement] [--limit N]
+//   db stats  [--db 文件]
+//   db position [--db 文件] [--step N] <局面字符串 | startpos [moves ...] | 棋谱文件>
+
+use crate::i18n::{tr, trf};
+use crate::symmetry::{inverse, transform_action};
+use crate::{Board, GameAction, GamePhase, GameReplayer, Player, RewardPattern, autosave, fnv1a, protocol, savegame};
+use rusqlite::{Connection, params, params_from_iter};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS games (
+    id      INTEGER PRIMARY KEY,
+    hash    TEXT NOT NULL UNIQUE,   -- 棋谱内容哈希，用于去重
+    path    TEXT NOT NULL,
+    black   TEXT NOT NULL DEFAULT '',
+    white   TEXT NOT NULL DEFAULT '',
+    winner  TEXT,                   -- black / white，未分胜负为 NULL
+    reason  TEXT NOT NULL,          -- 结束方式，见 reason_text
+    length  INTEGER NOT NULL,       -- 步数（落子、吃子、移动）
+    phase   TEXT NOT NULL,          -- 到达的最后阶段
+    record  TEXT NOT NULL           -- 棋谱 JSON
+);
+CREATE TABLE IF NOT EXISTS patterns (
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    step    INTEGER NOT NULL,
+    player  TEXT NOT NULL,
+    kind    TEXT NOT NULL,          -- square / tri / tetra / row / col / dragon
+    pattern TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS patterns_game ON patterns(game_id);
+CREATE INDEX IF NOT EXISTS patterns_kind ON patterns(kind);
+-- 对局经过的每个局面（规范形式），用于查找对称的局面
+CREATE TABLE IF NOT EXISTS positions (
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    step    INTEGER NOT NULL,       -- 该局面之前已走的步数
+    key     TEXT NOT NULL,          -- 规范局面字符串
+    sym     INTEGER NOT NULL,       -- 把对局中的局面变换为规范形式所用的变换
+    next    TEXT                    -- 对局中接下来的动作（JSON，对局原方向），终局为 NULL
+);
+CREATE INDEX IF NOT EXISTS positions_key ON positions(key);
+";
+
+/// 结束方式（pieces、no_moves、blocking、timeout、resign、unfinished）的说明，未知的结束方式原样显示
+pub fn reason_text(reason: &str) -> &str {
+    match reason {
+        "pieces" => tr("reason.pieces"),
+        "no_moves" => tr("reason.no_moves"),
+        "blocking" => tr("reason.blocking"),
+        "timeout" => tr("reason.timeout"),
+        "resign" => tr("reason.resign"),
+        "unfinished" => tr("reason.unfinished"),
+        _ => reason,
+    }
+}
+
+fn phase_rank(phase: &GamePhase) -> u8 {
+    match phase {
+        GamePhase::Placement => 0,
+        GamePhase::Capture => 1,
+        GamePhase::Movement => 2,
+    }
+}
+
+/// 重放棋谱得到的对局概要
+#[derive(Debug, Clone)]
+pub struct GameSummary {
+    /// 胜方，未分胜负时为 None
+    pub winner: Option<Player>,
+    /// 结束方式，见 reason_text
+    pub reason: &'static str,
+    /// 步数（落子、吃子、移动）
+    pub length: usize,
+    /// 到达的最后阶段
+    pub phase: GamePhase,
+    /// (第几步, 玩家, 模式)
+    pub patterns: Vec<(usize, Player, RewardPattern)>,
+    /// 对局经过的每个局面
+    pub positions: Vec<PositionEntry>,
+}
+
+/// 对局经过的一个局面
+#[derive(Debug, Clone)]
+pub struct PositionEntry {
+    /// 该局面之前已走的步数
+    pub step: usize,
+    /// 规范局面字符串
+    pub key: String,
+    /// 把该局面变换为规范形式所用的变换
+    pub sym: usize,
+    /// 对局中接下来的动作（对局原方向），终局为 None
+    pub next: Option<GameAction>,
+}
+
+impl PositionEntry {
+    fn new(board: &Board, step: usize, next: Option<GameAction>) -> Self {
+        let (key, sym) = board.canonical();
+        PositionEntry { step, key, sym, next }
+    }
+}
+
+/// 重放棋谱，统计结果、奖励模式和经过的局面
+pub fn summarize(record: &[GameAction]) -> Result<GameSummary, String> {
+    let mut board = Board::new();
+    let mut summary = GameSummary {
+        winner: None,
+        reason: "unfinished",
+        length: 0,
+        phase: GamePhase::Placement,
+        patterns: Vec::new(),
+        positions: Vec::new(),
+    };
+
+    for (i, action) in record.iter().enumerate() {
+        match action {
+            // 奖励记录由重放重新生成
+            GameAction::Reward { .. } | GameAction::Clock { .. } => {}
+            GameAction::Timeout { player } | GameAction::Resign { player } => {
+                summary.winner = Some(player.opponent());
+                summary.reason = if matches!(action, GameAction::Timeout { .. }) {
+                    "timeout"
+                } else {
+                    "resign"
+                };
+                summary.positions.push(PositionEntry::new(&board, summary.length, None));
+                return Ok(summary);
+            }
+            _ => {
+                if board.check_winner().is_some() || !board.legal_actions().contains(action) {
+                    return Err(trf("record.invalid_entry", &[&(i + 1), &action.notation()]));
+                }
+                summary
+                    .positions
+                    .push(PositionEntry::new(&board, summary.length, Some(action.clone())));
+                let mover = board.current_player;
+                let start = board.game_record.len();
+                let result = board.apply_action(action);
+                summary.length += 1;
+
+                for entry in &board.game_record[start..] {
+                    if let GameAction::Reward { player, pattern } = entry {
+                        summary.patterns.push((summary.length, *player, pattern.clone()));
+                    }
+                }
+                // 走子阶段可能回到吃棋阶段，只记录到达过的最后一个阶段
+                if phase_rank(&board.phase) > phase_rank(&summary.phase) {
+                    summary.phase = board.phase.clone();
+                }
+
+                // 合法动作执行失败：该动作使对方无法走棋，行动方判负
+                if result.is_err() {
+                    summary.winner = Some(mover.opponent());
+                    summary.reason = "blocking";
+                    summary.positions.push(PositionEntry::new(&board, summary.length, None));
+                    return Ok(summary);
+                }
+            }
+        }
+    }
+
+    summary.positions.push(PositionEntry::new(&board, summary.length, None));
+    if let Some(winner) = board.check_winner() {
+        summary.winner = Some(winner);
+        summary.reason = if board.player_pieces(winner.opponent()).len() < 3 {
+            "pieces"
+        } else {
+            "no_moves"
+        };
+    }
+    Ok(summary)
+}
+
+/// 打开数据库，不存在时创建
+pub fn open(path: &Path) -> Result<Connection, String> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| trf("io.create_failed", &[&dir.display(), &e]))?;
+    }
+    let conn = Connection::open(path).map_err(|e| trf("db.open_failed", &[&path.display(), &e]))?;
+    conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+    index_positions(&conn)?;
+    Ok(conn)
+}
+
+fn insert_positions(conn: &Connection, game_id: i64, positions: &[PositionEntry]) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare_cached("INSERT INTO positions (game_id, step, key, sym, next) VALUES (?1, ?2, ?3, ?4, ?5)")
+        .map_err(|e| e.to_string())?;
+    for entry in positions {
+        let next = entry.next.as_ref().map(serde_json::to_string).transpose().map_err(|e| e.to_string())?;
+        stmt.execute(params![game_id, entry.step as i64, entry.key, entry.sym as i64, next])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// 为旧版本数据库中还没有局面索引的对局补建索引
+fn index_positions(conn: &Connection) -> Result<(), String> {
+    let games: Vec<(i64, String)> = {
+        let mut stmt = conn
+            .prepare("SELECT id, record FROM games WHERE id NOT IN (SELECT DISTINCT game_id FROM positions)")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    if games.is_empty() {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    for (id, json) in games {
+        let record: Vec<GameAction> = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        insert_positions(&tx, id, &summarize(&record)?.positions)?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// 读取棋谱文件，支持棋谱和局面快照
+pub fn read_record(path: &Path) -> Result<Vec<GameAction>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    let record = if value.is_array() {
+        value
+    } else {
+        value.get("record").cloned().ok_or(tr("db.not_a_record"))?
+    };
+    serde_json::from_value(record).map_err(|e| e.to_string())
+}
+
+fn record_hash(record: &[GameAction]) -> String {
+    let json = serde_json::to_string(record).unwrap_or_default();
+    format!("{:016x}", fnv1a(&json))
+}
+
+/// 导入一局，已经导入过的棋谱返回 Ok(false)
+pub fn import_record(
+    conn: &Connection,
+    path: &Path,
+    record: &[GameAction],
+    black: &str,
+    white: &str,
+) -> Result<bool, String> {
+    let summary = summarize(record)?;
+    let json = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    // 一局的所有记录在同一个事务中写入，既保证完整也快得多
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let inserted = tx
+        .execute(
+            "INSERT OR IGNORE INTO games (hash, path, black, white, winner, reason, length, phase, record)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                record_hash(record),
+                path.to_string_lossy(),
+                black,
+                white,
+                summary.winner.map(Player::id),
+                summary.reason,
+                summary.length as i64,
+                summary.phase.id(),
+                json,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    if inserted == 0 {
+        return Ok(false);
+    }
+
+    let game_id = tx.last_insert_rowid();
+    for (step, player, pattern) in &summary.patterns {
+        tx.execute(
+            "INSERT INTO patterns (game_id, step, player, kind, pattern) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![game_id, *step as i64, player.id(), pattern.kind(), pattern.id()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    insert_positions(&tx, game_id, &summary.positions)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 数据库中的所有棋谱
+pub fn all_records(conn: &Connection) -> Result<Vec<Vec<GameAction>>, String> {
+    let mut stmt = conn.prepare("SELECT record FROM games ORDER BY id").map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    rows.map(|json| {
+        let json = json.map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    })
+    .collect()
+}
+
+/// 收集目录下（含子目录）的所有 .json 文件
+pub fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+        let mut children: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        children.sort();
+        for child in children {
+            if child.is_dir() || child.extension().is_some_and(|ext| ext == "json") {
+                collect_files(&child, files);
+            }
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+}
+
+/// 查询条件
+#[derive(Debug, Default)]
+pub struct GameQuery {
+    /// 黑方或白方的名字
+    pub player: Option<String>,
+    /// black、white 或 none（未分胜负）
+    pub winner: Option<String>,
+    /// 结束方式
+    pub reason: Option<String>,
+    /// 出现过的奖励模式种类
+    pub pattern: Option<String>,
+    /// 最少步数
+    pub min_length: Option<i64>,
+    /// 最多步数
+    pub max_length: Option<i64>,
+    /// 到达的最后阶段
+    pub phase: Option<String>,
+    /// 最多返回多少局
+    pub limit: Option<i64>,
+}
+
+/// 查询到的一局对局
+#[derive(Debug, Clone)]
+pub struct GameRow {
+    /// 数据库中的编号
+    pub id: i64,
+    /// 导入时的棋谱文件路径
+    pub path: String,
+    /// 黑方名字
+    pub black: String,
+    /// 白方名字
+    pub white: String,
+    /// black 或 white，未分胜负为 None
+    pub winner: Option<String>,
+    /// 结束方式
+    pub reason: String,
+    /// 步数
+    pub length: i64,
+    /// 到达的最后阶段
+    pub phase: String,
+}
+
+/// 按条件查询对局
+pub fn query_games(conn: &Connection, query: &GameQuery) -> Result<Vec<GameRow>, String> {
+    let mut sql = String::from(
+        "SELECT id, path, black, white, winner, reason, length, phase FROM games WHERE 1 = 1",
+    );
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+    // 子句中的每个 ? 都绑定到同一个参数
+    let mut bind = |sql: &mut String, clause: &str, value: rusqlite::types::Value| {
+        values.push(value);
+        sql.push_str(&clause.replace('?', &format!("?{}", values.len())));
+    };
+
+    if let Some(player) = &query.player {
+        bind(&mut sql, " AND (black = ? OR white = ?)", player.clone().into());
+    }
+    match query.winner.as_deref() {
+        Some("none") => sql.push_str(" AND winner IS NULL"),
+        Some(winner) => bind(&mut sql, " AND winner = ?", winner.to_string().into()),
+        None => {}
+    }
+    if let Some(reason) = &query.reason {
+        bind(&mut sql, " AND reason = ?", reason.clone().into());
+    }
+    if let Some(kind) = &query.pattern {
+        bind(
+            &mut sql,
+            " AND EXISTS (SELECT 1 FROM patterns p WHERE p.game_id = games.id AND p.kind = ?)",
+            kind.clone().into(),
+        );
+    }
+    if let Some(n) = query.min_length {
+        bind(&mut sql, " AND length >= ?", n.into());
+    }
+    if let Some(n) = query.max_length {
+        bind(&mut sql, " AND length <= ?", n.into());
+    }
+    if let Some(phase) = &query.phase {
+        bind(&mut sql, " AND phase = ?", phase.clone().into());
+    }
+    sql.push_str(" ORDER BY id");
+    if let Some(n) = query.limit {
+        bind(&mut sql, " LIMIT ?", n.into());
+    }
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values), |row| {
+            Ok(GameRow {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                black: row.get(2)?,
+                white: row.get(3)?,
+                winner: row.get(4)?,
+                reason: row.get(5)?,
+                length: row.get(6)?,
+                phase: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// 统计结果
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// 对局总数
+    pub games: i64,
+    /// 黑方胜局数
+    pub black_wins: i64,
+    /// 白方胜局数
+    pub white_wins: i64,
+    /// 平均步数
+    pub average_length: f64,
+    /// 各结束方式的对局数
+    pub reasons: Vec<(String, i64)>,
+    /// 到达各阶段的对局数
+    pub phases: Vec<(String, i64)>,
+    /// 每种奖励模式：(种类, 出现次数, 黑方次数, 白方次数, 出现过的对局数)
+    pub patterns: Vec<(String, i64, i64, i64, i64)>,
+}
+
+/// 统计数据库中的全部对局
+pub fn stats(conn: &Connection) -> Result<Stats, String> {
+    let err = |e: rusqlite::Error| e.to_string();
+    let mut stats = Stats::default();
+
+    (stats.games, stats.black_wins, stats.white_wins, stats.average_length) = conn
+        .query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(winner = 'black'), 0),
+                    COALESCE(SUM(winner = 'white'), 0),
+                    COALESCE(AVG(length), 0)
+             FROM games",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(err)?;
+
+    let group = |sql: &str| -> Result<Vec<(String, i64)>, String> {
+        let mut stmt = conn.prepare(sql).map_err(err)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(err)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(err)
+    };
+    stats.reasons = group("SELECT reason, COUNT(*) FROM games GROUP BY reason ORDER BY COUNT(*) DESC")?;
+    stats.phases = group("SELECT phase, COUNT(*) FROM games GROUP BY phase ORDER BY COUNT(*) DESC")?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT kind, COUNT(*), SUM(player = 'black'), SUM(player = 'white'), COUNT(DISTINCT game_id)
+             FROM patterns GROUP BY kind ORDER BY COUNT(*) DESC",
+        )
+        .map_err(err)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })
+        .map_err(err)?;
+    stats.patterns = rows.collect::<Result<Vec<_>, _>>().map_err(err)?;
+
+    Ok(stats)
+}
+
+/// 局面查找结果：某局对局经过了与查询局面相同或对称的局面
+#[derive(Debug, Clone)]
+pub struct PositionHit {
+    /// 对局编号
+    pub game_id: i64,
+    /// 棋谱文件路径
+    pub path: String,
+    /// 经过该局面时已走的步数
+    pub step: i64,
+    /// black 或 white，未分胜负为 None
+    pub winner: Option<String>,
+    /// 结束方式
+    pub reason: String,
+    /// 对局中接下来的动作，已变换到查询局面的方向
+    pub next: Option<GameAction>,
+}
+
+/// 查找经过 board 或其对称局面的对局
+pub fn find_position(conn: &Connection, board: &Board) -> Result<Vec<PositionHit>, String> {
+    let (key, sym) = board.canonical();
+    let back = inverse(sym);
+    // 查询局面自身对称时，同一着法有几种等价写法，统一取记号最小的一种
+    let stabilizer = board.stabilizer();
+    let normalize = |action: GameAction| {
+        stabilizer
+            .iter()
+            .map(|&s| transform_action(s, &action))
+            .min_by_key(GameAction::notation)
+            .unwrap_or(action)
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.game_id, g.path, p.step, p.sym, p.next, g.winner, g.reason
+             FROM positions p JOIN games g ON g.id = p.game_id
+             WHERE p.key = ?1 ORDER BY p.game_id, p.step",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([&key], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut hits = Vec::new();
+    for row in rows {
+        let (game_id, path, step, game_sym, next, winner, reason) = row.map_err(|e| e.to_string())?;
+        let next = match next {
+            Some(json) => {
+                let action: GameAction = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+                // 对局方向 -> 规范方向 -> 查询局面方向
+                let canonical = transform_action(game_sym as usize, &action);
+                Some(normalize(transform_action(back, &canonical)))
+            }
+            None => None,
+        };
+        hits.push(PositionHit {
+            game_id,
+            path,
+            step,
+            winner,
+            reason,
+            next,
+        });
+    }
+    Ok(hits)
+}
+
+/// 某个后续着法的统计，next 为 None 表示对局在该局面结束
+#[derive(Debug, Clone)]
+pub struct Continuation {
+    /// 后续着法
+    pub next: Option<GameAction>,
+    /// 出现的局数
+    pub count: usize,
+    /// 黑方胜局数
+    pub black_wins: usize,
+    /// 白方胜局数
+    pub white_wins: usize,
+}
+
+/// 按后续着法汇总查找结果，出现次数多的在前
+pub fn continuations(hits: &[PositionHit]) -> Vec<Continuation> {
+    let mut result: Vec<Continuation> = Vec::new();
+    for hit in hits {
+        let index = match result.iter().position(|c| c.next == hit.next) {
+            Some(i) => i,
+            None => {
+                result.push(Continuation {
+                    next: hit.next.clone(),
+                    count: 0,
+                    black_wins: 0,
+                    white_wins: 0,
+                });
+                result.len() - 1
+            }
+        };
+        let entry = &mut result[index];
+        entry.count += 1;
+        match hit.winner.as_deref() {
+            Some("black") => entry.black_wins += 1,
+            Some("white") => entry.white_wins += 1,
+            _ => {}
+        }
+    }
+    result.sort_by_key(|entry| Reverse(entry.count));
+    result
+}
+
+/// 命令行给出的局面：棋谱文件（可用 --step 指定第几步之后），或 position 命令的参数
+pub fn position_from_args(args: &[String], step: Option<String>) -> Result<Board, String> {
+    if let [path] = args {
+        let path = Path::new(path);
+        if path.is_file() {
+            let (board, _) = savegame::load(path)?;
+            return match step {
+                Some(step) => {
+                    let step = step.parse().map_err(|_| trf("arg.integer", &[&"step"]))?;
+                    let mut replayer = GameReplayer::new(board.game_record.clone());
+                    replayer.seek(step);
+                    Ok(replayer.get_current_board().clone())
+                }
+                None => Ok(board),
+            };
+        }
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    protocol::parse_position(&args).map_err(|e| e.to_string())
+}
+
+fn winner_text(winner: Option<&str>) -> &'static str {
+    match winner {
+        Some("black") => tr("db.black_won"),
+        Some("white") => tr("db.white_won"),
+        _ => tr("db.undecided"),
+    }
+}
+
+// 默认数据库放在本地数据目录下
+fn default_db_path() -> PathBuf {
+    autosave::data_dir().map_or(PathBuf::from("games.db"), |dir| dir.join("games.db"))
+}
+
+/// 拆分 "--名字 值" 形式的选项和其余参数，flags 中的选项不带值
+pub fn parse_options(
+    args: &[String],
+    flags: &[&str],
+) -> Result<(HashMap<String, String>, Vec<String>), String> {
+    let mut options = HashMap::new();
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.strip_prefix("--") {
+            Some(name) if flags.contains(&name) => {
+                options.insert(name.to_string(), String::new());
+            }
+            Some(name) => {
+                let value = iter.next().ok_or(trf("arg.value", &[&name]))?;
+                options.insert(name.to_string(), value.clone());
+            }
+            None => rest.push(arg.clone()),
+        }
+    }
+    Ok((options, rest))
+}
+
+fn percent(n: i64, total: i64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        n as f64 * 100.0 / total as f64
+    }
+}
+
+/// 命令行入口：`wudaofang db <子命令> ...`
+pub fn run_db(args: &[String]) -> Result<(), String> {
+    let usage = tr("db.usage");
+    let Some((command, args)) = args.split_first() else {
+        return Err(usage.to_string());
+    };
+    let (mut options, paths) = parse_options(args, &[])?;
+    let db_path = options.remove("db").map_or_else(default_db_path, PathBuf::from);
+    let conn = open(&db_path)?;
+
+    match command.as_str() {
+        "import" => {
+            let black = options.remove("black").unwrap_or_default();
+            let white = options.remove("white").unwrap_or_default();
+            let mut files = Vec::new();
+            if paths.is_empty() {
+                let dir = autosave::archive_dir().ok_or(tr("io.no_data_dir"))?;
+                collect_files(&dir, &mut files);
+            }
+            for path in &paths {
+                collect_files(Path::new(path), &mut files);
+            }
+
+            let (mut imported, mut duplicates, mut failed) = (0, 0, 0);
+            for file in &files {
+                match read_record(file).and_then(|r| import_record(&conn, file, &r, &black, &white)) {
+                    Ok(true) => imported += 1,
+                    Ok(false) => duplicates += 1,
+                    Err(e) => {
+                        eprintln!("{}", trf("import.skipped", &[&file.display(), &e]));
+                        failed += 1;
+                    }
+                }
+            }
+            println!(
+                "{}",
+                trf("db.imported", &[&imported, &duplicates, &failed, &db_path.display()])
+            );
+        }
+        "query" => {
+            let number = |options: &mut HashMap<String, String>, name: &str| -> Result<Option<i64>, String> {
+                options
+                    .remove(name)
+                    .map(|v| v.parse().map_err(|_| trf("arg.integer", &[&name])))
+                    .transpose()
+            };
+            let query = GameQuery {
+                min_length: number(&mut options, "min-length")?,
+                max_length: number(&mut options, "max-length")?,
+                limit: number(&mut options, "limit")?,
+                player: options.remove("player"),
+                winner: options.remove("winner"),
+                reason: options.remove("reason"),
+                pattern: options.remove("pattern"),
+                phase: options.remove("phase"),
+            };
+            let rows = query_games(&conn, &query)?;
+            for row in &rows {
+                let line = trf(
+                    "db.game_line",
+                    &[
+                        &winner_text(row.winner.as_deref()),
+                        &format!("{:<8}", reason_text(&row.reason)),
+                        &format!("{:>4}", row.length),
+                        &format!("{:<9}", row.phase),
+                        &row.black,
+                        &row.white,
+                    ],
+                );
+                println!("{:>5}  {}  {}", row.id, line, row.path);
+            }
+            println!("{}", trf("db.total", &[&rows.len()]));
+        }
+        "stats" => {
+            let stats = stats(&conn)?;
+            println!("{}", trf("db.stats_games", &[&stats.games]));
+            let rate = |n: i64| format!("{:.1}", percent(n, stats.games));
+            println!(
+                "{}",
+                trf(
+                    "db.stats_rates",
+                    &[
+                        &rate(stats.black_wins),
+                        &rate(stats.white_wins),
+                        &rate(stats.games - stats.black_wins - stats.white_wins),
+                    ]
+                )
+            );
+            println!("{}", trf("db.stats_length", &[&format!("{:.1}", stats.average_length)]));
+            println!("{}", tr("db.stats_reasons"));
+            for (reason, n) in &stats.reasons {
+                println!("  {:<12} {:>5} ({:.1}%)", reason_text(reason), n, percent(*n, stats.games));
+            }
+            println!("{}", tr("db.stats_phases"));
+            for (phase, n) in &stats.phases {
+                println!("  {:<12} {:>5} ({:.1}%)", phase, n, percent(*n, stats.games));
+            }
+            println!("{}", tr("db.stats_patterns"));
+            for (kind, total, black, white, games) in &stats.patterns {
+                let line = trf(
+                    "db.stats_pattern_line",
+                    &[&format!("{:>5}", total), black, white, &format!("{:.1}", percent(*games, stats.games))],
+                );
+                println!("  {:<8} {}", kind, line);
+            }
+        }
+        "position" => {
+            let board = position_from_args(&paths, options.remove("step"))?;
+            let hits = find_position(&conn, &board)?;
+            let mut games: Vec<i64> = hits.iter().map(|h| h.game_id).collect();
+            games.dedup();
+            println!("{}", trf("position.label", &[&board.to_position_string()]));
+            println!("{}", trf("db.position_hits", &[&hits.len(), &games.len()]));
+            if hits.is_empty() {
+                return Ok(());
+            }
+
+            println!("{}", tr("db.continuations"));
+            for c in continuations(&hits) {
+                let next = c.next.as_ref().map_or(tr("db.game_end").to_string(), GameAction::notation);
+                let line = trf(
+                    "db.continuation_line",
+                    &[&format!("{:>5}", c.count), &c.black_wins, &c.white_wins, &(c.count - c.black_wins - c.white_wins)],
+                );
+                println!("  {:<12} {}", next, line);
+            }
+            println!("{}", tr("db.games"));
+            for hit in &hits {
+                let next = hit.next.as_ref().map_or(tr("db.game_end").to_string(), GameAction::notation);
+                let line = trf(
+                    "db.hit_line",
+                    &[
+                        &hit.step,
+                        &format!("{:<12}", next),
+                        &winner_text(hit.winner.as_deref()),
+                        &reason_text(&hit.reason),
+                    ],
+                );
+                println!("{:>5}  {}  {}", hit.game_id, line, hit.path);
+            }
+        }
+        _ => return Err(usage.to_string()),
+    }
+    Ok(())
+}
@@ -0,0 +1,220 @@
+// 界面字体
+//
+// egui 自带的字体不含中文。启动时按以下顺序寻找可以显示中文的字体：
+// 1. --font 参数或 WUDAOFANG_FONT 环境变量指定的文件
+// 2. 当前目录或程序所在目录下的 assets/fonts/NotoSansSC.ttf
+// 3. 系统和用户字体目录中常见的中文字体（Noto Sans CJK、思源黑体、文泉驿、微软雅黑等）
+// 每个候选都检查字符映射表中是否有汉字，都找不到时使用 egui 自带字体，由调用方改用英文界面。
+
+use eframe::egui::{self, FontData, FontDefinitions, FontFamily};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use wudaofang::i18n::trf;
+
+// 常见中文字体的文件名（小写）及字体集中简体中文字体的序号，越靠前越优先
+const CJK_FONTS: [(&str, u32); 20] = [
+    ("notosanssc-regular.otf", 0),
+    ("notosanssc-regular.ttf", 0),
+    ("notosanssc.ttf", 0),
+    ("notosanscjksc-regular.otf", 0),
+    ("notosanscjk-regular.ttc", 2), // JP、KR、SC、TC、HK 依次排列
+    ("sourcehansanssc-regular.otf", 0),
+    ("sourcehansanscn-regular.otf", 0),
+    ("sourcehansans-regular.ttc", 2),
+    ("wqy-microhei.ttc", 0),
+    ("wqy-zenhei.ttc", 0),
+    ("droidsansfallbackfull.ttf", 0),
+    ("droidsansfallback.ttf", 0),
+    ("msyh.ttc", 0),
+    ("msyh.ttf", 0),
+    ("simhei.ttf", 0),
+    ("simsun.ttc", 0),
+    ("pingfang.ttc", 0),
+    ("hiragino sans gb.ttc", 0),
+    ("stheiti medium.ttc", 0),
+    ("arial unicode.ttf", 0),
+];
+
+// 字体目录只向下查找这么多层
+const MAX_DEPTH: usize = 4;
+
+// 用来判断字体能否显示中文的字符
+const PROBE: char = '棋';
+
+// 依次尝试各个候选字体，返回第一个能显示中文的；configured 为用户指定的文件
+pub fn load_cjk_font(configured: Option<&Path>) -> Option<FontData> {
+    if let Some(path) = configured {
+        match load(path, 0) {
+            Ok(font) => return Some(font),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    let mut bundled = vec![PathBuf::from("assets/fonts/NotoSansSC.ttf")];
+    if let Some(dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        bundled.push(dir.join("assets/fonts/NotoSansSC.ttf"));
+    }
+    if let Some(font) = bundled.iter().filter(|path| path.is_file()).find_map(|path| load(path, 0).ok()) {
+        return Some(font);
+    }
+    // 系统字体不合适时静默跳过
+    find_system_fonts().into_iter().find_map(|(path, index)| load(&path, index).ok())
+}
+
+// 把中文字体加到 egui 的字体列表：比例字体优先使用，等宽字体作为后备
+pub fn install(ctx: &egui::Context, font: FontData) {
+    let mut fonts = FontDefinitions::default();
+    fonts.font_data.insert("chinese".to_owned(), font);
+    fonts
+        .families
+        .entry(FontFamily::Proportional)
+        .or_default()
+        .insert(0, "chinese".to_owned());
+    fonts
+        .families
+        .entry(FontFamily::Monospace)
+        .or_default()
+        .push("chinese".to_owned());
+    ctx.set_fonts(fonts);
+}
+
+// 读取字体文件并检查其中是否有汉字
+fn load(path: &Path, index: u32) -> Result<FontData, String> {
+    let data = fs::read(path).map_err(|e| trf("io.read_failed", &[&path.display(), &e]))?;
+    if !has_glyph(&data, index, PROBE) {
+        return Err(trf("font.not_cjk", &[&path.display()]));
+    }
+    let mut font = FontData::from_owned(data);
+    font.index = index;
+    Ok(font)
+}
+
+// 系统和用户字体目录
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = dirs::font_dir() {
+        dirs.push(dir);
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".fonts"));
+    }
+    if cfg!(windows) {
+        let windir = std::env::var_os("WINDIR").map_or(PathBuf::from(r"C:\Windows"), PathBuf::from);
+        dirs.push(windir.join("Fonts"));
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+    } else {
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+    }
+    dirs
+}
+
+// 在字体目录中查找已知的中文字体，按 CJK_FONTS 中的优先顺序排列
+fn find_system_fonts() -> Vec<(PathBuf, u32)> {
+    let mut files = Vec::new();
+    for dir in font_dirs() {
+        collect_fonts(&dir, 0, &mut files);
+    }
+    let mut found: Vec<(usize, PathBuf, u32)> = files
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_lowercase();
+            let rank = CJK_FONTS.iter().position(|(known, _)| *known == name)?;
+            Some((rank, path, CJK_FONTS[rank].1))
+        })
+        .collect();
+    found.sort_by_key(|(rank, ..)| *rank);
+    found.into_iter().map(|(_, path, index)| (path, index)).collect()
+}
+
+fn collect_fonts(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                collect_fonts(&path, depth + 1, files);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// 字体的字符映射表（cmap）中是否有 ch，支持 TrueType/OpenType 字体和字体集（.ttc）
+fn has_glyph(data: &[u8], index: u32, ch: char) -> bool {
+    cmap_lookup(data, index, ch as u32).is_some_and(|glyph| glyph != 0)
+}
+
+fn cmap_lookup(data: &[u8], index: u32, code: u32) -> Option<u32> {
+    let u16_at = |pos: usize| data.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as u32);
+    let u32_at = |pos: usize| data.get(pos..pos + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+
+    // 字体集的文件头之后是各个字体的偏移
+    let font = if data.starts_with(b"ttcf") {
+        if index >= u32_at(8)? {
+            return None;
+        }
+        u32_at(12 + 4 * index as usize)? as usize
+    } else {
+        0
+    };
+
+    // 表目录：每项 16 字节，依次为标签、校验和、偏移、长度
+    let tables = u16_at(font + 4)? as usize;
+    let cmap = (0..tables)
+        .map(|i| font + 12 + 16 * i)
+        .find(|&record| data.get(record..record + 4) == Some(b"cmap".as_slice()))
+        .and_then(|record| u32_at(record + 8))? as usize;
+
+    // 编码子表：优先用完整 Unicode 的 12 号格式，其次是基本多文种平面的 4 号格式
+    let subtables: Vec<usize> = (0..u16_at(cmap + 2)? as usize)
+        .filter_map(|i| {
+            let record = cmap + 4 + 8 * i;
+            let (platform, encoding) = (u16_at(record)?, u16_at(record + 2)?);
+            let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+            unicode.then(|| u32_at(record + 4).map(|offset| cmap + offset as usize))?
+        })
+        .collect();
+    let mut glyph = None;
+    for &table in &subtables {
+        glyph = match u16_at(table)? {
+            12 => (0..u32_at(table + 12)? as usize).find_map(|i| {
+                let group = table + 16 + 12 * i;
+                let (start, end) = (u32_at(group)?, u32_at(group + 4)?);
+                (start..=end).contains(&code).then(|| u32_at(group + 8).map(|first| first + code - start))?
+            }),
+            4 if code <= 0xFFFF => {
+                let segments = u16_at(table + 6)? as usize / 2;
+                let ends = table + 14;
+                let starts = ends + 2 * segments + 2;
+                let deltas = starts + 2 * segments;
+                let ranges = deltas + 2 * segments;
+                (0..segments).find_map(|i| {
+                    let (start, end) = (u16_at(starts + 2 * i)?, u16_at(ends + 2 * i)?);
+                    if !(start..=end).contains(&code) {
+                        return None;
+                    }
+                    let delta = u16_at(deltas + 2 * i)?;
+                    let range = u16_at(ranges + 2 * i)? as usize;
+                    if range == 0 {
+                        return Some((code + delta) & 0xFFFF);
+                    }
+                    // idRangeOffset 是相对于自身位置的偏移
+                    let id = u16_at(ranges + 2 * i + range + 2 * (code - start) as usize)?;
+                    Some(if id == 0 { 0 } else { (id + delta) & 0xFFFF })
+                })
+            }
+            _ => None,
+        };
+        if glyph.is_some() {
+            break;
+        }
+    }
+    glyph
+}
@@ -0,0 +1,143 @@
+// 界面文字的多语言支持
+//
+// 所有给用户看的文字都通过消息编号从语言目录中取得，目录文件在 locales/ 下，
+// 每行一条 "编号 = 文字"，# 开头的行为注释。文字中的 {} 按顺序替换为参数。
+// 某个语言缺少的条目回退到简体中文，两者都没有时显示编号本身。
+//
+// 语言在启动时由 --lang 参数或 WUDAOFANG_LANG 环境变量选择，图形界面中也可以随时切换。
+// 引擎类型（玩家、阶段、奖励模式）另有不随语言变化的 id()，用于存档、数据库和协议。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    /// 简体中文
+    ZhCn,
+    /// 英文
+    En,
+}
+
+impl Lang {
+    /// 所有支持的语言，第一个为默认语言
+    pub const ALL: [Lang; 2] = [Lang::ZhCn, Lang::En];
+
+    /// 语言代码，用于命令行参数和环境变量
+    pub fn id(self) -> &'static str {
+        match self {
+            Lang::ZhCn => "zh-CN",
+            Lang::En => "en",
+        }
+    }
+
+    /// 语言自身的名称，切换语言的菜单中使用
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Lang::ZhCn => "简体中文",
+            Lang::En => "English",
+        }
+    }
+
+    /// 接受 zh-CN、zh_CN.UTF-8、en、en_US 等写法
+    pub fn parse(code: &str) -> Option<Lang> {
+        let code = code.trim().to_ascii_lowercase().replace('_', "-");
+        let base = code.split(['-', '.']).next().unwrap_or("");
+        match base {
+            "zh" => Some(Lang::ZhCn),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    fn catalog_source(self) -> &'static str {
+        match self {
+            Lang::ZhCn => include_str!("../locales/zh-CN.txt"),
+            Lang::En => include_str!("../locales/en.txt"),
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 当前界面语言
+pub fn lang() -> Lang {
+    Lang::ALL[CURRENT.load(Ordering::Relaxed) as usize]
+}
+
+/// 切换界面语言，之后的 tr、trf 都使用新的语言
+pub fn set_lang(lang: Lang) {
+    let index = Lang::ALL.iter().position(|&l| l == lang).unwrap_or(0);
+    CURRENT.store(index as u8, Ordering::Relaxed);
+}
+
+/// 从命令行参数中取出 --lang <代码>（可出现在任意位置），没有时使用 WUDAOFANG_LANG 环境变量
+pub fn init_from_args(args: &mut Vec<String>) -> Result<(), String> {
+    let code = match args.iter().position(|a| a == "--lang") {
+        Some(i) => {
+            if i + 1 >= args.len() {
+                return Err(tr("lang.missing").to_string());
+            }
+            let code = args.remove(i + 1);
+            args.remove(i);
+            Some(code)
+        }
+        None => std::env::var("WUDAOFANG_LANG").ok(),
+    };
+    if let Some(code) = code {
+        match Lang::parse(&code) {
+            Some(lang) => set_lang(lang),
+            None => return Err(trf("lang.unknown", &[&code, &supported()])),
+        }
+    }
+    Ok(())
+}
+
+// 支持的语言代码列表，用于错误提示
+fn supported() -> String {
+    Lang::ALL.iter().map(|l| l.id()).collect::<Vec<_>>().join(", ")
+}
+
+fn parse_catalog(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(key, text)| (key.trim(), text))
+        .collect()
+}
+
+fn catalog(lang: Lang) -> &'static HashMap<&'static str, &'static str> {
+    static CATALOGS: OnceLock<Vec<HashMap<&'static str, &'static str>>> = OnceLock::new();
+    let catalogs = CATALOGS.get_or_init(|| Lang::ALL.iter().map(|l| parse_catalog(l.catalog_source())).collect());
+    &catalogs[Lang::ALL.iter().position(|&l| l == lang).unwrap_or(0)]
+}
+
+/// 取当前语言的文字
+pub fn tr(key: &'static str) -> &'static str {
+    catalog(lang())
+        .get(key)
+        .or_else(|| catalog(Lang::ZhCn).get(key))
+        .copied()
+        // 目录中没有的编号原样显示，便于发现遗漏
+        .unwrap_or(key)
+}
+
+/// 取当前语言的文字并按顺序填入参数
+pub fn trf(key: &'static str, args: &[&dyn fmt::Display]) -> String {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut rest = tr(key);
+    while let Some(i) = rest.find("{}") {
+        result.push_str(&rest[..i]);
+        match args.next() {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        rest = &rest[i + 2..];
+    }
+    result.push_str(rest);
+    result
+}
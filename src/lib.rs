@@ -0,0 +1,2287 @@
+//! 五道方规则引擎
+//!
+//! 棋盘状态与规则（[`Board`]）、动作（[`GameAction`]）、棋谱重放（[`GameReplayer`]），
+//! 以及不依赖图形界面的搜索、存档、开局库、引擎协议和联机模块。
+//!
+//! 图形界面和命令行前端分别由 `gui`、`cli` 特性开启（默认都开启）；
+//! 只需要规则引擎的服务器、机器人和测试可以用 `default-features = false` 依赖本库，
+//! 不会引入 eframe 等图形界面依赖。
+//!
+//! ```
+//! use wudaofang::{Board, GameAction};
+//!
+//! let mut board = Board::new();
+//! let action = board.legal_actions()[0].clone();
+//! board.apply_action(&action).unwrap();
+//! assert_eq!(board.get_game_record().len(), 1);
+//! ```
+
+#![warn(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use i18n::{tr, trf};
+
+/// 搜索引擎：迭代加深的极大极小搜索 + alpha-beta 剪枝
+pub mod ai;
+/// 分析模式：后台线程持续分析给定的局面
+pub mod analysis;
+/// 未完成对局的自动存档和已结束对局的归档
+pub mod autosave;
+/// 开局库
+pub mod book;
+/// 命令行前端（终端对局、棋谱工具和各子命令的入口）
+#[cfg(feature = "cli")]
+pub mod cli;
+/// 对局计时
+pub mod clock;
+/// 本地对局数据库（SQLite）
+#[cfg(feature = "db")]
+pub mod db;
+/// 界面文字的多语言支持
+pub mod i18n;
+/// 局域网联机对战
+pub mod net;
+/// 文本引擎协议
+pub mod protocol;
+/// 存档格式
+pub mod savegame;
+/// 多房间对局服务器（JSON over HTTP）
+#[cfg(feature = "server")]
+pub mod server;
+/// 棋盘的对称变换
+pub mod symmetry;
+/// 全屏终端界面
+#[cfg(feature = "cli")]
+pub mod tui;
+
+/// 对局双方，黑方先行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Player {
+    /// 黑方
+    Black,
+    /// 白方
+    White,
+}
+
+impl Player {
+    /// 对方
+    pub fn opponent(self) -> Self {
+        match self {
+            Player::Black => Player::White,
+            Player::White => Player::Black,
+        }
+    }
+
+    fn as_char(&self) -> char {
+        match self {
+            Player::Black => '●', // 黑色实心圆
+            Player::White => '○', // 白色空心圆
+        }
+    }
+
+    fn color_name(&self) -> &'static str {
+        match self {
+            Player::Black => tr("player.black"),
+            Player::White => tr("player.white"),
+        }
+    }
+
+    /// 不随界面语言变化的标识，用于数据库和协议
+    pub fn id(self) -> &'static str {
+        match self {
+            Player::Black => "black",
+            Player::White => "white",
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.color_name())
+    }
+}
+
+/// 棋盘上的一格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cell {
+    /// 空位
+    Empty,
+    /// 有一方的棋子
+    Occupied(Player),
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Cell::Empty => write!(f, "·"), // 使用点表示空位
+            Cell::Occupied(p) => write!(f, "{}", p.as_char()),
+        }
+    }
+}
+
+/// 对局阶段：落子 → 吃棋 → 走子（走子阶段形成奖励后回到吃棋）
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub enum GamePhase {
+    /// 落子阶段
+    Placement,
+    /// 吃棋阶段
+    Capture,
+    /// 走子阶段
+    Movement,
+}
+
+impl GamePhase {
+    /// 不随界面语言变化的标识
+    pub fn id(&self) -> &'static str {
+        match self {
+            GamePhase::Placement => "placement",
+            GamePhase::Capture => "capture",
+            GamePhase::Movement => "movement",
+        }
+    }
+}
+
+impl fmt::Display for GamePhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GamePhase::Placement => write!(f, "{}", tr("phase.placement")),
+            GamePhase::Capture => write!(f, "{}", tr("phase.capture")),
+            GamePhase::Movement => write!(f, "{}", tr("phase.movement")),
+        }
+    }
+}
+
+/// 奖励模式：成方、成三斜、成四斜、成州、成龙
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RewardPattern {
+    /// 成方
+    Square {
+        /// 2x2 方块左上角的格子
+        top_left: (usize, usize),
+    },
+    /// 成三斜
+    Tri {
+        /// 0-3：左上、右上、左下、右下
+        id: usize,
+    },
+    /// 成四斜
+    Tetra {
+        /// 0-3：左上、右上、左下、右下
+        id: usize,
+    },
+    /// 成州(行)
+    Row {
+        /// 行号 0-4
+        index: usize,
+    },
+    /// 成州(列)
+    Col {
+        /// 列号 0-4
+        index: usize,
+    },
+    /// 成龙
+    Dragon {
+        /// 0 为主对角线，1 为副对角线
+        id: usize,
+    },
+}
+
+impl fmt::Display for RewardPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RewardPattern::Square { top_left: (r, c) } => write!(f, "{}", trf("pattern.square", &[r, c])),
+            RewardPattern::Tri { id } => write!(f, "{}", trf("pattern.tri", &[id])),
+            RewardPattern::Tetra { id } => write!(f, "{}", trf("pattern.tetra", &[id])),
+            RewardPattern::Row { index } => write!(f, "{}", trf("pattern.row", &[index])),
+            RewardPattern::Col { index } => write!(f, "{}", trf("pattern.col", &[index])),
+            RewardPattern::Dragon { id } => write!(f, "{}", trf("pattern.dragon", &[id])),
+        }
+    }
+}
+
+impl RewardPattern {
+    /// 棋盘上所有可能的奖励模式
+    pub fn catalog() -> Vec<RewardPattern> {
+        let mut patterns = Vec::new();
+        for r in 0..4 {
+            for c in 0..4 {
+                patterns.push(RewardPattern::Square { top_left: (r, c) });
+            }
+        }
+        patterns.extend((0..4).map(|id| RewardPattern::Tri { id }));
+        patterns.extend((0..4).map(|id| RewardPattern::Tetra { id }));
+        patterns.extend((0..5).map(|index| RewardPattern::Row { index }));
+        patterns.extend((0..5).map(|index| RewardPattern::Col { index }));
+        patterns.extend((0..2).map(|id| RewardPattern::Dragon { id }));
+        patterns
+    }
+
+    /// 模式包含的格子
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        match *self {
+            RewardPattern::Square { top_left: (r, c) } => {
+                vec![(r, c), (r, c + 1), (r + 1, c), (r + 1, c + 1)]
+            }
+            RewardPattern::Tri { id } => match id {
+                0 => vec![(0, 2), (1, 1), (2, 0)], // 左上三斜
+                1 => vec![(0, 2), (1, 3), (2, 4)], // 右上三斜
+                2 => vec![(2, 0), (3, 1), (4, 2)], // 左下三斜
+                _ => vec![(2, 4), (3, 3), (4, 2)], // 右下三斜
+            },
+            RewardPattern::Tetra { id } => match id {
+                0 => vec![(0, 1), (1, 2), (2, 3), (3, 4)], // 左上四斜
+                1 => vec![(0, 3), (1, 2), (2, 1), (3, 0)], // 右上四斜
+                2 => vec![(1, 0), (2, 1), (3, 2), (4, 3)], // 左下四斜
+                _ => vec![(1, 4), (2, 3), (3, 2), (4, 1)], // 右下四斜
+            },
+            RewardPattern::Row { index } => (0..5).map(|c| (index, c)).collect(),
+            RewardPattern::Col { index } => (0..5).map(|r| (r, index)).collect(),
+            RewardPattern::Dragon { id } => match id {
+                0 => (0..5).map(|i| (i, i)).collect(),     // 主对角线
+                _ => (0..5).map(|i| (i, 4 - i)).collect(), // 副对角线
+            },
+        }
+    }
+
+    /// 模式种类，用作数据库中的标识
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RewardPattern::Square { .. } => "square",
+            RewardPattern::Tri { .. } => "tri",
+            RewardPattern::Tetra { .. } => "tetra",
+            RewardPattern::Row { .. } => "row",
+            RewardPattern::Col { .. } => "col",
+            RewardPattern::Dragon { .. } => "dragon",
+        }
+    }
+
+    /// 不随界面语言变化的标识，例如 "square:1,2"、"row:3"
+    pub fn id(&self) -> String {
+        match self {
+            RewardPattern::Square { top_left: (r, c) } => format!("square:{},{}", r, c),
+            RewardPattern::Tri { id } | RewardPattern::Tetra { id } | RewardPattern::Dragon { id } => {
+                format!("{}:{}", self.kind(), id)
+            }
+            RewardPattern::Row { index } | RewardPattern::Col { index } => format!("{}:{}", self.kind(), index),
+        }
+    }
+
+    /// 形成该模式获得的奖励次数
+    pub fn reward(&self) -> u32 {
+        match self {
+            RewardPattern::Square { .. } | RewardPattern::Tri { .. } | RewardPattern::Tetra { .. } => 1,
+            RewardPattern::Row { .. } | RewardPattern::Col { .. } | RewardPattern::Dragon { .. } => 2,
+        }
+    }
+}
+
+/// 棋谱中的一条记录：落子、吃棋、移动，以及附带的奖励、用时、超时和认输
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameAction {
+    /// 落子
+    Place {
+        /// 行动方
+        player: Player,
+        /// 落子位置
+        pos: (usize, usize),
+    },
+    /// 吃掉 pos 上的对方棋子
+    Capture {
+        /// 行动方
+        player: Player,
+        /// 被吃的棋子
+        pos: (usize, usize),
+    },
+    /// 把棋子移到相邻的空位
+    Move {
+        /// 行动方
+        player: Player,
+        /// 起点
+        from: (usize, usize),
+        /// 终点
+        to: (usize, usize),
+    },
+    /// 上一个动作形成了奖励模式
+    Reward {
+        /// 形成模式的一方
+        player: Player,
+        /// 形成的模式
+        pattern: RewardPattern,
+    },
+    /// 上一个动作的用时（毫秒）
+    Clock {
+        /// 行动方
+        player: Player,
+        /// 用时（毫秒）
+        elapsed_ms: u64,
+    },
+    /// 超时判负
+    Timeout {
+        /// 超时的一方
+        player: Player,
+    },
+    /// 认输
+    Resign {
+        /// 认输的一方
+        player: Player,
+    },
+}
+
+impl GameAction {
+    /// 动作记号：落子 "2,3"，吃棋 "x2,3"，移动 "2,3-2,4"
+    pub fn notation(&self) -> String {
+        match self {
+            GameAction::Place { pos, .. } => format!("{},{}", pos.0, pos.1),
+            GameAction::Capture { pos, .. } => format!("x{},{}", pos.0, pos.1),
+            GameAction::Move { from, to, .. } => {
+                format!("{},{}-{},{}", from.0, from.1, to.0, to.1)
+            }
+            GameAction::Reward { pattern, .. } => format!("+{}", pattern.id()),
+            GameAction::Clock { elapsed_ms, .. } => format!("@{}ms", elapsed_ms),
+            GameAction::Timeout { .. } => "timeout".to_string(),
+            GameAction::Resign { .. } => "resign".to_string(),
+        }
+    }
+
+    /// 是否为棋盘上的动作（落子、吃子、移动），奖励和计时只是附带的记录
+    pub fn is_play(&self) -> bool {
+        matches!(
+            self,
+            GameAction::Place { .. } | GameAction::Capture { .. } | GameAction::Move { .. }
+        )
+    }
+
+    /// 执行动作或被记录的一方
+    pub fn player(&self) -> Player {
+        match self {
+            GameAction::Place { player, .. }
+            | GameAction::Capture { player, .. }
+            | GameAction::Move { player, .. }
+            | GameAction::Reward { player, .. }
+            | GameAction::Clock { player, .. }
+            | GameAction::Timeout { player }
+            | GameAction::Resign { player } => *player,
+        }
+    }
+}
+
+/// 执行动作时产生的事件，由 [`Board::take_events`] 取出
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// player 在 pos 落子
+    PiecePlaced {
+        /// 落子的一方
+        player: Player,
+        /// 落子位置
+        pos: (usize, usize),
+    },
+    /// 形成奖励模式，reward 为获得的额外落子或吃子次数
+    PatternFormed {
+        /// 形成模式的一方
+        player: Player,
+        /// 形成的模式
+        pattern: RewardPattern,
+        /// 获得的额外落子或吃子次数
+        reward: u32,
+    },
+    /// player 吃掉 pos 上的对方棋子
+    PieceCaptured {
+        /// 吃子的一方
+        player: Player,
+        /// 被吃的棋子
+        pos: (usize, usize),
+    },
+    /// player 把棋子从 from 移到 to
+    PieceMoved {
+        /// 移动的一方
+        player: Player,
+        /// 起点
+        from: (usize, usize),
+        /// 终点
+        to: (usize, usize),
+    },
+    /// 进入新的阶段
+    PhaseChanged {
+        /// 新的阶段
+        phase: GamePhase,
+    },
+    /// player 还有 remaining 次吃子，但对方的棋子都受保护，自动放弃
+    CaptureSkipped {
+        /// 放弃吃子的一方
+        player: Player,
+        /// 放弃的吃子次数
+        remaining: u32,
+    },
+    /// 轮到 player 行动
+    TurnChanged {
+        /// 接下来行动的一方
+        player: Player,
+    },
+    /// 对局结束
+    GameEnded {
+        /// 胜方
+        winner: Player,
+    },
+}
+
+/// 差一两步即可形成的奖励模式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Threat {
+    /// 可以形成模式的一方
+    pub player: Player,
+    /// 可以形成的模式
+    pub pattern: RewardPattern,
+    /// 还需占据的格子
+    pub needed: Vec<(usize, usize)>,
+    /// 形成后获得的额外落子或吃子次数
+    pub reward: u32,
+}
+
+/// 棋盘：局面、规则判定和本局棋谱
+///
+/// 动作通过 [`Board::apply_action`]（或 `place_piece`、`capture_piece`、`move_piece`）执行，
+/// [`Board::legal_actions`] 列出当前行棋方的所有合法动作，[`Board::check_winner`] 判定胜负。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Board {
+    grid: [[Cell; 5]; 5], // 5x5棋盘
+    current_player: Player,
+    phase: GamePhase,
+    // 落子阶段专用
+    extra_moves: u32, // 额外落子次数
+    // 吃棋阶段专用
+    capture_remaining: HashMap<Player, u32>, // 剩余吃子数量
+    capture_turn: Player,                    // 当前吃棋玩家
+    // 用于记录已触发的奖励模式
+    triggered_squares: HashSet<[usize; 2]>, // 成方 [左上角行, 左上角列]
+    triggered_tris: HashSet<usize>,         // 成三斜 0-3
+    triggered_tetras: HashSet<usize>,       // 成四斜 0-3
+    triggered_rows: HashSet<usize>,         // 成州行 0-4
+    triggered_cols: HashSet<usize>,         // 成州列 0-4
+    triggered_dragons: HashSet<usize>,      // 成龙 0-1
+    // 奖励模式保护的棋子
+    reward_pieces: HashMap<Player, HashSet<(usize, usize)>>,
+    // 游戏记录
+    game_record: Vec<GameAction>,
+    movement_phase_origin: MovementPhaseOrigin, // 添加这个字段
+    // 超时或认输判负的玩家
+    #[serde(default)]
+    forfeited: Option<Player>,
+    // 尚未取出的事件，None 表示不记录（搜索时）
+    #[serde(skip, default = "Board::event_queue")]
+    events: Option<Vec<GameEvent>>,
+}
+
+/// 进入移动阶段的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MovementPhaseOrigin {
+    /// 从落子阶段进入（满盘后）
+    FromPlacement,
+    /// 从吃棋阶段进入
+    FromCapture,
+    /// 从移动阶段自身进入（如吃棋后返回）
+    FromMovement,
+}
+
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
+}
+
+impl Board {
+    /// 开局局面
+    pub fn new() -> Self {
+        let mut board = Board {
+            grid: [[Cell::Empty; 5]; 5],
+            current_player: Player::Black,
+            phase: GamePhase::Placement,
+            extra_moves: 0,
+            capture_remaining: HashMap::new(),
+            capture_turn: Player::Black,
+            triggered_squares: HashSet::new(),
+            triggered_tris: HashSet::new(),
+            triggered_tetras: HashSet::new(),
+            triggered_rows: HashSet::new(),
+            triggered_cols: HashSet::new(),
+            triggered_dragons: HashSet::new(),
+            reward_pieces: HashMap::new(),
+            game_record: Vec::new(),
+            movement_phase_origin: MovementPhaseOrigin::FromPlacement, // 默认从落子阶段进入
+            forfeited: None,
+            events: Board::event_queue(),
+        };
+
+        // 初始化奖励棋子保护集
+        board.update_reward_pieces();
+        board
+    }
+
+    /// 获取当前游戏状态
+    pub fn get_state(&self) -> (GamePhase, Player) {
+        (self.phase.clone(), self.current_player)
+    }
+
+    /// 获取游戏记录
+    pub fn get_game_record(&self) -> &Vec<GameAction> {
+        &self.game_record
+    }
+
+    /// 当前行棋方（吃棋阶段为吃棋方）
+    pub fn current_player(&self) -> Player {
+        self.current_player
+    }
+
+    /// 格子 (row, col) 的状态
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        self.grid[row][col]
+    }
+
+    /// 落子阶段当前行棋方剩余的额外落子次数
+    pub fn extra_moves(&self) -> u32 {
+        self.extra_moves
+    }
+
+    /// 吃棋阶段 player 剩余的吃子数量
+    pub fn capture_remaining(&self, player: Player) -> u32 {
+        self.capture_remaining.get(&player).copied().unwrap_or(0)
+    }
+
+    /// (row, col) 上的棋子是否属于已形成的奖励模式（受保护，不能被吃）
+    pub fn is_protected(&self, row: usize, col: usize) -> bool {
+        match self.grid[row][col] {
+            Cell::Occupied(player) => self
+                .reward_pieces
+                .get(&player)
+                .is_some_and(|protected| protected.contains(&(row, col))),
+            Cell::Empty => false,
+        }
+    }
+
+    fn event_queue() -> Option<Vec<GameEvent>> {
+        Some(Vec::new())
+    }
+
+    /// 取出上次取出之后产生的事件
+    ///
+    /// 通过 [`Board::apply_action`]、[`Board::record_timeout`] 和 [`Board::record_resign`] 执行的动作
+    /// 按发生顺序产生落子、吃子、移动、形成奖励模式、阶段变化、轮换行棋方和对局结束的事件。
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// 是否记录事件（默认记录）。只关心局面的场合（如搜索）可以关闭以减少开销
+    pub fn set_event_recording(&mut self, on: bool) {
+        self.events = if on { Some(self.take_events()) } else { None };
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        if let Some(events) = &mut self.events {
+            events.push(event);
+        }
+    }
+
+    // 切换阶段
+    fn set_phase(&mut self, phase: GamePhase) {
+        if self.phase != phase {
+            self.emit(GameEvent::PhaseChanged { phase: phase.clone() });
+        }
+        self.phase = phase;
+    }
+
+    // 记录游戏动作
+    fn record_action(&mut self, action: GameAction) {
+        if self.events.is_some() {
+            let event = match &action {
+                GameAction::Place { player, pos } => Some(GameEvent::PiecePlaced { player: *player, pos: *pos }),
+                GameAction::Capture { player, pos } => Some(GameEvent::PieceCaptured { player: *player, pos: *pos }),
+                GameAction::Move { player, from, to } => Some(GameEvent::PieceMoved {
+                    player: *player,
+                    from: *from,
+                    to: *to,
+                }),
+                GameAction::Reward { player, pattern } => Some(GameEvent::PatternFormed {
+                    player: *player,
+                    pattern: pattern.clone(),
+                    reward: pattern.reward(),
+                }),
+                GameAction::Clock { .. } | GameAction::Timeout { .. } | GameAction::Resign { .. } => None,
+            };
+            if let Some(event) = event {
+                self.emit(event);
+            }
+        }
+        self.game_record.push(action);
+    }
+
+    /// 记录超时判负
+    pub fn record_timeout(&mut self, player: Player) {
+        self.forfeited = Some(player);
+        self.record_action(GameAction::Timeout { player });
+        self.emit(GameEvent::GameEnded { winner: player.opponent() });
+    }
+
+    /// 记录认输
+    pub fn record_resign(&mut self, player: Player) {
+        self.forfeited = Some(player);
+        self.record_action(GameAction::Resign { player });
+        self.emit(GameEvent::GameEnded { winner: player.opponent() });
+    }
+
+    // 检查位置是否有效
+    fn is_valid_pos(row: usize, col: usize) -> bool {
+        row < 5 && col < 5
+    }
+
+    /// 检查棋盘是否已满
+    pub fn is_full(&self) -> bool {
+        self.grid
+            .iter()
+            .all(|row| row.iter().all(|c| *c != Cell::Empty))
+    }
+
+    /// 获取玩家棋子位置
+    pub fn player_pieces(&self, player: Player) -> Vec<(usize, usize)> {
+        let mut pieces = Vec::new();
+        for (r, row) in self.grid.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                if let Cell::Occupied(p) = cell
+                    && *p == player
+                {
+                    pieces.push((r, c));
+                }
+            }
+        }
+        pieces
+    }
+
+    /// 检查玩家是否有合法移动
+    pub fn has_legal_moves(&self, player: Player) -> bool {
+        let pieces = self.player_pieces(player);
+
+        // 如果棋子少于3个，无法形成任何模式，自动判负
+        if pieces.len() < 3 {
+            return false;
+        }
+
+        for (r, c) in pieces {
+            // 检查上下左右四个方向
+            let neighbors = [
+                (r.wrapping_sub(1), c),
+                (r + 1, c),
+                (r, c.wrapping_sub(1)),
+                (r, c + 1),
+            ];
+
+            for (nr, nc) in neighbors {
+                if Self::is_valid_pos(nr, nc) && self.grid[nr][nc] == Cell::Empty {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// 执行落子
+    pub fn place_piece(&mut self, row: usize, col: usize) -> Result<u32, &'static str> {
+        if self.phase != GamePhase::Placement {
+            return Err(tr("error.not_placement"));
+        }
+
+        if !Self::is_valid_pos(row, col) {
+            return Err(tr("error.out_of_range"));
+        }
+
+        if self.grid[row][col] != Cell::Empty {
+            return Err(tr("error.occupied"));
+        }
+
+        // 落子
+        self.grid[row][col] = Cell::Occupied(self.current_player);
+
+        // 记录落子动作
+        self.record_action(GameAction::Place {
+            player: self.current_player,
+            pos: (row, col),
+        });
+
+        // 检查奖励并获取额外落子次数
+        let extra = self.check_rewards(row, col);
+
+        // 处理额外落子次数
+        self.extra_moves = self.extra_moves.saturating_add(extra);
+
+        // 消耗一次落子机会
+        if self.extra_moves > 0 {
+            self.extra_moves -= 1;
+        } else {
+            self.current_player = self.current_player.opponent();
+        }
+
+        // 检查是否需要进入吃棋阶段
+        if self.is_full() {
+            self.enter_capture_phase();
+        }
+
+        Ok(extra)
+    }
+
+    // 进入吃棋阶段
+    fn enter_capture_phase(&mut self) {
+        self.set_phase(GamePhase::Capture);
+
+     // 重置奖励模式记录并重新计算
+    self.triggered_squares.clear();
+    self.triggered_tris.clear();
+    self.triggered_tetras.clear();
+    self.triggered_rows.clear();
+    self.triggered_cols.clear();
+    self.triggered_dragons.clear();
+
+    self.scan_all_rewards();
+
+
+        // 设置吃棋顺序：第二个落子的玩家（白方）先吃棋
+    let first_player = Player::White; // 白方是先吃玩家
+    let second_player = Player::Black; // 黑方是后吃玩家
+
+
+      // 重置吃子数量为0，与落子阶段无关
+    self.capture_remaining = HashMap::new();
+    self.capture_remaining.insert(first_player, 0);
+    self.capture_remaining.insert(second_player, 0);
+
+    // 检查是否有可吃的棋子
+    let first_has_capturable = self.has_capturable_pieces(second_player);
+    let second_has_capturable = self.has_capturable_pieces(first_player);
+
+
+        // 只有当玩家有可吃棋子时才设置吃棋数量
+    if first_has_capturable {
+        self.capture_remaining.insert(first_player, 1);
+    } else {
+        self.capture_remaining.insert(first_player, 0);
+    }
+
+    if second_has_capturable {
+        self.capture_remaining.insert(second_player, 1);
+    } else {
+        self.capture_remaining.insert(second_player, 0);
+    }
+
+    // 没有可吃棋子的一方放弃这次吃子
+    for (player, capturable) in [(first_player, first_has_capturable), (second_player, second_has_capturable)] {
+        if !capturable {
+            self.emit(GameEvent::CaptureSkipped { player, remaining: 1 });
+        }
+    }
+
+         // 设置第一个有可吃棋子的玩家为当前玩家
+    if first_has_capturable {
+        self.current_player = first_player;
+        self.capture_turn = first_player;
+    } else if second_has_capturable {
+        self.current_player = second_player;
+        self.capture_turn = second_player;
+    } else {
+        // 如果都没有可吃的棋子，直接进入移动阶段
+        self.enter_movement_phase(MovementPhaseOrigin::FromPlacement);
+    }
+    }
+
+    // 检查是否有可吃的棋子。没有时吃子次数作废，不会停在无棋可吃的吃棋阶段
+    fn has_capturable_pieces(&self, opponent: Player) -> bool {
+        let protected = self
+            .reward_pieces
+            .get(&opponent)
+            .cloned()
+            .unwrap_or_default();
+
+        // 检查对手的所有棋子
+        for r in 0..5 {
+            for c in 0..5 {
+                if let Cell::Occupied(p) = self.grid[r][c]
+                    && p == opponent && !protected.contains(&(r, c))
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    // 扫描所有奖励模式
+    fn scan_all_rewards(&mut self) {
+        for player in [Player::Black, Player::White] {
+            // 扫描成方
+            for r in 0..4 {
+                for c in 0..4 {
+                    if self.is_square(r, c, player) {
+                        self.triggered_squares.insert([r, c]);
+                    }
+                }
+            }
+
+            // 扫描成三斜
+            for id in 0..4 {
+                if self.is_tri(id, player) {
+                    self.triggered_tris.insert(id);
+                }
+            }
+
+            // 扫描成四斜
+            for id in 0..4 {
+                if self.is_tetra(id, player) {
+                    self.triggered_tetras.insert(id);
+                }
+            }
+
+            // 扫描成州（行）
+            for r in 0..5 {
+                if self.is_row(r, player) {
+                    self.triggered_rows.insert(r);
+                }
+            }
+
+            // 扫描成州（列）
+            for c in 0..5 {
+                if self.is_col(c, player) {
+                    self.triggered_cols.insert(c);
+                }
+            }
+
+            // 扫描成龙
+            for id in 0..2 {
+                if self.is_dragon(id, player) {
+                    self.triggered_dragons.insert(id);
+                }
+            }
+        }
+
+        // 更新保护棋子
+        self.update_reward_pieces();
+    }
+
+    // 更新受保护的棋子
+    fn update_reward_pieces(&mut self) {
+        self.reward_pieces.clear();
+
+        // 黑方受保护棋子
+        let mut black_protected = HashSet::new();
+        self.add_reward_pieces(Player::Black, &mut black_protected);
+        self.reward_pieces.insert(Player::Black, black_protected);
+
+        // 白方受保护棋子
+        let mut white_protected = HashSet::new();
+        self.add_reward_pieces(Player::White, &mut white_protected);
+        self.reward_pieces.insert(Player::White, white_protected);
+    }
+
+    // 收集奖励模式中的棋子
+    fn add_reward_pieces(&self, player: Player, protected: &mut HashSet<(usize, usize)>) {
+        // 成方
+        for &[r, c] in &self.triggered_squares {
+            if self.is_square(r, c, player) {
+                protected.insert((r, c));
+                protected.insert((r, c + 1));
+                protected.insert((r + 1, c));
+                protected.insert((r + 1, c + 1));
+            }
+        }
+
+        // 成三斜
+        let tris = [
+            vec![(0, 2), (1, 1), (2, 0)], // 左上三斜
+            vec![(0, 2), (1, 3), (2, 4)], // 右上三斜
+            vec![(2, 0), (3, 1), (4, 2)], // 左下三斜
+            vec![(2, 4), (3, 3), (4, 2)], // 右下三斜
+        ];
+        for id in &self.triggered_tris {
+            if let Some(tri) = tris.get(*id)
+                && self.is_tri(*id, player)
+            {
+                for &(r, c) in tri {
+                    protected.insert((r, c));
+                }
+            }
+        }
+
+        // 成四斜
+        let tetras = [
+            vec![(0, 1), (1, 2), (2, 3), (3, 4)], // 左上四斜
+            vec![(0, 3), (1, 2), (2, 1), (3, 0)], // 右上四斜
+            vec![(1, 0), (2, 1), (3, 2), (4, 3)], // 左下四斜
+            vec![(1, 4), (2, 3), (3, 2), (4, 1)], // 右下四斜
+        ];
+        for id in &self.triggered_tetras {
+            if let Some(tetra) = tetras.get(*id)
+                && self.is_tetra(*id, player)
+            {
+                for &(r, c) in tetra {
+                    protected.insert((r, c));
+                }
+            }
+        }
+
+        // 成州（行）
+        for &r in &self.triggered_rows {
+            if self.is_row(r, player) {
+                for c in 0..5 {
+                    protected.insert((r, c));
+                }
+            }
+        }
+
+        // 成州（列）
+        for &c in &self.triggered_cols {
+            if self.is_col(c, player) {
+                for r in 0..5 {
+                    protected.insert((r, c));
+                }
+            }
+        }
+
+        // 成龙
+        let dragons = [
+            vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)], // 主对角线
+            vec![(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)], // 副对角线
+        ];
+        for id in &self.triggered_dragons {
+            if let Some(dragon) = dragons.get(*id)
+                && self.is_dragon(*id, player)
+            {
+                for &(r, c) in dragon {
+                    protected.insert((r, c));
+                }
+            }
+        }
+    }
+
+    /// 执行吃棋（单步吃一个棋子）
+    ///
+    /// 吃子后对方剩下的棋子都受保护时，剩余的吃子次数自动放弃（产生 [`GameEvent::CaptureSkipped`]）
+    pub fn capture_piece(&mut self, row: usize, col: usize) -> Result<(), &'static str> {
+        if self.phase != GamePhase::Capture {
+            return Err(tr("error.not_capture"));
+        }
+
+        let player = self.current_player;
+
+        // 获取当前玩家剩余吃子数量
+        let remaining = match self.capture_remaining.get(&player) {
+            Some(&r) if r > 0 => r,
+            _ => return Err(tr("error.no_capture_pending")),
+        };
+
+        if !Self::is_valid_pos(row, col) {
+            return Err(tr("error.invalid_position"));
+        }
+
+        let opponent = player.opponent();
+        let protected = self
+            .reward_pieces
+            .get(&opponent)
+            .cloned()
+            .unwrap_or_default();
+
+        // 验证吃棋位置
+        if protected.contains(&(row, col)) {
+            return Err(tr("error.protected"));
+        }
+
+        if let Cell::Occupied(p) = self.grid[row][col] {
+            if p != opponent {
+                return Err(tr("error.capture_own"));
+            }
+        } else {
+            return Err(tr("error.empty_cell"));
+        }
+
+        // 执行吃棋
+        self.grid[row][col] = Cell::Empty;
+
+        // 记录吃棋动作
+        self.record_action(GameAction::Capture {
+            player,
+            pos: (row, col),
+        });
+
+        // 更新吃棋剩余数量
+        *self.capture_remaining.get_mut(&player).unwrap() = remaining - 1;
+
+        
+
+        // 更新奖励棋子保护集
+        self.update_reward_pieces();
+
+        // 对方剩下的棋子都受保护时放弃剩余的吃子次数
+        let left = self.capture_remaining(player);
+        if left > 0 && !self.has_capturable_pieces(opponent) {
+            self.capture_remaining.insert(player, 0);
+            self.emit(GameEvent::CaptureSkipped { player, remaining: left });
+        }
+
+        // 检查吃棋后状态
+        if self.capture_remaining.values().sum::<u32>() == 0 {
+    // 所有吃棋完成，进入移动阶段
+    self.enter_movement_phase(MovementPhaseOrigin::FromMovement);
+    return Ok(());
+        }
+
+           // 如果当前玩家还有吃子机会，不切换玩家
+    if self.capture_remaining.get(&player).copied().unwrap_or(0) > 0 {
+        return Ok(());
+    }
+
+        // 切换到下一个吃棋玩家
+        let next_player = player.opponent();
+
+        // 如果下一个玩家没有可吃的棋子，则跳过
+// 检查下一个玩家是否有可吃的棋子
+let next_has_capturable = self.has_capturable_pieces(next_player.opponent());
+
+if !next_has_capturable {
+    // 如果下一个玩家没有可吃的棋子，检查是否所有玩家都完成吃棋
+    if self.capture_remaining.values().sum::<u32>() == 0 {
+        self.enter_movement_phase(MovementPhaseOrigin::FromCapture);
+        return Ok(());
+    } else {
+        // 跳过这个玩家，回到第一个吃棋玩家
+        self.current_player = self.capture_turn;
+        return Ok(());
+    }
+}
+
+self.current_player = next_player;
+
+        // 检查后吃棋责任
+        if player == self.capture_turn.opponent() {
+            let next_mover = self.capture_turn;
+            if !self.has_legal_moves(next_mover) {
+                return Err(tr("error.capture_blocks"));
+            }
+        }
+
+        Ok(())
+    }
+
+    // 进入移动阶段
+fn enter_movement_phase(&mut self, origin: MovementPhaseOrigin) {
+    self.set_phase(GamePhase::Movement);
+    self.movement_phase_origin = origin;
+    
+    match origin {
+        MovementPhaseOrigin::FromPlacement => {
+            // 从满盘进入移动阶段，白方先走
+            self.current_player = Player::White;
+        }
+        MovementPhaseOrigin::FromCapture => {
+            // 从吃棋阶段进入移动阶段，保持当前玩家不变
+            // 不需要改变current_player
+        }
+        MovementPhaseOrigin::FromMovement => {
+            // 从移动阶段自身进入（如吃棋后返回），切换玩家
+            self.current_player = self.current_player.opponent();
+        }
+    }
+    
+    self.update_reward_pieces();
+}
+
+// 添加新的奖励检查方法，专门用于走棋阶段
+fn check_rewards_after_move(&mut self, row: usize, col: usize) -> u32 {
+    let player = self.current_player;
+    let mut capture_count = 0;
+    
+    // 只检查与移动棋子相关的奖励模式
+    // 1. 检查成方 (1x1 正方形)
+    let squares = self.check_squares_after_move(row, col, player);
+    capture_count += squares;
+    
+    // 2. 检查成三斜 (3点斜线)
+    let tris = self.check_tris_after_move(row, col, player);
+    capture_count += tris;
+    
+    // 3. 检查成四斜 (4点斜线)
+    let tetras = self.check_tetras_after_move(row, col, player);
+    capture_count += tetras;
+    
+    // 4. 检查成州 (整行或整列)
+    let rows = self.check_rows_after_move(row, player);
+    capture_count += rows;
+    
+    let cols = self.check_cols_after_move(col, player);
+    capture_count += cols;
+    
+    // 5. 检查成龙 (对角线)
+    let dragons = self.check_dragons_after_move(row, col, player);
+    capture_count += dragons;
+    
+    capture_count
+}
+
+// 添加走棋阶段专用的奖励检查方法
+fn check_squares_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
+    let mut extra = 0;
+    
+    // 检查可能包含该点的所有正方形
+    for &(r, c) in &[
+        (row, col),
+        (row, col.saturating_sub(1)),
+        (row.saturating_sub(1), col),
+        (row.saturating_sub(1), col.saturating_sub(1)),
+    ] {
+        if r < 4 && c < 4
+            && self.is_square(r, c, player) && !self.triggered_squares.contains(&[r, c])
+        {
+            self.triggered_squares.insert([r, c]);
+            extra += 1;
+            
+            // 记录奖励模式
+            self.record_action(GameAction::Reward {
+                player,
+                pattern: RewardPattern::Square { top_left: (r, c) },
+            });
+        }
+    }
+    extra
+}
+
+fn check_tris_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
+    let mut extra = 0;
+    
+    for id in 0..4 {
+        if self.is_tri_affected_by_move(id, row, col) && 
+           self.is_tri(id, player) && 
+           !self.triggered_tris.contains(&id) {
+            self.triggered_tris.insert(id);
+            extra += 1;
+            
+            // 记录奖励模式
+            self.record_action(GameAction::Reward {
+                player,
+                pattern: RewardPattern::Tri { id },
+            });
+        }
+    }
+    extra
+}
+
+fn check_tetras_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
+    let mut extra = 0;
+    
+    for id in 0..4 {
+        if self.is_tetra_affected_by_move(id, row, col) && 
+           self.is_tetra(id, player) && 
+           !self.triggered_tetras.contains(&id) {
+            self.triggered_tetras.insert(id);
+            extra += 1;
+            
+            // 记录奖励模式
+            self.record_action(GameAction::Reward {
+                player,
+                pattern: RewardPattern::Tetra { id },
+            });
+        }
+    }
+    extra
+}
+
+fn check_rows_after_move(&mut self, row: usize, player: Player) -> u32 {
+    if self.is_row(row, player) && !self.triggered_rows.contains(&row) {
+        self.triggered_rows.insert(row);
+        
+        // 记录奖励模式
+        self.record_action(GameAction::Reward {
+            player,
+            pattern: RewardPattern::Row { index: row },
+        });
+        
+        2 // 成州奖励2次吃子机会
+    } else {
+        0
+    }
+}
+
+fn check_cols_after_move(&mut self, col: usize, player: Player) -> u32 {
+    if self.is_col(col, player) && !self.triggered_cols.contains(&col) {
+        self.triggered_cols.insert(col);
+        
+        // 记录奖励模式
+        self.record_action(GameAction::Reward {
+            player,
+            pattern: RewardPattern::Col { index: col },
+        });
+        
+        2 // 成州奖励2次吃子机会
+    } else {
+        0
+    }
+}
+
+fn check_dragons_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
+    let mut extra = 0;
+    
+    for id in 0..2 {
+        if self.is_dragon_affected_by_move(id, row, col) && 
+           self.is_dragon(id, player) && 
+           !self.triggered_dragons.contains(&id) {
+            self.triggered_dragons.insert(id);
+            extra += 2; // 成龙奖励2次吃子机会
+            
+            // 记录奖励模式
+            self.record_action(GameAction::Reward {
+                player,
+                pattern: RewardPattern::Dragon { id },
+            });
+        }
+    }
+    extra
+}
+
+// 添加辅助方法检查移动是否影响特定模式
+fn is_tri_affected_by_move(&self, id: usize, row: usize, col: usize) -> bool {
+    let positions = match id {
+        0 => vec![(0, 2), (1, 1), (2, 0)], // 左上三斜
+        1 => vec![(0, 2), (1, 3), (2, 4)], // 右上三斜
+        2 => vec![(2, 0), (3, 1), (4, 2)], // 左下三斜
+        3 => vec![(2, 4), (3, 3), (4, 2)], // 右下三斜
+        _ => return false,
+    };
+    
+    positions.contains(&(row, col))
+}
+
+fn is_tetra_affected_by_move(&self, id: usize, row: usize, col: usize) -> bool {
+    let positions = match id {
+        0 => vec![(0, 1), (1, 2), (2, 3), (3, 4)], // 左上四斜
+        1 => vec![(0, 3), (1, 2), (2, 1), (3, 0)], // 右上四斜
+        2 => vec![(1, 0), (2, 1), (3, 2), (4, 3)], // 左下四斜
+        3 => vec![(1, 4), (2, 3), (3, 2), (4, 1)], // 右下四斜
+        _ => return false,
+    };
+    
+    positions.contains(&(row, col))
+}
+
+fn is_dragon_affected_by_move(&self, id: usize, row: usize, col: usize) -> bool {
+    let positions = match id {
+        0 => vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)], // 主对角线
+        1 => vec![(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)], // 副对角线
+        _ => return false,
+    };
+    
+    positions.contains(&(row, col))
+}
+
+    /// 执行移动，返回获得的吃子次数
+    ///
+    /// 形成奖励模式但对方的棋子都受保护时不进入吃棋阶段，放弃吃子后按普通移动轮到对方
+    pub fn move_piece(
+        &mut self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Result<u32, &'static str> {
+        if self.phase != GamePhase::Movement {
+            return Err(tr("error.not_movement"));
+        }
+
+        let (from_row, from_col) = from;
+        let (to_row, to_col) = to;
+
+        // 验证移动位置
+        if !Self::is_valid_pos(from_row, from_col) || !Self::is_valid_pos(to_row, to_col) {
+            return Err(tr("error.out_of_range"));
+        }
+
+        // 检查起始位置是否属于当前玩家
+        if let Cell::Occupied(p) = self.grid[from_row][from_col] {
+            if p != self.current_player {
+                return Err(tr("error.move_other"));
+            }
+        } else {
+            return Err(tr("error.move_empty"));
+        }
+
+        // 检查目标位置是否为空
+        if self.grid[to_row][to_col] != Cell::Empty {
+            return Err(tr("error.move_occupied"));
+        }
+
+        // 检查移动是否相邻（上下左右）
+        let row_diff = from_row.abs_diff(to_row);
+        let col_diff = from_col.abs_diff(to_col);
+        if (row_diff == 1 && col_diff == 0) || (row_diff == 0 && col_diff == 1) {
+            // 有效移动
+        } else {
+            return Err(tr("error.not_adjacent"));
+        }
+
+        // 执行移动
+        let player = self.current_player;
+        self.grid[from_row][from_col] = Cell::Empty;
+        self.grid[to_row][to_col] = Cell::Occupied(player);
+
+        // 记录移动动作
+        self.record_action(GameAction::Move {
+            player,
+            from: (from_row, from_col),
+            to: (to_row, to_col),
+        });
+
+        // 检查奖励并获取可吃子数量
+       let capture_count = self.check_rewards_after_move(to_row, to_col);
+
+
+       // 如果有吃子机会，进入吃棋阶段让玩家选择吃哪些棋子
+    if capture_count > 0 {
+        // 更新奖励棋子保护集
+        self.update_reward_pieces();
+
+        if self.has_capturable_pieces(player.opponent()) {
+            // 设置吃棋阶段
+            self.set_phase(GamePhase::Capture);
+            self.capture_remaining.insert(player, capture_count);
+            self.capture_turn = player;
+
+            // 返回吃子数量，但不实际吃子
+            return Ok(capture_count);
+        }
+
+        // 对方的棋子都受保护，放弃吃子，按普通移动继续
+        self.emit(GameEvent::CaptureSkipped { player, remaining: capture_count });
+    }
+
+        // 检查移动后对方是否能走棋
+        let opponent = player.opponent();
+        if !self.has_legal_moves(opponent) {
+            // 导致对方无法走棋，当前玩家判负
+            return Err(tr("error.move_blocks"));
+        }
+
+        // 切换玩家
+        self.current_player = self.current_player.opponent();
+
+        Ok(0)
+    }
+
+    // 落子阶段的奖励检查
+    fn check_rewards(&mut self, row: usize, col: usize) -> u32 {
+        let mut extra = 0;
+
+        // 1. 检查成方 (1x1 正方形)
+        let squares = self.check_squares(row, col);
+        extra += squares;
+
+        // 2. 检查成三斜 (3点斜线)
+        let tris = self.check_tris();
+        extra += tris;
+
+        // 3. 检查成四斜 (4点斜线)
+        let tetras = self.check_tetras();
+        extra += tetras;
+
+        // 4. 检查成州 (整行或整列)
+        let rows = self.check_rows();
+        extra += rows;
+
+        let cols = self.check_cols();
+        extra += cols;
+
+        // 5. 检查成龙 (对角线)
+        let dragons = self.check_dragons();
+        extra += dragons;
+
+        extra
+    }
+
+    // 成方检测 (1x1 正方形)
+    fn check_squares(&mut self, row: usize, col: usize) -> u32 {
+        let mut extra = 0;
+        let player = self.current_player;
+
+        // 检查可能包含该点的所有正方形
+        for &(r, c) in &[
+            (row, col),
+            (row, col.saturating_sub(1)),
+            (row.saturating_sub(1), col),
+            (row.saturating_sub(1), col.saturating_sub(1)),
+        ] {
+            if r < 4 && c < 4
+                && self.is_square(r, c, player)
+            {
+                let square_id = [r, c];
+                if self.triggered_squares.insert(square_id) {
+                    extra += 1;
+
+                    // 记录奖励模式
+                    self.record_action(GameAction::Reward {
+                        player,
+                        pattern: RewardPattern::Square { top_left: (r, c) },
+                    });
+                }
+            }
+        }
+        extra
+    }
+
+    fn is_square(&self, r: usize, c: usize, player: Player) -> bool {
+        let corners = [(r, c), (r, c + 1), (r + 1, c), (r + 1, c + 1)];
+
+        corners
+            .iter()
+            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
+    }
+
+    // 成三斜检测 (3点斜线)
+    fn check_tris(&mut self) -> u32 {
+        let player = self.current_player;
+        let mut extra = 0;
+
+        for id in 0..4 {
+            if !self.triggered_tris.contains(&id) && self.is_tri(id, player) {
+                self.triggered_tris.insert(id);
+                extra += 1;
+
+                // 记录奖励模式
+                self.record_action(GameAction::Reward {
+                    player,
+                    pattern: RewardPattern::Tri { id },
+                });
+            }
+        }
+        extra
+    }
+
+    fn is_tri(&self, id: usize, player: Player) -> bool {
+        let positions = match id {
+            0 => vec![(0, 2), (1, 1), (2, 0)], // 左上三斜
+            1 => vec![(0, 2), (1, 3), (2, 4)], // 右上三斜
+            2 => vec![(2, 0), (3, 1), (4, 2)], // 左下三斜
+            3 => vec![(2, 4), (3, 3), (4, 2)], // 右下三斜
+            _ => return false,
+        };
+
+        positions
+            .iter()
+            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
+    }
+
+    // 成四斜检测 (4点斜线)
+    fn check_tetras(&mut self) -> u32 {
+        let player = self.current_player;
+        let mut extra = 0;
+
+        for id in 0..4 {
+            if !self.triggered_tetras.contains(&id) && self.is_tetra(id, player) {
+                self.triggered_tetras.insert(id);
+                extra += 1;
+
+                // 记录奖励模式
+                self.record_action(GameAction::Reward {
+                    player,
+                    pattern: RewardPattern::Tetra { id },
+                });
+            }
+        }
+        extra
+    }
+
+    fn is_tetra(&self, id: usize, player: Player) -> bool {
+        let positions = match id {
+            0 => vec![(0, 1), (1, 2), (2, 3), (3, 4)], // 左上四斜
+            1 => vec![(0, 3), (1, 2), (2, 1), (3, 0)], // 右上四斜
+            2 => vec![(1, 0), (2, 1), (3, 2), (4, 3)], // 左下四斜
+            3 => vec![(1, 4), (2, 3), (3, 2), (4, 1)], // 右下四斜
+            _ => return false,
+        };
+
+        positions
+            .iter()
+            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
+    }
+
+    // 成州检测 (整行)
+    fn check_rows(&mut self) -> u32 {
+        let player = self.current_player;
+        let mut extra = 0;
+
+        for r in 0..5 {
+            if !self.triggered_rows.contains(&r) && self.is_row(r, player) {
+                self.triggered_rows.insert(r);
+                extra += 2;
+
+                // 记录奖励模式
+                self.record_action(GameAction::Reward {
+                    player,
+                    pattern: RewardPattern::Row { index: r },
+                });
+            }
+        }
+        extra
+    }
+
+    fn is_row(&self, r: usize, player: Player) -> bool {
+        (0..5).all(|c| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
+    }
+
+    // 成州检测 (整列)
+    fn check_cols(&mut self) -> u32 {
+        let player = self.current_player;
+        let mut extra = 0;
+
+        for c in 0..5 {
+            if !self.triggered_cols.contains(&c) && self.is_col(c, player) {
+                self.triggered_cols.insert(c);
+                extra += 2;
+
+                // 记录奖励模式
+                self.record_action(GameAction::Reward {
+                    player,
+                    pattern: RewardPattern::Col { index: c },
+                });
+            }
+        }
+        extra
+    }
+
+    fn is_col(&self, c: usize, player: Player) -> bool {
+        (0..5).all(|r| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
+    }
+
+    // 成龙检测 (对角线)
+    fn check_dragons(&mut self) -> u32 {
+        let player = self.current_player;
+        let mut extra = 0;
+
+        for id in 0..2 {
+            if !self.triggered_dragons.contains(&id) && self.is_dragon(id, player) {
+                self.triggered_dragons.insert(id);
+                extra += 2;
+
+                // 记录奖励模式
+                self.record_action(GameAction::Reward {
+                    player,
+                    pattern: RewardPattern::Dragon { id },
+                });
+            }
+        }
+        extra
+    }
+
+    fn is_dragon(&self, id: usize, player: Player) -> bool {
+        let positions = match id {
+            0 => vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)], // 主对角线
+            1 => vec![(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)], // 副对角线
+            _ => return false,
+        };
+
+        positions
+            .iter()
+            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
+    }
+
+    // 模式是否已经触发过（触发过的模式不再给予奖励）
+    fn is_triggered(&self, pattern: &RewardPattern) -> bool {
+        match *pattern {
+            RewardPattern::Square { top_left: (r, c) } => self.triggered_squares.contains(&[r, c]),
+            RewardPattern::Tri { id } => self.triggered_tris.contains(&id),
+            RewardPattern::Tetra { id } => self.triggered_tetras.contains(&id),
+            RewardPattern::Row { index } => self.triggered_rows.contains(&index),
+            RewardPattern::Col { index } => self.triggered_cols.contains(&index),
+            RewardPattern::Dragon { id } => self.triggered_dragons.contains(&id),
+        }
+    }
+
+    /// 列出 player 差一两子（或一两步）即可形成的奖励模式
+    /// 落子阶段：所缺格子必须都是空位
+    /// 吃棋和走子阶段：所缺格子必须是空位，且各有一枚模式外的本方棋子可以一步走入
+    pub fn threats(&self, player: Player) -> Vec<Threat> {
+        let mut threats = Vec::new();
+
+        for pattern in RewardPattern::catalog() {
+            if self.is_triggered(&pattern) {
+                continue;
+            }
+
+            let cells = pattern.cells();
+            let needed: Vec<(usize, usize)> = cells
+                .iter()
+                .copied()
+                .filter(|&(r, c)| self.grid[r][c] != Cell::Occupied(player))
+                .collect();
+            if needed.is_empty() || needed.len() > 2 {
+                continue;
+            }
+            if needed.iter().any(|&(r, c)| self.grid[r][c] != Cell::Empty) {
+                continue;
+            }
+
+            if self.phase != GamePhase::Placement {
+                // 每个缺口可走入的本方棋子
+                let movers: Vec<Vec<(usize, usize)>> = needed
+                    .iter()
+                    .map(|&(r, c)| {
+                        [
+                            (r.wrapping_sub(1), c),
+                            (r + 1, c),
+                            (r, c.wrapping_sub(1)),
+                            (r, c + 1),
+                        ]
+                        .into_iter()
+                        .filter(|&(nr, nc)| {
+                            Self::is_valid_pos(nr, nc)
+                                && self.grid[nr][nc] == Cell::Occupied(player)
+                                && !cells.contains(&(nr, nc))
+                        })
+                        .collect()
+                    })
+                    .collect();
+
+                // 两个缺口需要由不同的棋子走入
+                let reachable = match movers.as_slice() {
+                    [a] => !a.is_empty(),
+                    [a, b] => a.iter().any(|x| b.iter().any(|y| x != y)),
+                    _ => false,
+                };
+                if !reachable {
+                    continue;
+                }
+            }
+
+            threats.push(Threat {
+                player,
+                reward: pattern.reward(),
+                pattern,
+                needed,
+            });
+        }
+
+        // 越接近完成、奖励越大的排在前面
+        threats.sort_by_key(|t| (t.needed.len(), std::cmp::Reverse(t.reward)));
+        threats
+    }
+
+    /// 当前保护着棋子的奖励模式及其所属玩家：已触发、所含格子仍全是同一方的棋子且都受保护
+    ///
+    /// 与 [`Board::is_protected`] 一致，保护从落子阶段结束后才开始计算
+    pub fn active_patterns(&self) -> Vec<(Player, RewardPattern)> {
+        RewardPattern::catalog()
+            .into_iter()
+            .filter(|pattern| self.is_triggered(pattern))
+            .filter_map(|pattern| {
+                let cells = pattern.cells();
+                let Cell::Occupied(owner) = self.grid[cells[0].0][cells[0].1] else {
+                    return None;
+                };
+                cells
+                    .iter()
+                    .all(|&(r, c)| self.grid[r][c] == Cell::Occupied(owner) && self.is_protected(r, c))
+                    .then_some((owner, pattern))
+            })
+            .collect()
+    }
+
+    /// 检查游戏是否结束
+    pub fn check_winner(&self) -> Option<Player> {
+        if let Some(player) = self.forfeited {
+            return Some(player.opponent());
+        }
+
+        // 只在吃棋和走子阶段检查
+        if self.phase == GamePhase::Placement {
+            return None;
+        }
+
+        let black_pieces = self.player_pieces(Player::Black).len();
+        let white_pieces = self.player_pieces(Player::White).len();
+
+        if black_pieces < 3 {
+            return Some(Player::White);
+        }
+
+        if white_pieces < 3 {
+            return Some(Player::Black);
+        }
+
+        // 检查是否有合法移动
+        if self.phase == GamePhase::Movement
+            && !self.has_legal_moves(self.current_player)
+        {
+            return Some(self.current_player.opponent());
+        }
+
+        None
+    }
+
+    /// 列出当前玩家的所有合法动作
+    pub fn legal_actions(&self) -> Vec<GameAction> {
+        let player = self.current_player;
+        let mut actions = Vec::new();
+
+        match self.phase {
+            GamePhase::Placement => {
+                for r in 0..5 {
+                    for c in 0..5 {
+                        if self.grid[r][c] == Cell::Empty {
+                            actions.push(GameAction::Place { player, pos: (r, c) });
+                        }
+                    }
+                }
+            }
+            GamePhase::Capture => {
+                if self.capture_remaining.get(&player).copied().unwrap_or(0) == 0 {
+                    return actions;
+                }
+                let opponent = player.opponent();
+                let protected = self.reward_pieces.get(&opponent);
+                for pos in self.player_pieces(opponent) {
+                    if !protected.is_some_and(|p| p.contains(&pos)) {
+                        actions.push(GameAction::Capture { player, pos });
+                    }
+                }
+            }
+            GamePhase::Movement => {
+                for (r, c) in self.player_pieces(player) {
+                    let neighbors = [
+                        (r.wrapping_sub(1), c),
+                        (r + 1, c),
+                        (r, c.wrapping_sub(1)),
+                        (r, c + 1),
+                    ];
+                    for (nr, nc) in neighbors {
+                        if Self::is_valid_pos(nr, nc) && self.grid[nr][nc] == Cell::Empty {
+                            actions.push(GameAction::Move {
+                                player,
+                                from: (r, c),
+                                to: (nr, nc),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        actions
+    }
+
+    /// 执行一个动作（落子/吃棋/移动），奖励记录不能直接执行
+    ///
+    /// 合法动作也可能返回错误：动作导致对方无法走棋时局面已经改变，行动方判负。
+    pub fn apply_action(&mut self, action: &GameAction) -> Result<u32, &'static str> {
+        if self.events.is_none() {
+            return self.execute(action);
+        }
+        let (player, recorded) = (self.current_player, self.game_record.len());
+        let result = self.execute(action);
+        if self.game_record.len() > recorded {
+            if self.current_player != player {
+                self.emit(GameEvent::TurnChanged { player: self.current_player });
+            }
+            let winner = match result {
+                Err(_) => Some(action.player().opponent()),
+                Ok(_) => self.check_winner(),
+            };
+            if let Some(winner) = winner {
+                self.emit(GameEvent::GameEnded { winner });
+            }
+        }
+        result
+    }
+
+    fn execute(&mut self, action: &GameAction) -> Result<u32, &'static str> {
+        match action {
+            GameAction::Place { player, pos } => {
+                if *player != self.current_player {
+                    return Err(tr("error.not_your_turn"));
+                }
+                self.place_piece(pos.0, pos.1)
+            }
+            GameAction::Capture { player, pos } => {
+                if *player != self.current_player {
+                    return Err(tr("error.not_your_turn"));
+                }
+                self.capture_piece(pos.0, pos.1).map(|_| 0)
+            }
+            GameAction::Move { player, from, to } => {
+                if *player != self.current_player {
+                    return Err(tr("error.not_your_turn"));
+                }
+                self.move_piece(*from, *to)
+            }
+            GameAction::Reward { .. } => Err(tr("error.reward_action")),
+            GameAction::Clock { .. } | GameAction::Timeout { .. } | GameAction::Resign { .. } => {
+                Err(tr("error.record_action"))
+            }
+        }
+    }
+
+    /// 解析动作记号，记号的含义取决于当前阶段：
+    /// 落子 "2,3"，吃棋 "x2,3"，移动 "2,3-2,4"
+    pub fn parse_action(&self, input: &str) -> Result<GameAction, &'static str> {
+        let input = input.trim();
+        let player = self.current_player;
+
+        if let Some(coord) = input.strip_prefix('x') {
+            let pos = parse_coord(coord)?;
+            return Ok(GameAction::Capture { player, pos });
+        }
+
+        if let Some((from, to)) = input.split_once('-') {
+            let from = parse_coord(from)?;
+            let to = parse_coord(to)?;
+            return Ok(GameAction::Move { player, from, to });
+        }
+
+        let pos = parse_coord(input)?;
+        match self.phase {
+            GamePhase::Capture => Ok(GameAction::Capture { player, pos }),
+            _ => Ok(GameAction::Place { player, pos }),
+        }
+    }
+
+    /// 局面字符串，格式：
+    /// 棋盘(5行用/分隔):行棋方:阶段:额外落子:黑吃子,白吃子:吃棋方:走子来源:已触发奖励(十六进制位掩码)
+    /// 例如初始局面为 "...../...../...../...../.....:b:p:0:0,0:b:p:0,0,0,0,0,0"
+    pub fn to_position_string(&self) -> String {
+        let rows: Vec<String> = self
+            .grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell {
+                        Cell::Empty => '.',
+                        Cell::Occupied(Player::Black) => 'b',
+                        Cell::Occupied(Player::White) => 'w',
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let player_char = |p: Player| match p {
+            Player::Black => 'b',
+            Player::White => 'w',
+        };
+        let phase = match self.phase {
+            GamePhase::Placement => 'p',
+            GamePhase::Capture => 'c',
+            GamePhase::Movement => 'm',
+        };
+        let origin = match self.movement_phase_origin {
+            MovementPhaseOrigin::FromPlacement => 'p',
+            MovementPhaseOrigin::FromCapture => 'c',
+            MovementPhaseOrigin::FromMovement => 'm',
+        };
+        let capture = |p: Player| self.capture_remaining.get(&p).copied().unwrap_or(0);
+        let mask = |set: &HashSet<usize>| set.iter().fold(0u32, |m, &i| m | (1 << i));
+        let squares = self
+            .triggered_squares
+            .iter()
+            .fold(0u32, |m, &[r, c]| m | (1 << (r * 4 + c)));
+
+        format!(
+            "{}:{}:{}:{}:{},{}:{}:{}:{:x},{:x},{:x},{:x},{:x},{:x}",
+            rows.join("/"),
+            player_char(self.current_player),
+            phase,
+            self.extra_moves,
+            capture(Player::Black),
+            capture(Player::White),
+            player_char(self.capture_turn),
+            origin,
+            squares,
+            mask(&self.triggered_tris),
+            mask(&self.triggered_tetras),
+            mask(&self.triggered_rows),
+            mask(&self.triggered_cols),
+            mask(&self.triggered_dragons),
+        )
+    }
+
+    /// 从局面字符串恢复棋盘（不含棋谱）
+    pub fn from_position_string(input: &str) -> Result<Board, &'static str> {
+        let err = tr("error.position_string");
+
+        let fields: Vec<&str> = input.trim().split(':').collect();
+        if fields.len() != 8 {
+            return Err(err);
+        }
+
+        let parse_player = |s: &str| match s {
+            "b" => Ok(Player::Black),
+            "w" => Ok(Player::White),
+            _ => Err(err),
+        };
+
+        let mut board = Board::new();
+
+        let rows: Vec<&str> = fields[0].split('/').collect();
+        if rows.len() != 5 {
+            return Err(err);
+        }
+        for (r, row) in rows.iter().enumerate() {
+            if row.chars().count() != 5 {
+                return Err(err);
+            }
+            for (c, ch) in row.chars().enumerate() {
+                board.grid[r][c] = match ch {
+                    '.' => Cell::Empty,
+                    'b' => Cell::Occupied(Player::Black),
+                    'w' => Cell::Occupied(Player::White),
+                    _ => return Err(err),
+                };
+            }
+        }
+
+        board.current_player = parse_player(fields[1])?;
+        board.phase = match fields[2] {
+            "p" => GamePhase::Placement,
+            "c" => GamePhase::Capture,
+            "m" => GamePhase::Movement,
+            _ => return Err(err),
+        };
+        board.extra_moves = fields[3].parse().map_err(|_| err)?;
+
+        let (black, white) = fields[4].split_once(',').ok_or(err)?;
+        board
+            .capture_remaining
+            .insert(Player::Black, black.parse().map_err(|_| err)?);
+        board
+            .capture_remaining
+            .insert(Player::White, white.parse().map_err(|_| err)?);
+
+        board.capture_turn = parse_player(fields[5])?;
+        board.movement_phase_origin = match fields[6] {
+            "p" => MovementPhaseOrigin::FromPlacement,
+            "c" => MovementPhaseOrigin::FromCapture,
+            "m" => MovementPhaseOrigin::FromMovement,
+            _ => return Err(err),
+        };
+
+        let masks = fields[7]
+            .split(',')
+            .map(|m| u32::from_str_radix(m, 16).map_err(|_| err))
+            .collect::<Result<Vec<u32>, _>>()?;
+        if masks.len() != 6 {
+            return Err(err);
+        }
+        let bits = |mask: u32, count: usize| (0..count).filter(move |i| mask & (1 << i) != 0);
+        board.triggered_squares = bits(masks[0], 16).map(|i| [i / 4, i % 4]).collect();
+        board.triggered_tris = bits(masks[1], 4).collect();
+        board.triggered_tetras = bits(masks[2], 4).collect();
+        board.triggered_rows = bits(masks[3], 5).collect();
+        board.triggered_cols = bits(masks[4], 5).collect();
+        board.triggered_dragons = bits(masks[5], 2).collect();
+
+        board.update_reward_pieces();
+        Ok(board)
+    }
+
+    /// 局面哈希（对局面字符串做 FNV-1a），不同程序版本之间保持一致
+    pub fn position_hash(&self) -> u64 {
+        fnv1a(&self.to_position_string())
+    }
+}
+
+/// FNV-1a 哈希，结果与平台和程序版本无关，可以写入文件
+pub fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// 棋谱重放器
+pub struct GameReplayer {
+    actions: Vec<GameAction>,
+    current_step: usize,
+    board: Board,
+}
+
+impl GameReplayer {
+    /// 按棋谱创建重放器，从开局开始
+    pub fn new(actions: Vec<GameAction>) -> Self {
+        GameReplayer {
+            actions,
+            current_step: 0,
+            board: Board::new(),
+        }
+    }
+
+    /// 执行下一条记录，棋谱已结束时返回 None
+    pub fn step_forward(&mut self) -> Option<&Board> {
+        if self.current_step >= self.actions.len() {
+            return None;
+        }
+
+        let action = &self.actions[self.current_step];
+        match action {
+            GameAction::Place { pos, .. } => {
+                self.board.place_piece(pos.0, pos.1).ok();
+            }
+            GameAction::Capture { pos, .. } => {
+                self.board.capture_piece(pos.0, pos.1).ok();
+            }
+            GameAction::Move { from, to, .. } => {
+                self.board.move_piece(*from, *to).ok();
+            }
+            GameAction::Timeout { player } => {
+                self.board.record_timeout(*player);
+            }
+            GameAction::Resign { player } => {
+                self.board.record_resign(*player);
+            }
+            _ => {} // 奖励模式不需要执行操作
+        }
+
+        self.current_step += 1;
+        Some(&self.board)
+    }
+
+    /// 回到开局
+    pub fn reset(&mut self) {
+        self.current_step = 0;
+        self.board = Board::new();
+    }
+
+    /// 当前局面
+    pub fn get_current_board(&self) -> &Board {
+        &self.board
+    }
+
+    /// 按步划分棋谱：每步从一个落子/吃子/移动开始，包含其后的奖励、计时等记录
+    pub fn steps(&self) -> Vec<&[GameAction]> {
+        let mut starts: Vec<usize> = self
+            .actions
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| a.is_play())
+            .map(|(i, _)| i)
+            .collect();
+        starts.push(self.actions.len());
+        starts.windows(2).map(|w| &self.actions[w[0]..w[1]]).collect()
+    }
+
+    /// 已执行的步数
+    pub fn step(&self) -> usize {
+        self.actions[..self.current_step]
+            .iter()
+            .filter(|a| a.is_play())
+            .count()
+    }
+
+    /// 跳到第 step 步执行完之后的局面（0 为开局）
+    pub fn seek(&mut self, step: usize) {
+        if step < self.step() {
+            self.reset();
+        }
+        while self.step() < step && self.step_forward().is_some() {}
+        // 连同该步附带的奖励等记录一起跳过
+        while self.current_step < self.actions.len()
+            && !self.actions[self.current_step].is_play()
+        {
+            self.step_forward();
+        }
+    }
+}
+
+/// 棋盘坐标 (行, 列)，均为 0-4
+pub type Coord = (usize, usize);
+
+/// 解析坐标输入 "行,列"
+pub fn parse_coord(input: &str) -> Result<Coord, &'static str> {
+    let parts: Vec<&str> = input.split(',').collect();
+    if parts.len() != 2 {
+        return Err(tr("error.coord_format"));
+    }
+
+    let row = parts[0]
+        .parse::<usize>()
+        .map_err(|_| tr("error.row_number"))?;
+    let col = parts[1]
+        .parse::<usize>()
+        .map_err(|_| tr("error.col_number"))?;
+
+    if row > 4 || col > 4 {
+        return Err(tr("error.coord_range"));
+    }
+
+    Ok((row, col))
+}
+
+/// 解析移动输入 "行,列 行,列"
+pub fn parse_move(input: &str) -> Result<(Coord, Coord), &'static str> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    if parts.len() != 2 {
+        return Err(tr("error.move_format"));
+    }
+
+    let from = parse_coord(parts[0])?;
+    let to = parse_coord(parts[1])?;
+
+    Ok((from, to))
+}
+
+/// 规则说明的各条（消息编号），图形界面和终端共用
+pub const RULES: [&str; 6] = [
+    "rules.phases",
+    "rules.placement",
+    "rules.rewards",
+    "rules.capture",
+    "rules.movement",
+    "rules.victory",
+];
+
+/// 黑方视角的分值：普通分值以一子为 1.00，必胜/必败显示步数
+pub fn format_score(score: i32) -> String {
+    if ai::is_decisive(score) {
+        let plies = ai::WIN_SCORE - score.abs();
+        let winner = if score > 0 { Player::Black } else { Player::White };
+        trf("score.forced_win", &[&winner, &plies])
+    } else {
+        format!("{:+.2}", score as f32 / 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 旧版引擎（吃子次数不会自动放弃）下下完的一局，以及它的终局局面
+    const OLD_GAME: &str = "3,1 1,2 0,0 4,0 3,0 2,3 2,4 3,4 0,1 2,0 0,3 4,4 0,4 3,2 2,2 1,0 2,1 4,3 4,2 1,1 \
+        1,3 4,1 1,4 3,3 0,2 x3,1 x1,2 1,1-1,2 0,1-1,1 4,1-3,1 1,1-0,1 1,2-1,1 0,2-1,2 x4,0 3,1-4,1 \
+        3,0-3,1 2,0-3,0 2,1-2,0 x3,4 3,0-4,0 2,4-3,4 4,0-3,0 3,1-2,1 3,0-4,0 3,4-2,4 4,4-3,4 2,0-3,0 \
+        1,0-2,0 0,0-1,0 4,3-4,4 4,2-4,3 3,2-3,1 1,2-0,2 4,1-4,2 3,3-3,2 x2,3 4,0-4,1 4,3-3,3 4,2-4,3 \
+        1,0-0,0 1,1-1,0 2,2-1,2 x4,4 4,1-4,2 2,1-1,1 x4,3 2,0-2,1 3,2-2,2 3,4-4,4 3,0-4,0 4,2-4,1 \
+        2,4-3,4 1,0-2,0 1,1-1,0 3,1-3,0 1,4-2,4 4,1-4,2 4,0-4,1 4,4-4,3 1,3-2,3 x2,1 x4,3 2,0-2,1 \
+        0,4-1,4 3,0-4,0 0,3-1,3 x4,2";
+    const OLD_GAME_END: &str = "bbb../b.bbb/.wbbb/...bb/wb...:b:c:2:1,0:b:m:8ce,e,7,1,0,0";
+
+    fn skipped(events: &[GameEvent]) -> Vec<(Player, u32)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::CaptureSkipped { player, remaining } => Some((*player, *remaining)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn remaining_captures_are_skipped_once_every_target_is_protected() {
+        // 白方还有两次吃子，黑方左上角的方受保护，只有 4,4 可以吃
+        let mut board =
+            Board::from_position_string("bb.../bb.../..ww./..ww./....b:w:c:0:0,2:w:m:1,0,0,0,0,0").unwrap();
+        assert_eq!(board.legal_actions(), vec![GameAction::Capture { player: Player::White, pos: (4, 4) }]);
+
+        board.apply_action(&GameAction::Capture { player: Player::White, pos: (4, 4) }).unwrap();
+        assert_eq!(skipped(&board.take_events()), vec![(Player::White, 1)]);
+        assert_eq!(board.capture_remaining(Player::White), 0);
+        assert_eq!(board.phase, GamePhase::Movement);
+        assert_eq!(board.current_player(), Player::Black);
+        assert!(!board.legal_actions().is_empty());
+    }
+
+    #[test]
+    fn forming_move_without_targets_continues_as_a_normal_move() {
+        // 白方只剩右下角受保护的方，黑方 2,1-1,1 成方但无子可吃
+        let mut board =
+            Board::from_position_string("bb.../b..../.b.../...ww/...ww:b:m:0:0,0:b:m:8000,0,0,0,0,0").unwrap();
+
+        let action = GameAction::Move { player: Player::Black, from: (2, 1), to: (1, 1) };
+        assert_eq!(board.apply_action(&action), Ok(0));
+        assert_eq!(skipped(&board.take_events()), vec![(Player::Black, 1)]);
+        assert_eq!(board.phase, GamePhase::Movement);
+        assert_eq!(board.capture_remaining(Player::Black), 0);
+        assert_eq!(board.current_player(), Player::White);
+        // 成方照常记录，新方里的棋子同样受保护
+        assert!(board.game_record.contains(&GameAction::Reward {
+            player: Player::Black,
+            pattern: RewardPattern::Square { top_left: (0, 0) },
+        }));
+        assert!(board.legal_actions().iter().all(|a| matches!(a, GameAction::Move { .. })));
+    }
+
+    #[test]
+    fn games_from_before_capture_skipping_still_replay() {
+        let mut board = Board::new();
+        for notation in OLD_GAME.split_whitespace() {
+            let action = board.parse_action(notation).unwrap();
+            assert!(board.legal_actions().contains(&action), "{notation}");
+            board.apply_action(&action).unwrap();
+            assert!(skipped(&board.take_events()).is_empty(), "{notation}");
+        }
+        assert_eq!(board.to_position_string(), OLD_GAME_END);
+        assert_eq!(board.check_winner(), Some(Player::Black));
+    }
+
+    #[test]
+    fn actions_report_events_in_order() {
+        use GameEvent::*;
+        let (b, w) = (Player::Black, Player::White);
+        let mut board =
+            Board::from_position_string("bb.bb/wwwb./wbbwb/bbw.w/w.bww:w:p:0:0,0:b:p:0,0,0,0,0,0").unwrap();
+        let mut play = |notation: &str| {
+            let action = board.parse_action(notation).unwrap();
+            assert_eq!(board.apply_action(&action).map(|_| ()), Ok(()), "{notation}");
+            board.take_events()
+        };
+
+        assert_eq!(play("4,1"), vec![PiecePlaced { player: w, pos: (4, 1) }, TurnChanged { player: b }]);
+        // 获得额外落子时行棋方不变，不产生 TurnChanged
+        assert_eq!(
+            play("1,4"),
+            vec![
+                PiecePlaced { player: b, pos: (1, 4) },
+                PatternFormed { player: b, pattern: RewardPattern::Square { top_left: (0, 3) }, reward: 1 },
+            ]
+        );
+        play("3,3");
+        // 满盘时先报告奖励，再进入吃棋阶段，最后轮换行棋方
+        assert_eq!(
+            play("0,2"),
+            vec![
+                PiecePlaced { player: b, pos: (0, 2) },
+                PatternFormed { player: b, pattern: RewardPattern::Tri { id: 1 }, reward: 1 },
+                PatternFormed { player: b, pattern: RewardPattern::Row { index: 0 }, reward: 2 },
+                PhaseChanged { phase: GamePhase::Capture },
+                TurnChanged { player: w },
+            ]
+        );
+        assert_eq!(play("x3,1"), vec![PieceCaptured { player: w, pos: (3, 1) }, TurnChanged { player: b }]);
+        assert_eq!(
+            play("x1,2"),
+            vec![
+                PieceCaptured { player: b, pos: (1, 2) },
+                PhaseChanged { phase: GamePhase::Movement },
+                TurnChanged { player: w },
+            ]
+        );
+        assert_eq!(
+            play("1,1-1,2"),
+            vec![PieceMoved { player: w, from: (1, 1), to: (1, 2) }, TurnChanged { player: b }]
+        );
+        // 不合法的动作不改变局面，也不产生事件
+        assert!(board.apply_action(&GameAction::Move { player: b, from: (0, 0), to: (2, 2) }).is_err());
+        assert!(board.take_events().is_empty());
+    }
+
+    #[test]
+    fn forfeiting_move_ends_the_game() {
+        // 黑方 2,1-1,1 堵死白方全部棋子，黑方判负
+        let mut board =
+            Board::from_position_string("wwwb./b.b../.b.../...../.....:b:m:0:0,0:b:m:0,0,0,0,0,0").unwrap();
+        let action = GameAction::Move { player: Player::Black, from: (2, 1), to: (1, 1) };
+        assert!(board.apply_action(&action).is_err());
+        assert_eq!(
+            board.take_events(),
+            vec![
+                GameEvent::PieceMoved { player: Player::Black, from: (2, 1), to: (1, 1) },
+                GameEvent::GameEnded { winner: Player::White },
+            ]
+        );
+    }
+}
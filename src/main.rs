@@ -1,1796 +1,1105 @@
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::fmt;
-use std::io::{self, Write};
-use std::sync::{Arc, Mutex};
+// 五道方图形界面，命令行参数中有子命令时转交 cli 模块执行
+
+use std::path::PathBuf;
 use std::thread;
 use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum Player {
-    Black,
-    White,
-}
+use wudaofang::i18n::{self, tr, trf};
+use wudaofang::{Board, Cell, GameAction, GameEvent, GamePhase, GameReplayer, Player, RULES, RewardPattern, Threat, format_score};
+use wudaofang::{ai, analysis, autosave, book, clock, net, protocol, savegame};
+#[cfg(feature = "cli")]
+use wudaofang::cli;
 
-impl Player {
-    fn opponent(self) -> Self {
-        match self {
-            Player::Black => Player::White,
-            Player::White => Player::Black,
-        }
-    }
+mod animation;
+mod fonts;
+mod theme;
 
-    fn as_char(&self) -> char {
-        match self {
-            Player::Black => '●', // 黑色实心圆
-            Player::White => '○', // 白色空心圆
-        }
-    }
+use eframe::egui::{self, ViewportBuilder};
+use eframe::egui::{Stroke, FontId, Align2, RichText};
+use std::f32::consts::PI;
 
-    fn color_name(&self) -> &str {
-        match self {
-            Player::Black => "黑方",
-            Player::White => "白方",
-        }
-    }
-}
+use animation::{AnimationSpeed, Animator, Effect};
+use theme::Theme;
+fn main() -> eframe::Result<()> {
+    let mut args: Vec<String> = std::env::args().collect();
+    if let Err(e) = i18n::init_from_args(&mut args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    // 命令行模式：各子命令见 cli::run_command
+    #[cfg(feature = "cli")]
+    match cli::run_command(&args[1..]) {
+        Some(0) => return Ok(()),
+        Some(code) => std::process::exit(code),
+        None => {}
+    }
+
+    // --engine <命令> 让外部引擎执白方
+    let engine = match args.iter().position(|a| a == "--engine") {
+        Some(i) => match args.get(i + 1).map(|cmd| protocol::ExternalEngine::spawn_command_line(cmd)) {
+            Some(Ok(engine)) => Some(protocol::EngineWorker::spawn(engine)),
+            Some(Err(e)) => {
+                eprintln!("{}", e);
+                None
+            }
+            None => {
+                eprintln!("{}", tr("arg.engine_command"));
+                None
+            }
+        },
+        None => None,
+    };
 
-impl fmt::Display for Player {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.color_name())
+    // 中文字体：--font <文件> 或 WUDAOFANG_FONT 指定，否则在系统字体目录中查找
+    let configured_font = match args.iter().position(|a| a == "--font") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => {
+                eprintln!("{}", tr("arg.font_path"));
+                None
+            }
+        },
+        None => std::env::var_os("WUDAOFANG_FONT").map(PathBuf::from),
+    };
+    let font = fonts::load_cjk_font(configured_font.as_deref());
+    let font_missing = font.is_none();
+    if font_missing {
+        // egui 自带字体显示不了中文，改用英文界面
+        i18n::set_lang(i18n::Lang::En);
+        eprintln!("{}", tr("font.not_found"));
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum Cell {
-    Empty,
-    Occupied(Player),
-}
+    let options = eframe::NativeOptions {
+        viewport: ViewportBuilder::default().with_inner_size([800.0, 600.0]),
+        ..Default::default()
+    };
 
-impl fmt::Display for Cell {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Cell::Empty => write!(f, "·"), // 使用点表示空位
-            Cell::Occupied(p) => write!(f, "{}", p.as_char()),
-        }
-    }
-}
+    eframe::run_native(
+        tr("gui.title"),
+        options,
+        Box::new(move |cc| {
+            if let Some(font) = font {
+                fonts::install(&cc.egui_ctx, font);
+            }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
-pub enum GamePhase {
-    Placement, // 落子阶段
-    Capture,   // 吃棋阶段
-    Movement,  // 走子阶段
+            let mut app = WudaoApp::new();
+            if font_missing {
+                app.message = tr("font.not_found").to_string();
+            }
+            app.engine = engine;
+            app.resume = match autosave::load() {
+                Some(Ok(board)) => Some(board),
+                Some(Err(e)) => {
+                    app.message = trf("autosave.load_failed", &[&e]);
+                    None
+                }
+                None => None,
+            };
+            Box::new(app)
+        }),
+    )
 }
 
-impl fmt::Display for GamePhase {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            GamePhase::Placement => write!(f, "落子阶段"),
-            GamePhase::Capture => write!(f, "吃棋阶段"),
-            GamePhase::Movement => write!(f, "走子阶段"),
-        }
-    }
+struct WudaoApp {
+    board: Board,
+    selected_cell: Option<(usize, usize)>,
+    message: String,
+    game_over: bool,
+    show_help: bool,
+    input_mode: InputMode,
+    time: f32, // 用于动画效果的时间变量
+    engine: Option<protocol::EngineWorker>, // 外部引擎（执白方）
+    net: Option<net::NetSession>, // 联机对战
+    net_addr: String,             // 联机地址输入框
+    show_net: bool,
+    time_control: clock::TimeControl, // 新对局使用的计时方式
+    clock: clock::GameClock,          // 本局时钟（联机对局不计时）
+    show_threats: bool,               // 显示双方即将形成的奖励模式
+    show_patterns: bool,              // 标出生效中的奖励模式
+    hint: Option<(u64, GameAction)>,  // 提示的动作及其对应局面的哈希
+    hint_search: Option<(u64, std::sync::mpsc::Receiver<ai::SearchResult>)>,
+    analyzer: Option<analysis::Analyzer>, // 分析模式
+    book: Option<book::Book>,             // 开局库（首次打开开局浏览器时加载）
+    show_book: bool,
+    replay: Option<Replay>,               // 回放模式
+    replay_path: String,                  // 回放的棋谱文件
+    show_replay: bool,
+    save_path: Option<String>,          // 当前对局的存档文件
+    save_kind: savegame::SaveKind,      // 当前存档文件的格式
+    file_dialog: Option<FileDialog>,    // 打开的文件对话框
+    autosaved_len: usize,               // 自动存档时的棋谱长度
+    archived: bool,                     // 已结束的对局是否已归档
+    resume: Option<Board>,              // 启动时发现的未完成对局
+    animator: Animator,                 // 棋盘动画
+    show_history: bool,                 // 显示着法记录面板
+    history_view: Option<(usize, Board)>, // 正在查看的历史局面：着法记录中的序号及该条记录之后的局面
+    history_base: (usize, Board),         // 对局开始时的棋谱长度和局面，快照存档不一定能从开局重放
+    theme: &'static Theme,              // 界面配色
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum RewardPattern {
-    Square { top_left: (usize, usize) }, // 成方
-    Tri { id: usize },                   // 成三斜
-    Tetra { id: usize },                 // 成四斜
-    Row { index: usize },                // 成州(行)
-    Col { index: usize },                // 成州(列)
-    Dragon { id: usize },                // 成龙
+// 文件对话框
+enum FileDialog {
+    SaveAs { path: String, kind: savegame::SaveKind },
+    Open { path: String },
 }
 
-impl fmt::Display for RewardPattern {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            RewardPattern::Square { top_left: (r, c) } => write!(f, "成方[位置:({},{})]", r, c),
-            RewardPattern::Tri { id } => write!(f, "成三斜[模式:{}]", id),
-            RewardPattern::Tetra { id } => write!(f, "成四斜[模式:{}]", id),
-            RewardPattern::Row { index } => write!(f, "成州[行:{}]", index),
-            RewardPattern::Col { index } => write!(f, "成州[列:{}]", index),
-            RewardPattern::Dragon { id } => write!(f, "成龙[对角线:{}]", id),
-        }
-    }
+// 回放状态
+struct Replay {
+    replayer: GameReplayer,
+    autoplay: bool,
+    speed: f32,     // 自动播放速度（步/秒）
+    next_tick: f32, // 自动播放下一步的时间
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum GameAction {
-    Place {
-        player: Player,
-        pos: (usize, usize),
-    },
-    Capture {
-        player: Player,
-        pos: (usize, usize),
-    },
-    Move {
-        player: Player,
-        from: (usize, usize),
-        to: (usize, usize),
-    },
-    Reward {
-        player: Player,
-        pattern: RewardPattern,
-    },
+// 着法记录面板中的一行
+struct HistoryLine {
+    index: usize,  // 在棋谱中的序号
+    turn: usize,   // 所属回合
+    indent: bool,  // 回合内的后续记录缩进显示
+    text: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Board {
-    grid: [[Cell; 5]; 5], // 5x5棋盘
-    current_player: Player,
-    phase: GamePhase,
-    // 落子阶段专用
-    extra_moves: u32, // 额外落子次数
-    // 吃棋阶段专用
-    capture_remaining: HashMap<Player, u32>, // 剩余吃子数量
-    capture_turn: Player,                    // 当前吃棋玩家
-    // 用于记录已触发的奖励模式
-    triggered_squares: HashSet<[usize; 2]>, // 成方 [左上角行, 左上角列]
-    triggered_tris: HashSet<usize>,         // 成三斜 0-3
-    triggered_tetras: HashSet<usize>,       // 成四斜 0-3
-    triggered_rows: HashSet<usize>,         // 成州行 0-4
-    triggered_cols: HashSet<usize>,         // 成州列 0-4
-    triggered_dragons: HashSet<usize>,      // 成龙 0-1
-    // 奖励模式保护的棋子
-    reward_pieces: HashMap<Player, HashSet<(usize, usize)>>,
-    // 游戏记录
-    game_record: Vec<GameAction>,
-    movement_phase_origin: MovementPhaseOrigin, // 添加这个字段
-}
+// 外部引擎每步的思考时间
+const ENGINE_MOVETIME: Duration = Duration::from_millis(1000);
+// 提示的搜索时间
+const HINT_MOVETIME: Duration = Duration::from_millis(800);
+// 分析面板显示的候选动作数
+const ANALYSIS_CANDIDATES: usize = 5;
+// 开局浏览器显示的着法数
+const BOOK_EXPLORER_MOVES: usize = 8;
 
-// 添加枚举来标识进入移动阶段的方式
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
-pub enum MovementPhaseOrigin {
-    FromPlacement, // 从落子阶段进入（满盘后）
-    FromCapture,   // 从吃棋阶段进入
-    FromMovement,  // 从移动阶段自身进入（如吃棋后返回）
+#[derive(PartialEq)]
+enum InputMode {
+    Placement,
+    Capture,
+    MovementFrom,
+    MovementTo,
 }
 
-
-impl Board {
-    pub fn new() -> Self {
-        let mut board = Board {
-            grid: [[Cell::Empty; 5]; 5],
-            current_player: Player::Black,
-            phase: GamePhase::Placement,
-            extra_moves: 0,
-            capture_remaining: HashMap::new(),
-            capture_turn: Player::Black,
-            triggered_squares: HashSet::new(),
-            triggered_tris: HashSet::new(),
-            triggered_tetras: HashSet::new(),
-            triggered_rows: HashSet::new(),
-            triggered_cols: HashSet::new(),
-            triggered_dragons: HashSet::new(),
-            reward_pieces: HashMap::new(),
-            game_record: Vec::new(),
-            movement_phase_origin: MovementPhaseOrigin::FromPlacement, // 默认从落子阶段进入
+impl WudaoApp {
+    fn new() -> Self {
+        Self {
+            board: Board::new(),
+            selected_cell: None,
+            message: String::new(),
+            game_over: false,
+            show_help: true,
+            input_mode: InputMode::Placement,
+            time: 0.0,
+            engine: None,
+            net: None,
+            net_addr: net::DEFAULT_ADDR.to_string(),
+            show_net: false,
+            time_control: clock::TimeControl::Unlimited,
+            clock: clock::GameClock::new(clock::TimeControl::Unlimited, Player::Black, Instant::now()),
+            show_threats: false,
+            show_patterns: true,
+            hint: None,
+            hint_search: None,
+            analyzer: None,
+            book: None,
+            show_book: false,
+            replay: None,
+            replay_path: "wudao_game_record.json".to_string(),
+            show_replay: false,
+            save_path: None,
+            save_kind: savegame::SaveKind::Record,
+            file_dialog: None,
+            autosaved_len: 0,
+            archived: false,
+            resume: None,
+            animator: Animator::new(AnimationSpeed::Normal),
+            show_history: true,
+            history_view: None,
+            history_base: (0, Board::new()),
+            theme: theme::load(),
+        }
+    }
+
+    // 从给定局面开始新对局，保留引擎、联机和界面设置
+    fn start_game(&mut self, board: Board) {
+        let mut engine = self.engine.take();
+        if let Some(e) = engine.as_mut() {
+            e.cancel();
+        }
+        let net = self.net.take();
+        let net_addr = std::mem::take(&mut self.net_addr);
+        let time_control = self.time_control;
+        let show_threats = self.show_threats;
+        let show_patterns = self.show_patterns;
+        let show_history = self.show_history;
+        let theme = self.theme;
+        let analyzer = self.analyzer.take();
+        let book = self.book.take();
+        let show_book = self.show_book;
+        let replay_path = std::mem::take(&mut self.replay_path);
+        let animation_speed = self.animator.speed();
+        *self = Self::new();
+        self.animator.set_speed(animation_speed);
+        self.show_threats = show_threats;
+        self.show_patterns = show_patterns;
+        self.show_history = show_history;
+        self.theme = theme;
+        self.analyzer = analyzer;
+        self.book = book;
+        self.show_book = show_book;
+        self.replay_path = replay_path;
+        self.engine = engine;
+        self.net_addr = net_addr;
+        self.time_control = time_control;
+        self.net = net;
+
+        self.history_base = (board.get_game_record().len(), board.clone());
+        self.board = board;
+        self.clock = clock::GameClock::new(time_control, self.board.current_player(), Instant::now());
+        self.input_mode = match self.board.get_state().0 {
+            GamePhase::Placement => InputMode::Placement,
+            GamePhase::Capture => InputMode::Capture,
+            GamePhase::Movement => InputMode::MovementFrom,
         };
-
-        // 初始化奖励棋子保护集
-        board.update_reward_pieces();
-        board
-    }
-
-    pub fn admit_defeat(&self, remark : &String) -> bool {
-        if remark.eq("f") {
-            print!("玩家{}，认输！", self.current_player);
-            return true; 
+        if let Some(winner) = self.board.check_winner() {
+            self.message = trf("gui.game_over_winner", &[&winner]);
+            self.game_over = true;
+            self.clock.stop(Instant::now());
         }
-        false
-    }
-
-    // 获取当前游戏状态
-    pub fn get_state(&self) -> (GamePhase, Player) {
-        (self.phase.clone(), self.current_player)
-    }
-
-    // 获取游戏记录
-    pub fn get_game_record(&self) -> &Vec<GameAction> {
-        &self.game_record
-    }
-
-    // // 打印棋盘
-    // pub fn print_board(&self) {
-    //     println!("  0 1 2 3 4");
-    //     for (i, row) in self.grid.iter().enumerate() {
-    //         print!("{} ", i);
-    //         for cell in row {
-    //             print!("{} ", cell);
-    //         }
-    //         println!();
-    //     }
-    // }
-
-    // 打印棋盘（增强版）
-    pub fn print_board(&self) {
-        println!("\n  0 1 2 3 4  ← 列坐标");
-        for (i, row) in self.grid.iter().enumerate() {
-            print!("{} ", i); // 行坐标
-            for cell in row {
-                print!("{} ", cell);
-            }
-            println!();
+        // 打开的是已结束的对局时不再重复归档
+        self.archived = self.game_over;
+        // 新对局替换掉旧的自动存档
+        if self.board.get_game_record().is_empty() {
+            autosave::clear();
         }
-        println!("↑ 行坐标");
-    }
-
-    // 打印游戏状态
-    // pub fn print_game_status(&self) {
-    //     println!("\n===== 游戏状态 =====");
-    //     println!("当前阶段: {}", self.phase);
-    //     println!("当前玩家: {}", self.current_player);
-
-    //     match self.phase {
-    //         GamePhase::Placement => {
-    //             if self.extra_moves > 0 {
-    //                 println!("额外落子次数: {}", self.extra_moves);
-    //             }
-    //             println!("提示: 请输入落子位置 (格式: 行,列), 例如: 2,3");
-    //         }
-    //         GamePhase::Capture => {
-    //             let remaining = self.capture_remaining.get(&self.current_player)
-    //                 .copied().unwrap_or(0);
-    //             println!("剩余吃子数量: {}", remaining);
-    //             println!("提示: 请输入吃子位置 (格式: 行,列), 例如: 1,2");
-    //         }
-    //         GamePhase::Movement => {
-    //             println!("提示: 请输入要移动的棋子位置和目标位置 (格式: 原行,原列 新行,新列), 例如: 1,2 1,3");
-    //         }
-    //     }
-    // }
-
-    pub fn print_game_status(&self) {
-        println!("\n===== 游戏状态 =====");
-        println!("当前阶段: {}", self.phase);
-        println!("当前玩家: {}", self.current_player);
-
-        match self.phase {
-            GamePhase::Placement => {
-                if self.extra_moves > 0 {
-                    println!("额外落子次数: {}", self.extra_moves);
-                }
-                println!("提示: 请输入落子位置 (格式: 行,列), 例如: 2,3");
-            }
-            GamePhase::Capture => {
-                let remaining = self
-                    .capture_remaining
-                    .get(&self.current_player)
-                    .copied()
-                    .unwrap_or(0);
-                println!("剩余吃子数量: {}", remaining);
-                println!("提示: 请输入吃子位置 (格式: 行,列), 例如: 1,2");
-                println!("注意: 不能吃受保护棋子（在奖励模式中的棋子）");
-            }
-            GamePhase::Movement => {
-                println!(
-                    "提示: 请输入要移动的棋子位置和目标位置 (格式: 原行,原列 新行,新列), 例如: 1,2 1,3"
-                );
-                println!("注意: 只能移动到相邻位置（上下左右）");
-            }
+        if let Some(net) = self.net.as_mut() {
+            net.send_state(&self.board, None);
         }
     }
 
-    // 记录游戏动作
-    fn record_action(&mut self, action: GameAction) {
-        self.game_record.push(action);
-    }
-
-    // 检查位置是否有效
-    fn is_valid_pos(row: usize, col: usize) -> bool {
-        row < 5 && col < 5
-    }
-
-    // 检查棋盘是否已满
-    pub fn is_full(&self) -> bool {
-        self.grid
-            .iter()
-            .all(|row| row.iter().all(|c| *c != Cell::Empty))
-    }
-
-    // 获取玩家棋子位置
-    pub fn player_pieces(&self, player: Player) -> Vec<(usize, usize)> {
-        let mut pieces = Vec::new();
-        for (r, row) in self.grid.iter().enumerate() {
-            for (c, cell) in row.iter().enumerate() {
-                if let Cell::Occupied(p) = cell {
-                    if *p == player {
-                        pieces.push((r, c));
-                    }
-                }
-            }
+    // 每个动作之后自动存档，对局结束时归档棋谱（联机对局不存档）
+    fn autosave(&mut self) {
+        if self.net.is_some() {
+            return;
         }
-        pieces
-    }
-
-    // 检查玩家是否有合法移动
-    pub fn has_legal_moves(&self, player: Player) -> bool {
-        let pieces = self.player_pieces(player);
 
-        // 如果棋子少于3个，无法形成任何模式，自动判负
-        if pieces.len() < 3 {
-            return false;
-        }
-
-        for (r, c) in pieces {
-            // 检查上下左右四个方向
-            let neighbors = [
-                (r.wrapping_sub(1), c),
-                (r + 1, c),
-                (r, c.wrapping_sub(1)),
-                (r, c + 1),
-            ];
-
-            for (nr, nc) in neighbors {
-                if Self::is_valid_pos(nr, nc) && self.grid[nr][nc] == Cell::Empty {
-                    return true;
+        if self.game_over {
+            if !self.archived && !self.board.get_game_record().is_empty() {
+                self.archived = true;
+                match autosave::archive(&self.board) {
+                    Ok(path) => self.message += &trf("gui.archived", &[&path.display()]),
+                    Err(e) => self.message = trf("gui.archive_failed", &[&e]),
                 }
+                autosave::clear();
             }
+            return;
         }
 
-        false
-    }
-
-    // 执行落子
-    pub fn place_piece(&mut self, row: usize, col: usize) -> Result<u32, &'static str> {
-        if self.phase != GamePhase::Placement {
-            return Err("当前不是落子阶段");
-        }
-
-        if !Self::is_valid_pos(row, col) {
-            return Err("位置无效，必须在0-4范围内");
-        }
-
-        if self.grid[row][col] != Cell::Empty {
-            return Err("该位置已有棋子，请选择空位");
-        }
-
-        // 落子
-        self.grid[row][col] = Cell::Occupied(self.current_player);
-
-        // 记录落子动作
-        self.record_action(GameAction::Place {
-            player: self.current_player,
-            pos: (row, col),
-        });
-
-        // 检查奖励并获取额外落子次数
-        let extra = self.check_rewards(row, col);
-
-        // 处理额外落子次数
-        self.extra_moves = self.extra_moves.saturating_add(extra);
-
-        // 消耗一次落子机会
-        if self.extra_moves > 0 {
-            self.extra_moves -= 1;
-        } else {
-            self.current_player = self.current_player.opponent();
-        }
-
-        // 检查是否需要进入吃棋阶段
-        if self.is_full() {
-            self.enter_capture_phase();
+        let len = self.board.get_game_record().len();
+        if len != self.autosaved_len {
+            self.autosaved_len = len;
+            if let Err(e) = autosave::save(&self.board) {
+                self.message = trf("gui.autosave_failed", &[&e]);
+            }
         }
-
-        Ok(extra)
-    }
-
-    // 进入吃棋阶段
-    fn enter_capture_phase(&mut self) {
-        self.phase = GamePhase::Capture;
-
-     // 重置奖励模式记录并重新计算
-    self.triggered_squares.clear();
-    self.triggered_tris.clear();
-    self.triggered_tetras.clear();
-    self.triggered_rows.clear();
-    self.triggered_cols.clear();
-    self.triggered_dragons.clear();
-
-    self.scan_all_rewards();
-
-
-        // 设置吃棋顺序：第二个落子的玩家（白方）先吃棋
-    let first_player = Player::White; // 白方是先吃玩家
-    let second_player = Player::Black; // 黑方是后吃玩家
-
-
-      // 重置吃子数量为0，与落子阶段无关
-    self.capture_remaining = HashMap::new();
-    self.capture_remaining.insert(first_player, 0);
-    self.capture_remaining.insert(second_player, 0);
-
-    // 检查是否有可吃的棋子
-    let first_has_capturable = self.has_capturable_pieces(second_player);
-    let second_has_capturable = self.has_capturable_pieces(first_player);
-
-
-        // 只有当玩家有可吃棋子时才设置吃棋数量
-    if first_has_capturable {
-        self.capture_remaining.insert(first_player, 1);
-    } else {
-        self.capture_remaining.insert(first_player, 0);
     }
 
-    if second_has_capturable {
-        self.capture_remaining.insert(second_player, 1);
-    } else {
-        self.capture_remaining.insert(second_player, 0);
-    }
-
-         // 设置第一个有可吃棋子的玩家为当前玩家
-    if first_has_capturable {
-        self.current_player = first_player;
-        self.capture_turn = first_player;
-    } else if second_has_capturable {
-        self.current_player = second_player;
-        self.capture_turn = second_player;
-    } else {
-        // 如果都没有可吃的棋子，直接进入移动阶段
-        self.enter_movement_phase(MovementPhaseOrigin::FromPlacement);
-        return;
-    }
-    }
+    // 启动时询问是否继续上次未完成的对局
+    fn draw_resume_dialog(&mut self, ctx: &egui::Context) {
+        let Some(board) = &self.resume else {
+            return;
+        };
+        let (phase, player) = board.get_state();
+        let steps = board.get_game_record().iter().filter(|a| a.is_play()).count();
+
+        let mut resume = false;
+        let mut discard = false;
+        egui::Window::new(RichText::new(tr("gui.resume_title")).font(FontId::proportional(16.0)))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(trf("gui.resume_text", &[&phase, &steps, &player]))
+                    .font(FontId::proportional(14.0)));
+                ui.horizontal(|ui| {
+                    resume = ui.button(RichText::new(tr("gui.resume")).font(FontId::proportional(14.0))).clicked();
+                    discard = ui.button(RichText::new(tr("gui.discard")).font(FontId::proportional(14.0))).clicked();
+                });
+            });
 
-    // 检查是否有可吃的棋子
-    fn has_capturable_pieces(&self, opponent: Player) -> bool {
-        let protected = self
-            .reward_pieces
-            .get(&opponent)
-            .cloned()
-            .unwrap_or_default();
-
-        // 检查对手的所有棋子
-        for r in 0..5 {
-            for c in 0..5 {
-                if let Cell::Occupied(p) = self.grid[r][c] {
-                    if p == opponent && !protected.contains(&(r, c)) {
-                        return true;
-                    }
-                }
+        if resume {
+            if let Some(board) = self.resume.take() {
+                self.start_game(board);
+                self.autosaved_len = self.board.get_game_record().len();
+                self.message = tr("gui.resumed").to_string();
             }
+        } else if discard {
+            self.resume = None;
+            autosave::clear();
         }
-        false
     }
 
-    // 扫描所有奖励模式
-    fn scan_all_rewards(&mut self) {
-        for player in [Player::Black, Player::White] {
-            // 扫描成方
-            for r in 0..4 {
-                for c in 0..4 {
-                    if self.is_square(r, c, player) {
-                        self.triggered_squares.insert([r, c]);
-                    }
-                }
-            }
-
-            // 扫描成三斜
-            for id in 0..4 {
-                if self.is_tri(id, player) {
-                    self.triggered_tris.insert(id);
-                }
-            }
-
-            // 扫描成四斜
-            for id in 0..4 {
-                if self.is_tetra(id, player) {
-                    self.triggered_tetras.insert(id);
-                }
-            }
-
-            // 扫描成州（行）
-            for r in 0..5 {
-                if self.is_row(r, player) {
-                    self.triggered_rows.insert(r);
-                }
-            }
-
-            // 扫描成州（列）
-            for c in 0..5 {
-                if self.is_col(c, player) {
-                    self.triggered_cols.insert(c);
-                }
-            }
-
-            // 扫描成龙
-            for id in 0..2 {
-                if self.is_dragon(id, player) {
-                    self.triggered_dragons.insert(id);
-                }
+    // 保存到当前存档文件，还没有存档文件时打开“另存为”
+    fn save_game(&mut self) {
+        match self.save_path.clone() {
+            Some(path) => self.save_game_as(path, self.save_kind),
+            None => {
+                self.file_dialog = Some(FileDialog::SaveAs {
+                    path: "wudao_game_record.json".to_string(),
+                    kind: self.save_kind,
+                });
             }
         }
-
-        // 更新保护棋子
-        self.update_reward_pieces();
     }
 
-    // 计算玩家吃子数量
-    fn calculate_capture_count(&self, player: Player) -> u32 {
-        let mut count = 0;
-
-        // 成方数量
-        for square in &self.triggered_squares {
-            if self.is_square(square[0], square[1], player) {
-                count += 1;
+    fn save_game_as(&mut self, path: String, kind: savegame::SaveKind) {
+        match savegame::save(std::path::Path::new(&path), &self.board, kind) {
+            Ok(()) => {
+                self.message = trf("gui.saved", &[&kind, &path]);
+                self.save_path = Some(path);
+                self.save_kind = kind;
             }
+            Err(e) => self.message = trf("gui.save_failed", &[&e]),
         }
+    }
 
-        // 成三斜数量
-        for id in &self.triggered_tris {
-            if self.is_tri(*id, player) {
-                count += 1;
-            }
+    // 打开棋谱或局面快照，从其最后的局面继续对局
+    fn open_game(&mut self, path: String) {
+        if self.net.as_ref().is_some_and(|n| n.role() == net::Role::Client) {
+            self.message = tr("gui.open_host_only").to_string();
+            return;
         }
-
-        // 成四斜数量
-        for id in &self.triggered_tetras {
-            if self.is_tetra(*id, player) {
-                count += 1;
+        match savegame::load(std::path::Path::new(&path)) {
+            Ok((board, kind)) => {
+                self.start_game(board);
+                if !self.game_over {
+                    self.message = trf("gui.opened", &[&kind, &path]);
+                }
+                self.save_path = Some(path);
+                self.save_kind = kind;
             }
+            Err(e) => self.message = trf("gui.open_failed", &[&e]),
         }
-
-        // 成州数量
-        count += self
-            .triggered_rows
-            .iter()
-            .filter(|&&r| self.is_row(r, player))
-            .count() as u32
-            * 2;
-
-        count += self
-            .triggered_cols
-            .iter()
-            .filter(|&&c| self.is_col(c, player))
-            .count() as u32
-            * 2;
-
-        // 成龙数量
-        count += self
-            .triggered_dragons
-            .iter()
-            .filter(|&&id| self.is_dragon(id, player))
-            .count() as u32
-            * 2;
-
-        count
     }
 
-    // 更新受保护的棋子
-    fn update_reward_pieces(&mut self) {
-        self.reward_pieces.clear();
-
-        // 黑方受保护棋子
-        let mut black_protected = HashSet::new();
-        self.add_reward_pieces(Player::Black, &mut black_protected);
-        self.reward_pieces.insert(Player::Black, black_protected);
-
-        // 白方受保护棋子
-        let mut white_protected = HashSet::new();
-        self.add_reward_pieces(Player::White, &mut white_protected);
-        self.reward_pieces.insert(Player::White, white_protected);
-    }
-
-    // 收集奖励模式中的棋子
-    fn add_reward_pieces(&self, player: Player, protected: &mut HashSet<(usize, usize)>) {
-        // 成方
-        for &[r, c] in &self.triggered_squares {
-            if self.is_square(r, c, player) {
-                protected.insert((r, c));
-                protected.insert((r, c + 1));
-                protected.insert((r + 1, c));
-                protected.insert((r + 1, c + 1));
-            }
-        }
+    // 另存为/打开对话框
+    fn draw_file_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = self.file_dialog.as_mut() else {
+            return;
+        };
+        let title = match dialog {
+            FileDialog::SaveAs { .. } => tr("gui.save_as"),
+            FileDialog::Open { .. } => tr("gui.open"),
+        };
 
-        // 成三斜
-        let tris = [
-            vec![(0, 2), (1, 1), (2, 0)], // 左上三斜
-            vec![(0, 2), (1, 3), (2, 4)], // 右上三斜
-            vec![(2, 0), (3, 1), (4, 2)], // 左下三斜
-            vec![(2, 4), (3, 3), (4, 2)], // 右下三斜
-        ];
-        for id in &self.triggered_tris {
-            if let Some(tri) = tris.get(*id) {
-                if self.is_tri(*id, player) {
-                    for &(r, c) in tri {
-                        protected.insert((r, c));
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new(RichText::new(title).font(FontId::proportional(16.0)))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                match dialog {
+                    FileDialog::SaveAs { path, kind } => {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(tr("gui.file")).font(FontId::proportional(14.0)));
+                            ui.text_edit_singleline(path);
+                        });
+                        ui.horizontal(|ui| {
+                            for option in [savegame::SaveKind::Record, savegame::SaveKind::Snapshot] {
+                                ui.radio_value(kind, option, RichText::new(option.to_string()).font(FontId::proportional(14.0)));
+                            }
+                        });
                     }
-                }
-            }
-        }
-
-        // 成四斜
-        let tetras = [
-            vec![(0, 1), (1, 2), (2, 3), (3, 4)], // 左上四斜
-            vec![(0, 3), (1, 2), (2, 1), (3, 0)], // 右上四斜
-            vec![(1, 0), (2, 1), (3, 2), (4, 3)], // 左下四斜
-            vec![(1, 4), (2, 3), (3, 2), (4, 1)], // 右下四斜
-        ];
-        for id in &self.triggered_tetras {
-            if let Some(tetra) = tetras.get(*id) {
-                if self.is_tetra(*id, player) {
-                    for &(r, c) in tetra {
-                        protected.insert((r, c));
+                    FileDialog::Open { path } => {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(tr("gui.file")).font(FontId::proportional(14.0)));
+                            ui.text_edit_singleline(path);
+                        });
+                        ui.label(RichText::new(tr("gui.open_help")).font(FontId::proportional(12.0)));
                     }
                 }
-            }
-        }
+                ui.horizontal(|ui| {
+                    confirmed = ui.button(RichText::new(tr("gui.ok")).font(FontId::proportional(14.0))).clicked();
+                    cancelled = ui.button(RichText::new(tr("gui.cancel")).font(FontId::proportional(14.0))).clicked();
+                });
+            });
 
-        // 成州（行）
-        for &r in &self.triggered_rows {
-            if self.is_row(r, player) {
-                for c in 0..5 {
-                    protected.insert((r, c));
-                }
-            }
-        }
-
-        // 成州（列）
-        for &c in &self.triggered_cols {
-            if self.is_col(c, player) {
-                for r in 0..5 {
-                    protected.insert((r, c));
-                }
+        if cancelled {
+            self.file_dialog = None;
+        } else if confirmed {
+            match self.file_dialog.take() {
+                Some(FileDialog::SaveAs { path, kind }) => self.save_game_as(path, kind),
+                Some(FileDialog::Open { path }) => self.open_game(path),
+                None => {}
             }
         }
+    }
 
-        // 成龙
-        let dragons = [
-            vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)], // 主对角线
-            vec![(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)], // 副对角线
-        ];
-        for id in &self.triggered_dragons {
-            if let Some(dragon) = dragons.get(*id) {
-                if self.is_dragon(*id, player) {
-                    for &(r, c) in dragon {
-                        protected.insert((r, c));
-                    }
+    // 执行本方动作，联机时交由主机裁决
+    fn play(&mut self, action: GameAction) -> Result<u32, String> {
+        match self.net.as_mut() {
+            Some(net) => net.submit(&mut self.board, &action),
+            None => {
+                let result = self.board.apply_action(&action).map_err(String::from);
+                if result.is_ok() {
+                    self.clock.on_action(&mut self.board, action.player(), Instant::now());
                 }
+                result
             }
         }
     }
 
-    // 执行吃棋（单步吃一个棋子）
-    pub fn capture_piece(&mut self, row: usize, col: usize) -> Result<(), &'static str> {
-        if self.phase != GamePhase::Capture {
-            return Err("当前不是吃棋阶段");
+    // 在后台搜索当前局面的最佳动作
+    fn request_hint(&mut self) {
+        if self.game_over {
+            self.message = tr("gui.game_finished").to_string();
+            return;
         }
-
-        let player = self.current_player;
-
-        // 获取当前玩家剩余吃子数量
-        let remaining = match self.capture_remaining.get(&player) {
-            Some(&r) if r > 0 => r,
-            _ => return Err("没有待处理的吃棋任务"),
-        };
-
-        if !Self::is_valid_pos(row, col) {
-            return Err("位置无效");
+        if self.is_engine_turn()
+            || self.net.as_ref().is_some_and(|n| n.local_player() != self.board.current_player())
+        {
+            self.message = tr("gui.not_our_turn").to_string();
+            return;
         }
-
-        let opponent = player.opponent();
-        let protected = self
-            .reward_pieces
-            .get(&opponent)
-            .cloned()
-            .unwrap_or_default();
-
-        // 验证吃棋位置
-        if protected.contains(&(row, col)) {
-            return Err("不能吃受保护的棋子");
+        if self.hint_search.is_some() {
+            return;
         }
 
-        if let Cell::Occupied(p) = self.grid[row][col] {
-            if p != opponent {
-                return Err("只能吃对方棋子");
-            }
-        } else {
-            return Err("该位置没有棋子");
-        }
-
-        // 执行吃棋
-        self.grid[row][col] = Cell::Empty;
-
-        // 记录吃棋动作
-        self.record_action(GameAction::Capture {
-            player,
-            pos: (row, col),
+        let board = self.board.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            let stop = std::sync::atomic::AtomicBool::new(false);
+            let limits = ai::SearchLimits {
+                movetime: Some(HINT_MOVETIME),
+                depth: None,
+            };
+            let _ = tx.send(ai::search(&board, limits, &stop, |_| {}));
         });
-
-        // 更新吃棋剩余数量
-        *self.capture_remaining.get_mut(&player).unwrap() = remaining - 1;
-
-        
-
-        // 更新奖励棋子保护集
-        self.update_reward_pieces();
-
-        // 检查吃棋后状态
-        if self.capture_remaining.values().sum::<u32>() == 0 {
-    // 所有吃棋完成，进入移动阶段
-    self.enter_movement_phase(MovementPhaseOrigin::FromMovement);
-    return Ok(());
-        }
-
-           // 如果当前玩家还有吃子机会，不切换玩家
-    if self.capture_remaining.get(&player).copied().unwrap_or(0) > 0 {
-        return Ok(());
+        self.hint_search = Some((self.board.position_hash(), rx));
+        self.message = tr("gui.hint_thinking").to_string();
     }
 
-        // 切换到下一个吃棋玩家
-        let next_player = player.opponent();
-
-        // 如果下一个玩家没有可吃的棋子，则跳过
-// 检查下一个玩家是否有可吃的棋子
-let next_has_capturable = self.has_capturable_pieces(next_player.opponent());
-
-if !next_has_capturable {
-    // 如果下一个玩家没有可吃的棋子，检查是否所有玩家都完成吃棋
-    if self.capture_remaining.values().sum::<u32>() == 0 {
-        self.enter_movement_phase(MovementPhaseOrigin::FromCapture);
-        return Ok(());
-    } else {
-        // 跳过这个玩家，回到第一个吃棋玩家
-        self.current_player = self.capture_turn;
-        return Ok(());
-    }
-}
-
-self.current_player = next_player;
-
-        // 检查后吃棋责任
-        if player == self.capture_turn.opponent() {
-            let next_mover = self.capture_turn;
-            if !self.has_legal_moves(next_mover) {
-                return Err("后吃棋导致对方无法走棋，你输了");
+    // 接收提示结果，局面已经变化的结果直接丢弃
+    fn poll_hint(&mut self) {
+        let Some((hash, rx)) = &self.hint_search else {
+            return;
+        };
+        let hash = *hash;
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.hint_search = None;
+                return;
             }
-        }
-
-        Ok(())
-    }
+        };
+        self.hint_search = None;
 
-    // 进入移动阶段
-fn enter_movement_phase(&mut self, origin: MovementPhaseOrigin) {
-    self.phase = GamePhase::Movement;
-    self.movement_phase_origin = origin;
-    
-    match origin {
-        MovementPhaseOrigin::FromPlacement => {
-            // 从满盘进入移动阶段，白方先走
-            self.current_player = Player::White;
+        if hash != self.board.position_hash() {
+            return;
         }
-        MovementPhaseOrigin::FromCapture => {
-            // 从吃棋阶段进入移动阶段，保持当前玩家不变
-            // 不需要改变current_player
-        }
-        MovementPhaseOrigin::FromMovement => {
-            // 从移动阶段自身进入（如吃棋后返回），切换玩家
-            self.current_player = self.current_player.opponent();
-        }
-    }
-    
-    self.update_reward_pieces();
-}
-
-// 添加新的奖励检查方法，专门用于走棋阶段
-fn check_rewards_after_move(&mut self, row: usize, col: usize) -> u32 {
-    let player = self.current_player;
-    let mut capture_count = 0;
-    
-    // 只检查与移动棋子相关的奖励模式
-    // 1. 检查成方 (1x1 正方形)
-    let squares = self.check_squares_after_move(row, col, player);
-    capture_count += squares;
-    
-    // 2. 检查成三斜 (3点斜线)
-    let tris = self.check_tris_after_move(row, col, player);
-    capture_count += tris;
-    
-    // 3. 检查成四斜 (4点斜线)
-    let tetras = self.check_tetras_after_move(row, col, player);
-    capture_count += tetras;
-    
-    // 4. 检查成州 (整行或整列)
-    let rows = self.check_rows_after_move(row, player);
-    capture_count += rows;
-    
-    let cols = self.check_cols_after_move(col, player);
-    capture_count += cols;
-    
-    // 5. 检查成龙 (对角线)
-    let dragons = self.check_dragons_after_move(row, col, player);
-    capture_count += dragons;
-    
-    capture_count
-}
-
-// 添加走棋阶段专用的奖励检查方法
-fn check_squares_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
-    let mut extra = 0;
-    
-    // 检查可能包含该点的所有正方形
-    for &(r, c) in &[
-        (row, col),
-        (row, col.saturating_sub(1)),
-        (row.saturating_sub(1), col),
-        (row.saturating_sub(1), col.saturating_sub(1)),
-    ] {
-        if r < 4 && c < 4 {
-            if self.is_square(r, c, player) && !self.triggered_squares.contains(&[r, c]) {
-                self.triggered_squares.insert([r, c]);
-                extra += 1;
-                
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Square { top_left: (r, c) },
-                });
+        match result.best {
+            Some(action) => {
+                let explanation = ai::explain(&self.board, &action);
+                self.message = if explanation.is_empty() {
+                    trf("hint.action", &[&action.notation()])
+                } else {
+                    trf("hint.action_explained", &[&action.notation(), &explanation])
+                };
+                self.hint = Some((hash, action));
             }
+            None => self.message = tr("hint.no_action").to_string(),
         }
     }
-    extra
-}
 
-fn check_tris_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
-    let mut extra = 0;
-    
-    for id in 0..4 {
-        if self.is_tri_affected_by_move(id, row, col, player) && 
-           self.is_tri(id, player) && 
-           !self.triggered_tris.contains(&id) {
-            self.triggered_tris.insert(id);
-            extra += 1;
-            
-            // 记录奖励模式
-            self.record_action(GameAction::Reward {
-                player,
-                pattern: RewardPattern::Tri { id },
-            });
-        }
-    }
-    extra
-}
+    // 分析面板：评估条、主变例和前几个候选动作，分值均为黑方视角
+    fn draw_analysis(&mut self, ui: &mut egui::Ui, board: &Board) {
+        let Some(analyzer) = self.analyzer.as_mut() else {
+            return;
+        };
+        analyzer.set_position(board);
+        let update = analyzer.poll().cloned();
+
+        egui::Frame::group(ui.style())
+            .fill(self.theme.panel)
+            .stroke(Stroke::new(1.0, self.theme.panel_stroke))
+            .rounding(5.0)
+            .show(ui, |ui| {
+                let Some(update) = update else {
+                    let text = if board.check_winner().is_some() { tr("game.finished") } else { tr("analysis.running") };
+                    ui.label(RichText::new(text).font(FontId::proportional(14.0)));
+                    return;
+                };
+                let Some(score) = update.black_score() else {
+                    return;
+                };
+                let from_black = |s: i32| match update.side {
+                    Player::Black => s,
+                    Player::White => -s,
+                };
 
-fn check_tetras_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
-    let mut extra = 0;
-    
-    for id in 0..4 {
-        if self.is_tetra_affected_by_move(id, row, col, player) && 
-           self.is_tetra(id, player) && 
-           !self.triggered_tetras.contains(&id) {
-            self.triggered_tetras.insert(id);
-            extra += 1;
-            
-            // 记录奖励模式
-            self.record_action(GameAction::Reward {
-                player,
-                pattern: RewardPattern::Tetra { id },
+                // 评估条：黑色部分越长越有利于黑方
+                let black_share = if ai::is_decisive(score) {
+                    if score > 0 { 1.0 } else { 0.0 }
+                } else {
+                    0.5 + 0.5 * score as f32 / (score.abs() as f32 + 400.0)
+                };
+                let (bar, _) = ui.allocate_exact_size(egui::vec2(260.0, 16.0), egui::Sense::hover());
+                let painter = ui.painter();
+                painter.rect_filled(bar, 3.0, self.theme.white);
+                let mut black_part = bar;
+                black_part.set_width(bar.width() * black_share);
+                painter.rect_filled(black_part, 3.0, self.theme.black);
+                painter.rect_stroke(bar, 3.0, Stroke::new(1.0, self.theme.panel_stroke));
+
+                ui.label(RichText::new(trf(
+                    "analysis.summary",
+                    &[&format_score(score), &update.depth, &update.nodes],
+                )).font(FontId::proportional(14.0)));
+
+                let pv: Vec<String> = update.candidates[0].pv.iter().map(|a| a.notation()).collect();
+                ui.label(RichText::new(trf("analysis.pv", &[&pv.join(" ")])).font(FontId::monospace(13.0)));
+
+                for (i, candidate) in update.candidates.iter().take(ANALYSIS_CANDIDATES).enumerate() {
+                    ui.label(RichText::new(format!(
+                        "{}. {:<12} {}",
+                        i + 1,
+                        candidate.action.notation(),
+                        format_score(from_black(candidate.score))
+                    )).font(FontId::monospace(13.0)).color(self.theme.value));
+                }
             });
-        }
-    }
-    extra
-}
-
-fn check_rows_after_move(&mut self, row: usize, player: Player) -> u32 {
-    if self.is_row(row, player) && !self.triggered_rows.contains(&row) {
-        self.triggered_rows.insert(row);
-        
-        // 记录奖励模式
-        self.record_action(GameAction::Reward {
-            player,
-            pattern: RewardPattern::Row { index: row },
-        });
-        
-        2 // 成州奖励2次吃子机会
-    } else {
-        0
     }
-}
 
-fn check_cols_after_move(&mut self, col: usize, player: Player) -> u32 {
-    if self.is_col(col, player) && !self.triggered_cols.contains(&col) {
-        self.triggered_cols.insert(col);
-        
-        // 记录奖励模式
-        self.record_action(GameAction::Reward {
-            player,
-            pattern: RewardPattern::Col { index: col },
-        });
-        
-        2 // 成州奖励2次吃子机会
-    } else {
-        0
-    }
-}
+    // 开局浏览器：当前落子阶段局面在开局库中的着法和胜率，返回点击的着法
+    fn draw_book(&self, ui: &mut egui::Ui, board: &Board) -> Option<(usize, usize)> {
+        let book = self.book.as_ref()?;
+        let moves = book.moves(board);
+        let mut clicked = None;
+
+        egui::Frame::group(ui.style())
+            .fill(self.theme.panel)
+            .stroke(Stroke::new(1.0, self.theme.panel_stroke))
+            .rounding(5.0)
+            .show(ui, |ui| {
+                if board.get_state().0 != GamePhase::Placement {
+                    ui.label(RichText::new(tr("book.placement_only")).font(FontId::proportional(14.0)));
+                    return;
+                }
+                if moves.is_empty() {
+                    ui.label(RichText::new(tr("book.not_found")).font(FontId::proportional(14.0)));
+                    return;
+                }
 
-fn check_dragons_after_move(&mut self, row: usize, col: usize, player: Player) -> u32 {
-    let mut extra = 0;
-    
-    for id in 0..2 {
-        if self.is_dragon_affected_by_move(id, row, col, player) && 
-           self.is_dragon(id, player) && 
-           !self.triggered_dragons.contains(&id) {
-            self.triggered_dragons.insert(id);
-            extra += 2; // 成龙奖励2次吃子机会
-            
-            // 记录奖励模式
-            self.record_action(GameAction::Reward {
-                player,
-                pattern: RewardPattern::Dragon { id },
+                ui.label(RichText::new(tr("book.explorer_title")).font(FontId::proportional(14.0)));
+                egui::Grid::new("book_moves").striped(true).show(ui, |ui| {
+                    ui.label(RichText::new(tr("book.col_move")).font(FontId::proportional(13.0)));
+                    ui.label(RichText::new(tr("book.col_games")).font(FontId::proportional(13.0)));
+                    ui.label(RichText::new(tr("book.col_black_wins")).font(FontId::proportional(13.0)));
+                    ui.label(RichText::new(tr("book.col_white_wins")).font(FontId::proportional(13.0)));
+                    ui.end_row();
+
+                    for m in moves.iter().take(BOOK_EXPLORER_MOVES) {
+                        let percent = |n: u32| format!("{:.1}%", n as f32 * 100.0 / m.games as f32);
+                        let notation = RichText::new(format!("{},{}", m.pos.0, m.pos.1)).font(FontId::monospace(13.0));
+                        if ui.button(notation).clicked() {
+                            clicked = Some(m.pos);
+                        }
+                        ui.label(RichText::new(m.games.to_string()).font(FontId::monospace(13.0)));
+                        ui.label(RichText::new(percent(m.black_wins)).font(FontId::monospace(13.0)));
+                        ui.label(RichText::new(percent(m.white_wins)).font(FontId::monospace(13.0)));
+                        ui.end_row();
+                    }
+                });
             });
-        }
-    }
-    extra
-}
-
-// 添加辅助方法检查移动是否影响特定模式
-fn is_tri_affected_by_move(&self, id: usize, row: usize, col: usize, player: Player) -> bool {
-    let positions = match id {
-        0 => vec![(0, 2), (1, 1), (2, 0)], // 左上三斜
-        1 => vec![(0, 2), (1, 3), (2, 4)], // 右上三斜
-        2 => vec![(2, 0), (3, 1), (4, 2)], // 左下三斜
-        3 => vec![(2, 4), (3, 3), (4, 2)], // 右下三斜
-        _ => return false,
-    };
-    
-    positions.contains(&(row, col))
-}
-
-fn is_tetra_affected_by_move(&self, id: usize, row: usize, col: usize, player: Player) -> bool {
-    let positions = match id {
-        0 => vec![(0, 1), (1, 2), (2, 3), (3, 4)], // 左上四斜
-        1 => vec![(0, 3), (1, 2), (2, 1), (3, 0)], // 右上四斜
-        2 => vec![(1, 0), (2, 1), (3, 2), (4, 3)], // 左下四斜
-        3 => vec![(1, 4), (2, 3), (3, 2), (4, 1)], // 右下四斜
-        _ => return false,
-    };
-    
-    positions.contains(&(row, col))
-}
-
-fn is_dragon_affected_by_move(&self, id: usize, row: usize, col: usize, player: Player) -> bool {
-    let positions = match id {
-        0 => vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)], // 主对角线
-        1 => vec![(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)], // 副对角线
-        _ => return false,
-    };
-    
-    positions.contains(&(row, col))
-}
-
-    // 执行移动
-    pub fn move_piece(
-        &mut self,
-        from: (usize, usize),
-        to: (usize, usize),
-    ) -> Result<u32, &'static str> {
-        if self.phase != GamePhase::Movement {
-            return Err("当前不是走子阶段");
-        }
-
-        let (from_row, from_col) = from;
-        let (to_row, to_col) = to;
-
-        // 验证移动位置
-        if !Self::is_valid_pos(from_row, from_col) || !Self::is_valid_pos(to_row, to_col) {
-            return Err("位置无效，必须在0-4范围内");
-        }
-
-        // 检查起始位置是否属于当前玩家
-        if let Cell::Occupied(p) = self.grid[from_row][from_col] {
-            if p != self.current_player {
-                return Err("只能移动自己的棋子");
+        clicked
+    }
+
+    // 打开棋谱进入回放模式，当前对局的时钟暂停
+    fn open_replay(&mut self) {
+        let actions: Vec<GameAction> = match std::fs::read_to_string(&self.replay_path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+        {
+            Ok(actions) => actions,
+            Err(e) => {
+                self.message = trf("replay.open_failed", &[&self.replay_path, &e]);
+                return;
             }
-        } else {
-            return Err("起始位置无棋子");
-        }
-
-        // 检查目标位置是否为空
-        if self.grid[to_row][to_col] != Cell::Empty {
-            return Err("目标位置已被占用");
-        }
-
-        // 检查移动是否相邻（上下左右）
-        let row_diff = from_row.abs_diff(to_row);
-        let col_diff = from_col.abs_diff(to_col);
-        if (row_diff == 1 && col_diff == 0) || (row_diff == 0 && col_diff == 1) {
-            // 有效移动
-        } else {
-            return Err("只能移动到相邻位置（上下左右）");
-        }
-
-        // 执行移动
-        let player = self.current_player;
-        self.grid[from_row][from_col] = Cell::Empty;
-        self.grid[to_row][to_col] = Cell::Occupied(player);
+        };
 
-        // 记录移动动作
-        self.record_action(GameAction::Move {
-            player,
-            from: (from_row, from_col),
-            to: (to_row, to_col),
+        let replayer = GameReplayer::new(actions);
+        self.message = trf("replay.opened", &[&self.replay_path, &replayer.steps().len()]);
+        self.replay = Some(Replay {
+            replayer,
+            autoplay: false,
+            speed: 1.0,
+            next_tick: 0.0,
         });
-
-        // 检查奖励并获取可吃子数量
-       let capture_count = self.check_rewards_after_move(to_row, to_col);
-
-
-       // 如果有吃子机会，进入吃棋阶段让玩家选择吃哪些棋子
-    if capture_count > 0 {
-        // 设置吃棋阶段
-        self.phase = GamePhase::Capture;
-        self.capture_remaining.insert(player, capture_count);
-        self.capture_turn = player;
-        
-        // 更新奖励棋子保护集
-        self.update_reward_pieces();
-        
-        // 返回吃子数量，但不实际吃子
-        return Ok(capture_count);
-    }
-
-        // 检查移动后对方是否能走棋
-        let opponent = player.opponent();
-        if !self.has_legal_moves(opponent) {
-            // 导致对方无法走棋，当前玩家判负
-            return Err("移动导致对方无法走棋，你输了");
+        self.selected_cell = None;
+        if !self.game_over {
+            self.clock.stop(Instant::now());
         }
-
-        // 切换玩家
-        self.current_player = self.current_player.opponent();
-
-        Ok(0)
     }
 
-    // 落子阶段的奖励检查
-    fn check_rewards(&mut self, row: usize, col: usize) -> u32 {
-        let mut extra = 0;
-        let player = self.current_player;
-
-        // 1. 检查成方 (1x1 正方形)
-        let squares = self.check_squares(row, col);
-        if squares > 0 {
-            println!("玩家 {} 形成成方奖励", player);
+    // 退出回放，回到当前对局
+    fn close_replay(&mut self) {
+        if self.replay.take().is_some() && !self.game_over {
+            self.clock.resume(Instant::now());
         }
-        extra += squares;
-
-        // 2. 检查成三斜 (3点斜线)
-        let tris = self.check_tris();
-        if tris > 0 {
-            println!("玩家 {} 形成成三斜奖励", player);
-        }
-        extra += tris;
-
-        // 3. 检查成四斜 (4点斜线)
-        let tetras = self.check_tetras();
-        if tetras > 0 {
-            println!("玩家 {} 形成成四斜奖励", player);
-        }
-        extra += tetras;
-
-        // 4. 检查成州 (整行或整列)
-        let rows = self.check_rows();
-        if rows > 0 {
-            println!("玩家 {} 形成成州(行)奖励", player);
-        }
-        extra += rows;
-
-        let cols = self.check_cols();
-        if cols > 0 {
-            println!("玩家 {} 形成成州(列)奖励", player);
-        }
-        extra += cols;
-
-        // 5. 检查成龙 (对角线)
-        let dragons = self.check_dragons();
-        if dragons > 0 {
-            println!("玩家 {} 形成成龙奖励", player);
-        }
-        extra += dragons;
-
-        extra
     }
 
-    // 移动阶段的奖励检查
-    fn check_rewards_movement(&mut self, row: usize, col: usize) -> u32 {
-        // 重置所有奖励模式（因为移动可能改变模式）
-        self.triggered_squares.clear();
-        self.triggered_tris.clear();
-        self.triggered_tetras.clear();
-        self.triggered_rows.clear();
-        self.triggered_cols.clear();
-        self.triggered_dragons.clear();
-
-        // 重新检查所有奖励模式
-        let mut capture_count = 0;
-        let player = self.current_player;
-
-        // 1. 检查所有成方
-        for r in 0..4 {
-            for c in 0..4 {
-                if self.is_square(r, c, player) {
-                    self.triggered_squares.insert([r, c]);
-                    capture_count += 1;
-
-                    // 记录奖励模式
-                    self.record_action(GameAction::Reward {
-                        player,
-                        pattern: RewardPattern::Square { top_left: (r, c) },
-                    });
-                    // println!("玩家 {} 形成成方[位置:({},{})]", player, r, c);
-                }
-            }
-        }
-
-        // 2. 检查所有成三斜
-        for id in 0..4 {
-            if self.is_tri(id, player) {
-                self.triggered_tris.insert(id);
-                capture_count += 1;
-
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Tri { id },
+    // 回放文件面板
+    fn draw_replay_panel(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style())
+            .fill(self.theme.panel)
+            .stroke(Stroke::new(1.0, self.theme.panel_stroke))
+            .rounding(5.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tr("replay.record")).font(FontId::proportional(14.0)));
+                    ui.text_edit_singleline(&mut self.replay_path);
+                    if ui.button(RichText::new(tr("gui.open")).font(FontId::proportional(14.0))).clicked() {
+                        self.open_replay();
+                    }
+                    if self.replay.is_some()
+                        && ui.button(RichText::new(tr("replay.exit")).font(FontId::proportional(14.0))).clicked()
+                    {
+                        self.close_replay();
+                        self.message = tr("replay.back").to_string();
+                    }
                 });
-                // println!("玩家 {} 形成成三斜[模式:{}]", player, id);
-            }
-        }
-
-        // 3. 检查所有成四斜
-        for id in 0..4 {
-            if self.is_tetra(id, player) {
-                self.triggered_tetras.insert(id);
-                capture_count += 1;
+            });
+    }
 
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Tetra { id },
-                });
-                // println!("玩家 {} 形成成四斜[模式:{}]", player, id);
+    // 回放模式：控制按钮、进度条、棋盘和着法列表
+    fn draw_replay(&mut self, ui: &mut egui::Ui) {
+        let time = self.time;
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        let total = replay.replayer.steps().len();
+        let mut step = replay.replayer.step();
+
+        // 自动播放
+        if replay.autoplay && time >= replay.next_tick {
+            if step < total {
+                step += 1;
+                replay.next_tick = time + 1.0 / replay.speed;
+            } else {
+                replay.autoplay = false;
             }
         }
 
-        // 4. 检查所有成州（行）
-        for r in 0..5 {
-            if self.is_row(r, player) {
-                self.triggered_rows.insert(r);
-                capture_count += 2;
-
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Row { index: r },
-                });
-                // println!("玩家 {} 形成成州[行:{}]", player, r);
+        ui.horizontal(|ui| {
+            if ui.button(RichText::new(tr("replay.first")).font(FontId::proportional(14.0))).clicked() {
+                step = 0;
             }
-        }
-
-        // 4. 检查所有成州（列）
-        for c in 0..5 {
-            if self.is_col(c, player) {
-                self.triggered_cols.insert(c);
-                capture_count += 2;
-
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Col { index: c },
-                });
-                // println!("玩家 {} 形成成州[列:{}]", player, c);
+            if ui.button(RichText::new(tr("replay.prev")).font(FontId::proportional(14.0))).clicked() {
+                step = step.saturating_sub(1);
             }
-        }
-
-        // 5. 检查所有成龙
-        for id in 0..2 {
-            if self.is_dragon(id, player) {
-                self.triggered_dragons.insert(id);
-                capture_count += 2;
-
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Dragon { id },
-                });
-                // println!("玩家 {} 形成成龙[对角线:{}]", player, id);
+            if ui.button(RichText::new(tr("replay.next")).font(FontId::proportional(14.0))).clicked() {
+                step = (step + 1).min(total);
             }
-        }
-
-        capture_count
-    }
-
-    // 成方检测 (1x1 正方形)
-    fn check_squares(&mut self, row: usize, col: usize) -> u32 {
-        let mut extra = 0;
-        let player = self.current_player;
-
-        // 检查可能包含该点的所有正方形
-        for &(r, c) in &[
-            (row, col),
-            (row, col.saturating_sub(1)),
-            (row.saturating_sub(1), col),
-            (row.saturating_sub(1), col.saturating_sub(1)),
-        ] {
-            if r < 4 && c < 4 {
-                if self.is_square(r, c, player) {
-                    let square_id = [r, c];
-                    if self.triggered_squares.insert(square_id) {
-                        extra += 1;
-
-                        // 记录奖励模式
-                        self.record_action(GameAction::Reward {
-                            player,
-                            pattern: RewardPattern::Square { top_left: (r, c) },
-                        });
-                    }
-                }
+            if ui.button(RichText::new(tr("replay.last")).font(FontId::proportional(14.0))).clicked() {
+                step = total;
             }
-        }
-        extra
-    }
-
-    fn is_square(&self, r: usize, c: usize, player: Player) -> bool {
-        let corners = [(r, c), (r, c + 1), (r + 1, c), (r + 1, c + 1)];
-
-        corners
-            .iter()
-            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
-    }
-
-    // 成三斜检测 (3点斜线)
-    fn check_tris(&mut self) -> u32 {
-        let player = self.current_player;
-        let mut extra = 0;
-
-        for id in 0..4 {
-            if !self.triggered_tris.contains(&id) && self.is_tri(id, player) {
-                self.triggered_tris.insert(id);
-                extra += 1;
-
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Tri { id },
-                });
+            if ui.checkbox(&mut replay.autoplay, RichText::new(tr("replay.autoplay")).font(FontId::proportional(14.0))).changed() {
+                replay.next_tick = time + 1.0 / replay.speed;
             }
-        }
-        extra
-    }
-
-    fn is_tri(&self, id: usize, player: Player) -> bool {
-        let positions = match id {
-            0 => vec![(0, 2), (1, 1), (2, 0)], // 左上三斜
-            1 => vec![(0, 2), (1, 3), (2, 4)], // 右上三斜
-            2 => vec![(2, 0), (3, 1), (4, 2)], // 左下三斜
-            3 => vec![(2, 4), (3, 3), (4, 2)], // 右下三斜
-            _ => return false,
-        };
-
-        positions
-            .iter()
-            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
-    }
-
-    // 成四斜检测 (4点斜线)
-    fn check_tetras(&mut self) -> u32 {
-        let player = self.current_player;
-        let mut extra = 0;
-
-        for id in 0..4 {
-            if !self.triggered_tetras.contains(&id) && self.is_tetra(id, player) {
-                self.triggered_tetras.insert(id);
-                extra += 1;
+            ui.add(egui::Slider::new(&mut replay.speed, 0.5..=5.0).text(tr("replay.speed")));
+        });
+        ui.add(egui::Slider::new(&mut step, 0..=total).text(trf("replay.total", &[&total])));
 
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Tetra { id },
-                });
-            }
+        if step != replay.replayer.step() {
+            replay.replayer.seek(step);
         }
-        extra
-    }
 
-    fn is_tetra(&self, id: usize, player: Player) -> bool {
-        let positions = match id {
-            0 => vec![(0, 1), (1, 2), (2, 3), (3, 4)], // 左上四斜
-            1 => vec![(0, 3), (1, 2), (2, 1), (3, 0)], // 右上四斜
-            2 => vec![(1, 0), (2, 1), (3, 2), (4, 3)], // 左下四斜
-            3 => vec![(1, 4), (2, 3), (3, 2), (4, 1)], // 右下四斜
-            _ => return false,
+        let Some(replay) = self.replay.as_ref() else {
+            return;
         };
-
-        positions
-            .iter()
-            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
-    }
-
-    // 成州检测 (整行)
-    fn check_rows(&mut self) -> u32 {
-        let player = self.current_player;
-        let mut extra = 0;
-
-        for r in 0..5 {
-            if !self.triggered_rows.contains(&r) && self.is_row(r, player) {
-                self.triggered_rows.insert(r);
-                extra += 2;
-
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Row { index: r },
+        let steps = replay.replayer.steps();
+        let board = replay.replayer.get_current_board();
+        let annotations: &[GameAction] = if step > 0 { steps[step - 1] } else { &[] };
+
+        let (phase, player) = board.get_state();
+        ui.label(RichText::new(trf("replay.status", &[&step, &phase, &player]))
+            .font(FontId::proportional(14.0))
+            .color(self.theme.value));
+
+        let mut jump = None;
+        ui.horizontal_top(|ui| {
+            self.draw_board(ui, board, annotations, &[], time);
+
+            egui::ScrollArea::vertical()
+                .max_height(260.0)
+                .min_scrolled_width(220.0)
+                .show(ui, |ui| {
+                    for (i, entries) in steps.iter().enumerate() {
+                        let text = format!("{}. {} {}", i + 1, entries[0].player(), entries[0].notation());
+                        let response = ui.selectable_label(i + 1 == step, RichText::new(text).font(FontId::monospace(13.0)));
+                        if response.clicked() {
+                            jump = Some(i + 1);
+                        }
+                        if i + 1 == step && replay.autoplay {
+                            response.scroll_to_me(Some(egui::Align::Center));
+                        }
+                        for entry in &entries[1..] {
+                            let note = match entry {
+                                GameAction::Reward { pattern, .. } => format!("    {} +{}", pattern, pattern.reward()),
+                                GameAction::Timeout { player } => format!("    {}", trf("record.timeout", &[player])),
+                                GameAction::Resign { player } => format!("    {}", trf("record.resign", &[player])),
+                                _ => continue,
+                            };
+                            ui.label(RichText::new(note).font(FontId::monospace(12.0)).color(self.theme.note));
+                        }
+                    }
                 });
-            }
-        }
-        extra
-    }
-
-    fn is_row(&self, r: usize, player: Player) -> bool {
-        (0..5).all(|c| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
-    }
-
-    // 成州检测 (整列)
-    fn check_cols(&mut self) -> u32 {
-        let player = self.current_player;
-        let mut extra = 0;
-
-        for c in 0..5 {
-            if !self.triggered_cols.contains(&c) && self.is_col(c, player) {
-                self.triggered_cols.insert(c);
-                extra += 2;
+        });
 
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Col { index: c },
-                });
-            }
+        if let Some(winner) = board.check_winner().filter(|_| step == total) {
+            ui.label(RichText::new(trf("replay.end", &[&winner]))
+                .font(FontId::proportional(16.0))
+                .color(self.theme.alert));
         }
-        extra
-    }
 
-    fn is_col(&self, c: usize, player: Player) -> bool {
-        (0..5).all(|r| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
+        if let Some(step) = jump
+            && let Some(replay) = self.replay.as_mut()
+        {
+            replay.replayer.seek(step);
+            replay.autoplay = false;
+        }
     }
 
-    // 成龙检测 (对角线)
-    fn check_dragons(&mut self) -> u32 {
-        let player = self.current_player;
-        let mut extra = 0;
+    // 着法记录面板：按回合列出棋谱，点击一条记录只读查看该记录之后的局面
+    fn draw_history(&mut self, ctx: &egui::Context) {
+        let record = self.board.get_game_record();
+        let lines = history_lines(record);
+        let mut select = None;
 
-        for id in 0..2 {
-            if !self.triggered_dragons.contains(&id) && self.is_dragon(id, player) {
-                self.triggered_dragons.insert(id);
-                extra += 2;
+        egui::SidePanel::right("history")
+            .resizable(true)
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tr("gui.history")).font(FontId::proportional(16.0)).color(self.theme.label));
+                    if ui.button(RichText::new(tr("history.copy")).font(FontId::proportional(13.0))).clicked() {
+                        ui.output_mut(|o| o.copied_text = history_text(&lines));
+                        self.message = tr("history.copied").to_string();
+                    }
+                });
+                ui.separator();
 
-                // 记录奖励模式
-                self.record_action(GameAction::Reward {
-                    player,
-                    pattern: RewardPattern::Dragon { id },
+                if lines.is_empty() {
+                    ui.label(RichText::new(tr("history.empty")).font(FontId::proportional(13.0)).color(self.theme.muted));
+                    return;
+                }
+                let viewing = self.history_view.as_ref().map(|(index, _)| *index);
+                egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                    for line in &lines {
+                        let text = if line.indent {
+                            RichText::new(format!("    {}", line.text)).font(FontId::monospace(12.0)).color(self.theme.note)
+                        } else {
+                            RichText::new(&line.text).font(FontId::monospace(13.0))
+                        };
+                        if ui.selectable_label(viewing == Some(line.index), text).clicked() {
+                            select = Some(line.index);
+                        }
+                    }
                 });
+            });
+
+        if let Some(index) = select {
+            // 对局开始之后的记录从开始时的局面接着重放，之前的记录只能从开局重放
+            let (base_len, base) = &self.history_base;
+            let board = if index >= *base_len {
+                savegame::replay_record(base.clone(), &record[*base_len..=index])
+            } else {
+                savegame::board_from_record(&record[..=index])
+            };
+            match board {
+                Ok(board) => self.history_view = Some((index, board)),
+                Err(e) => self.message = e,
             }
         }
-        extra
     }
 
-    fn is_dragon(&self, id: usize, player: Player) -> bool {
-        let positions = match id {
-            0 => vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)], // 主对角线
-            1 => vec![(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)], // 副对角线
-            _ => return false,
-        };
-
-        positions
-            .iter()
-            .all(|&(r, c)| matches!(self.grid[r][c], Cell::Occupied(p) if p == player))
-    }
-
-    // 检查游戏是否结束
-    pub fn check_winner(&self) -> Option<Player> {
-        // 只在吃棋和走子阶段检查
-        if self.phase == GamePhase::Placement {
-            return None;
+    // 检查是否有一方超时
+    fn poll_clock(&mut self) {
+        if self.game_over || self.net.is_some() {
+            return;
         }
-
-        let black_pieces = self.player_pieces(Player::Black).len();
-        let white_pieces = self.player_pieces(Player::White).len();
-
-        if black_pieces < 3 {
-            return Some(Player::White);
-        }
-
-        if white_pieces < 3 {
-            return Some(Player::Black);
-        }
-
-        // 检查是否有合法移动
-        if self.phase == GamePhase::Movement {
-            if !self.has_legal_moves(self.current_player) {
-                return Some(self.current_player.opponent());
+        let now = Instant::now();
+        if let Some(loser) = self.clock.flagged(now) {
+            self.clock.stop(now);
+            self.board.record_timeout(loser);
+            if let Some(engine) = self.engine.as_mut() {
+                engine.cancel();
             }
-        }
-
-        None
-    }
-}
-
-// 棋谱重放器
-pub struct GameReplayer {
-    actions: Vec<GameAction>,
-    current_step: usize,
-    board: Board,
-}
+            self.message = trf("gui.timeout", &[&loser, &loser.opponent()]);
+            self.game_over = true;
+        }
+    }
+
+    // 双方时钟，行棋方高亮
+    fn draw_clocks(&self, ui: &mut egui::Ui) {
+        let now = Instant::now();
+        let control = self.clock.control();
+        ui.horizontal(|ui| {
+            for player in [Player::Black, Player::White] {
+                let state = self.clock.state(player, now);
+                let running = self.clock.is_running() && self.board.current_player() == player;
+                let low = control != clock::TimeControl::Unlimited
+                    && state.main.as_secs() < 30
+                    && state.period_left.is_none_or(|left| left.as_secs() < 10);
+                let color = if low {
+                    self.theme.alert
+                } else if running {
+                    self.theme.value
+                } else {
+                    self.theme.muted
+                };
 
-impl GameReplayer {
-    pub fn new(actions: Vec<GameAction>) -> Self {
-        GameReplayer {
-            actions,
-            current_step: 0,
-            board: Board::new(),
-        }
+                egui::Frame::group(ui.style())
+                    .fill(if running { self.theme.panel_active } else { self.theme.panel })
+                    .stroke(Stroke::new(if running { 2.0 } else { 1.0 }, self.theme.panel_stroke))
+                    .rounding(5.0)
+                    .show(ui, |ui| {
+                        ui.label(RichText::new(format!("{}", player)).font(FontId::proportional(14.0)));
+                        ui.label(RichText::new(state.display(control)).font(FontId::monospace(26.0)).color(color));
+                    });
+                ui.add_space(10.0);
+            }
+        });
     }
 
-    pub fn step_forward(&mut self) -> Option<&Board> {
-        if self.current_step >= self.actions.len() {
-            return None;
-        }
+    // 处理联机消息
+    fn poll_net(&mut self) {
+        let Some(net) = self.net.as_mut() else {
+            return;
+        };
+        let (phase, _) = self.board.get_state();
+        let local_player = net.local_player();
 
-        let action = &self.actions[self.current_step];
-        match action {
-            GameAction::Place { player, pos } => {
-                self.board.place_piece(pos.0, pos.1).ok();
-            }
-            GameAction::Capture { player, pos } => {
-                self.board.capture_piece(pos.0, pos.1).ok();
-            }
-            GameAction::Move { player, from, to } => {
-                self.board.move_piece(*from, *to).ok();
+        for event in net.poll(&mut self.board) {
+            match event {
+                net::NetEvent::Connected(player) => {
+                    self.message = trf("net.connected_as", &[&player]);
+                }
+                net::NetEvent::Disconnected => {
+                    self.message = tr("net.peer_disconnected").to_string();
+                }
+                net::NetEvent::Updated(Some(action)) => {
+                    if let GameAction::Place { player, .. }
+                    | GameAction::Capture { player, .. }
+                    | GameAction::Move { player, .. } = &action
+                        && *player != local_player
+                    {
+                        self.message = trf("net.peer_action", &[&action.notation()]);
+                    }
+                    self.game_over = false;
+                }
+                net::NetEvent::Updated(None) => {
+                    self.message = tr("net.synced").to_string();
+                    self.game_over = false;
+                    self.selected_cell = None;
+                    self.input_mode = match self.board.get_state().0 {
+                        GamePhase::Placement => InputMode::Placement,
+                        GamePhase::Capture => InputMode::Capture,
+                        GamePhase::Movement => InputMode::MovementFrom,
+                    };
+                }
+                net::NetEvent::Rejected(reason) => {
+                    self.message = trf("net.rejected", &[&reason]);
+                }
+                net::NetEvent::Resigned(player) => {
+                    self.message = trf("gui.resigned", &[&player]);
+                    self.game_over = true;
+                }
             }
-            _ => {} // 奖励模式不需要执行操作
         }
 
-        self.current_step += 1;
-        Some(&self.board)
-    }
-
-    pub fn reset(&mut self) {
-        self.current_step = 0;
-        self.board = Board::new();
-    }
-
-    pub fn get_current_board(&self) -> &Board {
-        &self.board
-    }
-}
-
-// 读取用户输入
-fn read_input(prompt: &str) -> String {
-    print!("{}", prompt);
-    io::stdout().flush().unwrap();
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
-    input.trim().to_string()
-}
-
-// 解析坐标输入
-fn parse_coord(input: &str) -> Result<(usize, usize), &'static str> {
-    let parts: Vec<&str> = input.split(',').collect();
-    if parts.len() != 2 {
-        return Err("输入格式错误，请使用 行,列 格式，例如: 2,3");
-    }
-
-    let row = parts[0]
-        .parse::<usize>()
-        .map_err(|_| "行号必须是0-4之间的数字")?;
-    let col = parts[1]
-        .parse::<usize>()
-        .map_err(|_| "列号必须是0-4之间的数字")?;
-
-    if row > 4 || col > 4 {
-        return Err("行和列必须在0-4范围内");
+        self.after_action(phase);
+    }
+
+    // 联机设置面板
+    fn draw_net_panel(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style())
+            .fill(self.theme.panel)
+            .stroke(Stroke::new(1.0, self.theme.panel_stroke))
+            .rounding(5.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tr("net.address")).font(FontId::proportional(14.0)));
+                    ui.text_edit_singleline(&mut self.net_addr);
+
+                    match self.net.as_ref().map(|n| n.role()) {
+                        None => {
+                            if ui.button(RichText::new(tr("net.host")).font(FontId::proportional(14.0))).clicked() {
+                                match net::NetSession::host(&self.net_addr) {
+                                    Ok(session) => {
+                                        let addr = session.local_addr().map_or(String::new(), |a| a.to_string());
+                                        self.message = trf("net.hosted", &[&addr]);
+                                        self.net = Some(session);
+                                    }
+                                    Err(e) => self.message = trf("net.host_failed", &[&e]),
+                                }
+                            }
+                            if ui.button(RichText::new(tr("net.join")).font(FontId::proportional(14.0))).clicked() {
+                                match net::NetSession::join(&self.net_addr) {
+                                    Ok(session) => {
+                                        self.message = tr("net.joining").to_string();
+                                        self.net = Some(session);
+                                    }
+                                    Err(e) => self.message = trf("net.join_failed", &[&e]),
+                                }
+                            }
+                        }
+                        Some(role) => {
+                            let connected = self.net.as_ref().is_some_and(|n| n.is_connected());
+                            let status = match (role, connected) {
+                                (_, true) => tr("net.connected"),
+                                (net::Role::Host, false) => tr("net.waiting"),
+                                (net::Role::Client, false) => tr("net.not_connected"),
+                            };
+                            ui.label(RichText::new(status).font(FontId::proportional(14.0)).color(self.theme.message));
+
+                            if role == net::Role::Client && !connected
+                                && ui.button(RichText::new(tr("net.reconnect")).font(FontId::proportional(14.0))).clicked()
+                                && let Some(net) = self.net.as_mut()
+                                && let Err(e) = net.reconnect(&self.board)
+                            {
+                                self.message = trf("net.reconnect_failed", &[&e]);
+                            }
+                            if ui.button(RichText::new(tr("net.disconnect")).font(FontId::proportional(14.0))).clicked() {
+                                self.net = None;
+                                self.message = tr("net.disconnected").to_string();
+                            }
+                        }
+                    }
+                });
+            });
     }
 
-    Ok((row, col))
-}
-
-fn parse_move(input: &str) -> Result<((usize, usize), (usize, usize)), &'static str> {
-    let parts: Vec<&str> = input.split_whitespace().collect();
-    if parts.len() != 2 {
-        return Err("输入格式错误，请使用 原行,原列 新行,新列 格式");
+    // 是否轮到外部引擎行棋
+    fn is_engine_turn(&self) -> bool {
+        self.engine.is_some() && self.board.current_player() == Player::White
     }
 
-    let from = parse_coord(parts[0])?;
-    let to = parse_coord(parts[1])?;
-
-    Ok((from, to))
-}
-
-// 主游戏循环
-// fn main() {
-//     println!("\n===== 欢迎来到五道方游戏! =====");
-//     println!("游戏规则说明:");
-//     println!("1. 游戏分为三个阶段: 落子阶段、吃棋阶段、走子阶段");
-//     println!("2. 落子阶段: 玩家轮流在5x5棋盘上放置棋子");
-//     println!(
-//         "3. 形成特定模式可获得奖励: 成方(+1子)、成三斜(+1子)、成四斜(+1子)、成州(+2子)、成龙(+2子)"
-//     );
-//     println!("4. 棋盘满后进入吃棋阶段: 后落子的玩家先吃棋，轮流吃掉对方棋子");
-//     println!("5. 吃棋完成后进入走子阶段: 玩家轮流移动自己的棋子");
-//     println!("6. 胜利条件: 对方棋子少于3个或无法移动时获胜");
-//     println!("================================\n");
-
-//     let mut board = Board::new();
-//     let mut game_over = false;
-
-//     while !game_over {
-//         board.print_board();
-//         board.print_game_status();
-
-//         // 检查胜利条件（只在吃棋和走子阶段）
-//         if let Some(winner) = board.check_winner() {
-//             println!("\n===== 游戏结束! =====");
-//             println!("{} 获胜!", winner);
-//             game_over = true;
-//             continue;
-//         }
-
-//         match board.phase {
-//             GamePhase::Placement => {
-//                 let input = read_input(&format!("{} 请输入落子位置: ", board.current_player));
-
-//                 if board.admit_defeat(&input) {
-//                     println!("{} 认输，游戏结束！", board.current_player);
-//                     game_over = true;
-//                     continue;
-//                 }
-//                 match parse_coord(&input) {
-//                     Ok((row, col)) => match board.place_piece(row, col) {
-//                         Ok(extra) => {
-//                             if extra > 0 {
-//                                 println!(
-//                                     "{} 形成奖励模式，获得额外落子次数: {}",
-//                                     board.current_player, extra
-//                                 );
-//                             }
-//                         }
-//                         Err(e) => println!("操作失败: {}", e),
-//                     },
-//                     Err(e) => println!("输入错误: {}", e),
-//                 }
-//             }
-
-//             GamePhase::Capture => {
-//                 // 检查当前玩家是否可以吃子
-//                 let opponent = board.current_player.opponent();
-//                 let opponent_pieces = board.player_pieces(opponent);
-//                 let protected = board
-//                     .reward_pieces
-//                     .get(&opponent)
-//                     .cloned()
-//                     .unwrap_or_default();
-
-//                 // 找出可吃的棋子（不在保护集中的）
-//                 let capturable: Vec<_> = opponent_pieces
-//                     .iter()
-//                     .filter(|pos| !protected.contains(pos))
-//                     .collect();
-
-//                 // 如果当前玩家有吃子任务但无棋子可吃，自动跳过
-//                 if capturable.is_empty() {
-//                     let player = board.current_player;
-//                     let remaining = board.capture_remaining.get(&player).copied().unwrap_or(0);
-
-//                     if remaining > 0 {
-//                         println!("{} 没有可吃的棋子，自动跳过吃棋阶段", player);
-
-//                         // 清零当前玩家的吃子任务
-//                         *board.capture_remaining.get_mut(&player).unwrap() = 0;
-
-//                         // 检查是否所有玩家都完成吃棋
-//                         if board.capture_remaining.values().sum::<u32>() == 0 {
-//                             board.enter_movement_phase();
-//                             continue;
-//                         }
-
-//                         // 切换到下一个玩家
-//                         board.current_player = player.opponent();
-//                         continue;
-//                     }
-//                 }
-
-//                 // 正常处理吃棋输入
-//                 let input = read_input(&format!("{} 请输入吃子位置: ", board.current_player));
-
-//                 match parse_coord(&input) {
-//                     Ok((row, col)) => match board.capture_piece(row, col) {
-//                         Ok(_) => println!("吃棋成功!"),
-//                         Err(e) => println!("操作失败: {}", e),
-//                     },
-//                     Err(e) => println!("输入错误: {}", e),
-//                 }
-//             }
-
-//             GamePhase::Movement => {
-//                 let input = read_input(&format!(
-//                     "{} 请输入移动指令 (原位置 目标位置): ",
-//                     board.current_player
-//                 ));
-
-//                 match parse_move(&input) {
-//                     Ok((from, to)) => match board.move_piece(from, to) {
-//                         Ok(captured) => {
-//                             if captured > 0 {
-//                                 println!("移动成功! 吃掉对方 {} 个棋子", captured);
-//                             } else {
-//                                 println!("移动成功!");
-//                             }
-//                         }
-//                         Err(e) => println!("操作失败: {}", e),
-//                     },
-//                     Err(e) => println!("输入错误: {}", e),
-//                 }
-//             }
-//         }
-//     }
-
-//     // 保存棋谱选项
-//     let save = read_input("是否保存棋谱? (y/n): ");
-//     if save.to_lowercase() == "y" {
-//         let serialized = serde_json::to_string_pretty(board.get_game_record()).unwrap();
-//         let filename = "wudao_game_record.json";
-//         std::fs::write(filename, &serialized).unwrap();
-//         println!("棋谱已保存到 {}", filename);
-//     }
-
-//     println!("\n感谢游玩五道方游戏！");
-// }
-
+    // 请求外部引擎行棋，并执行其给出的动作
+    fn poll_engine(&mut self) {
+        if self.game_over || self.replay.is_some() || !self.is_engine_turn() {
+            return;
+        }
+        let Some(engine) = self.engine.as_mut() else {
+            return;
+        };
 
-use eframe::egui::{self, ViewportBuilder};
-use eframe::egui::{FontData, FontDefinitions, FontFamily};
-use eframe::egui::{Color32, Stroke, FontId, Align2, RichText};
-use std::f32::consts::PI;
-fn main() -> eframe::Result<()> {
+        if !engine.is_thinking() {
+            engine.request(&self.board, ENGINE_MOVETIME);
+            return;
+        }
 
-    let options = eframe::NativeOptions {
-        viewport: ViewportBuilder::default().with_inner_size([800.0, 600.0]),
-        ..Default::default()
-    };
+        let Some(reply) = engine.try_recv() else {
+            return;
+        };
+        let (phase, _) = self.board.get_state();
+        match reply {
+            Ok(action) => match self.board.apply_action(&action) {
+                Ok(_) => {
+                    self.clock.on_action(&mut self.board, action.player(), Instant::now());
+                    self.message = trf("engine.action", &[&action.notation()]);
+                }
+                Err(e) => self.message = trf("engine.action_failed", &[&e]),
+            },
+            Err(e) => {
+                self.message = e;
+                self.engine = None;
+            }
+        }
+        self.after_action(phase);
+    }
     
-eframe::run_native(
-        "五道方游戏",
-        options,
-        Box::new(|cc| {
-            // 设置中文字体
-            let mut fonts = FontDefinitions::default();
-            
-            // 使用系统字体或回退字体
-            fonts.font_data.insert(
-                "chinese".to_owned(),
-                FontData::from_static(include_bytes!("../assets/fonts/NotoSansSC.ttf")), // 替换为您的中文字体路径
-            );
-            
-            // 或者使用默认字体并添加中文字符支持
-            fonts
-                .families
-                .get_mut(&FontFamily::Proportional)
-                .unwrap()
-                .insert(0, "chinese".to_owned());
-                
-            fonts
-                .families
-                .get_mut(&FontFamily::Monospace)
-                .unwrap()
-                .push("chinese".to_owned());
-                
-            cc.egui_ctx.set_fonts(fonts);
-            
-            Box::new(WudaoApp::new())
-        }),
-    )
-}
+    fn handle_cell_click(&mut self, row: usize, col: usize) {
+    let (phase, player) = self.board.get_state();
 
-struct WudaoApp {
-    board: Board,
-    selected_cell: Option<(usize, usize)>,
-    message: String,
-    game_over: bool,
-    show_help: bool,
-    input_mode: InputMode,
-    time: f32, // 用于动画效果的时间变量
-}
+    if self.resume.is_some() {
+        self.message = tr("gui.resume_first").to_string();
+        return;
+    }
 
-#[derive(PartialEq)]
-enum InputMode {
-    Placement,
-    Capture,
-    MovementFrom,
-    MovementTo,
-}
+    if self.is_engine_turn() {
+        self.message = tr("gui.wait_engine").to_string();
+        return;
+    }
 
-impl WudaoApp {
-    fn new() -> Self {
-        Self {
-            board: Board::new(),
-            selected_cell: None,
-            message: String::new(),
-            game_over: false,
-            show_help: true,
-            input_mode: InputMode::Placement,
-            time: 0.0,
+    if let Some(net) = &self.net {
+        if !net.is_connected() {
+            self.message = tr("net.no_opponent").to_string();
+            return;
+        }
+        if player != net.local_player() {
+            self.message = tr("net.wait_opponent").to_string();
+            return;
         }
     }
     
-    fn handle_cell_click(&mut self, row: usize, col: usize) {
-    let (phase, player) = self.board.get_state();
-    
     match phase {
         GamePhase::Placement => {
-            match self.board.place_piece(row, col) {
+            match self.play(GameAction::Place { player, pos: (row, col) }) {
                 Ok(extra) => {
-                    self.message = format!("在({},{})落子", row, col);
+                    self.message = trf("gui.placed", &[&row, &col]);
                     if extra > 0 {
-                        self.message += &format!("，获得额外落子次数: {}", extra);
+                        self.message += &trf("gui.extra_placements", &[&extra]);
                     }
                 }
                 Err(e) => {
-                    self.message = format!("落子失败: {}", e);
+                    self.message = trf("gui.place_failed", &[&e]);
                 }
             }
         }
         GamePhase::Capture => {
-            match self.board.capture_piece(row, col) {
+            match self.play(GameAction::Capture { player, pos: (row, col) }) {
                 Ok(_) => {
-                    self.message = format!("在({},{})吃子成功", row, col);
+                    self.message = trf("gui.captured", &[&row, &col]);
                 }
                 Err(e) => {
-                    self.message = format!("吃子失败: {}", e);
+                    self.message = trf("gui.capture_failed", &[&e]);
                 }
             }
         }
         GamePhase::Movement => {
             if self.input_mode == InputMode::MovementFrom {
                 // 选择要移动的棋子
-                if let Cell::Occupied(p) = self.board.grid[row][col] {
+                if let Cell::Occupied(p) = self.board.cell(row, col) {
                     if p == player {
                         self.selected_cell = Some((row, col));
                         self.input_mode = InputMode::MovementTo;
-                        self.message = format!("已选择棋子({},{})，请选择目标位置", row, col);
+                        self.message = trf("gui.selected", &[&row, &col]);
                     } else {
-                        self.message = "只能选择自己的棋子".to_string();
+                        self.message = tr("gui.select_own").to_string();
                     }
                 } else {
-                    self.message = "请选择有棋子的位置".to_string();
+                    self.message = tr("gui.select_piece").to_string();
                 }
             } else if self.input_mode == InputMode::MovementTo {
                 // 选择目标位置
                 if let Some(from) = self.selected_cell {
                     if from == (row, col) {
-                        self.message = "不能移动到同一位置".to_string();
+                        self.message = tr("gui.same_point").to_string();
                         return;
                     }
                     
-                    match self.board.move_piece(from, (row, col)) {
+                    match self.play(GameAction::Move { player, from, to: (row, col) }) {
                         Ok(captured) => {
                             if captured > 0 {
-                                self.message = format!("从({},{})移动到({},{})成功! 吃掉对方 {} 个棋子", 
-                                    from.0, from.1, row, col, captured);
+                                self.message = trf("gui.moved_captured",
+                                    &[&from.0, &from.1, &row, &col, &captured]);
                             } else {
-                                self.message = format!("从({},{})移动到({},{})成功!", 
-                                    from.0, from.1, row, col);
+                                self.message = trf("gui.moved",
+                                    &[&from.0, &from.1, &row, &col]);
                             }
                             self.selected_cell = None;
                             self.input_mode = InputMode::MovementFrom;
                         }
                         Err(e) => {
-                            self.message = format!("移动失败: {}", e);
+                            self.message = trf("gui.move_failed", &[&e]);
                             self.selected_cell = None;
                             self.input_mode = InputMode::MovementFrom;
                         }
@@ -1799,30 +1108,66 @@ impl WudaoApp {
             }
         }
     }
-    
-    // 检查游戏是否结束
-    if let Some(winner) = self.board.check_winner() {
-        self.message = format!("游戏结束! {} 获胜!", winner);
-        self.game_over = true;
+
+    self.after_action(phase);
+}
+
+    // 动作执行后按动作产生的事件提示奖励模式、更新输入模式和胜负
+    fn after_action(&mut self, phase: GamePhase) {
+        let events = self.board.take_events();
+        self.animator.push_events(&events, self.time);
+        // 放弃吃子的说明放在最后，不被进入新阶段的提示覆盖
+        let mut skipped = Vec::new();
+        for event in &events {
+            match event {
+                GameEvent::PatternFormed { player, pattern, .. } => {
+                    self.message += &trf("gui.pattern_formed", &[player, pattern]);
+                }
+                GameEvent::PhaseChanged { phase } => self.enter_phase(phase),
+                GameEvent::CaptureSkipped { player, remaining } => {
+                    skipped.push(trf("capture.skipped", &[player, remaining]));
+                }
+                GameEvent::GameEnded { winner } if !self.game_over => self.finish_game(*winner),
+                _ => {}
+            }
+        }
+        for note in skipped {
+            self.message = if self.message.is_empty() { note } else { trf("list.join", &[&self.message, &note]) };
+        }
+
+        // 联机时局面可能整体替换而没有事件，以局面为准
+        if events.is_empty() {
+            let (new_phase, _) = self.board.get_state();
+            if new_phase != phase {
+                self.enter_phase(&new_phase);
+            }
+            if let Some(winner) = self.board.check_winner().filter(|_| !self.game_over) {
+                self.finish_game(winner);
+            }
+        }
     }
-    
-    // 更新输入模式
-    let (new_phase, _) = self.board.get_state();
-    if new_phase != phase {
-        match new_phase {
+
+    fn enter_phase(&mut self, phase: &GamePhase) {
+        match phase {
             GamePhase::Placement => self.input_mode = InputMode::Placement,
             GamePhase::Capture => self.input_mode = InputMode::Capture,
             GamePhase::Movement => {
                 self.input_mode = InputMode::MovementFrom;
                 self.selected_cell = None;
-            },
+            }
         }
-        self.message = format!("进入{}", new_phase);
+        self.message = trf("gui.entered_phase", &[phase]);
     }
-}
-    
-    // 修改 draw_board 方法，添加 time 参数
-    fn draw_board(&mut self, ui: &mut egui::Ui, time: f32) {
+
+    fn finish_game(&mut self, winner: Player) {
+        self.message = trf("gui.game_over_winner", &[&winner]);
+        self.game_over = true;
+        self.clock.stop(Instant::now());
+    }
+
+    // 绘制棋盘，返回被点击的格子。annotations 为需要在棋盘上标注的动作（回放时为当前步），
+    // effects 为正在播放的动画及其进度
+    fn draw_board(&self, ui: &mut egui::Ui, board: &Board, annotations: &[GameAction], effects: &[(&Effect, f32)], time: f32) -> Option<(usize, usize)> {
         let cell_size = 50.0;
         let padding = 30.0;
         let board_size = cell_size * 4.0 + padding * 2.0;
@@ -1834,22 +1179,29 @@ impl WudaoApp {
         );
         
         let rect = response.rect;
+        let theme = self.theme;
+        let cell_center = |(row, col): (usize, usize)| {
+            egui::pos2(
+                rect.left() + padding + col as f32 * cell_size,
+                rect.top() + padding + row as f32 * cell_size,
+            )
+        };
         
         // 绘制木质棋盘背景
-        painter.rect_filled(rect, 5.0, Color32::from_rgb(188, 143, 101));
+        painter.rect_filled(rect, 5.0, theme.board);
         
         // 绘制棋盘网格线
         for i in 0..5 {
             let x = rect.left() + padding + i as f32 * cell_size;
             painter.line_segment(
                 [egui::pos2(x, rect.top() + padding), egui::pos2(x, rect.bottom() - padding)],
-                Stroke::new(2.0, Color32::from_rgb(80, 50, 20))
+                Stroke::new(2.0, theme.grid)
             );
             
             let y = rect.top() + padding + i as f32 * cell_size;
             painter.line_segment(
                 [egui::pos2(rect.left() + padding, y), egui::pos2(rect.right() - padding, y)],
-                Stroke::new(2.0, Color32::from_rgb(80, 50, 20))
+                Stroke::new(2.0, theme.grid)
             );
         }
         
@@ -1859,21 +1211,76 @@ impl WudaoApp {
             painter.text(
                 egui::pos2(x, rect.top() + padding - 20.0),
                 Align2::CENTER_CENTER,
-                &i.to_string(),
+                i.to_string(),
                 FontId::proportional(16.0),
-                Color32::from_rgb(50, 30, 10)
+                theme.coords
             );
             
             let y = rect.top() + padding + i as f32 * cell_size;
             painter.text(
                 egui::pos2(rect.left() + padding - 20.0, y),
                 Align2::CENTER_CENTER,
-                &i.to_string(),
+                i.to_string(),
                 FontId::proportional(16.0),
-                Color32::from_rgb(50, 30, 10)
+                theme.coords
             );
         }
         
+        // 悬停的格子，各项悬停说明在最后一起显示
+        let hovered = response.hover_pos().map(|pos| {
+            (
+                ((pos.y - rect.top() - padding + cell_size / 2.0) / cell_size) as usize,
+                ((pos.x - rect.left() - padding + cell_size / 2.0) / cell_size) as usize,
+            )
+        });
+        let mut tooltip = Vec::new();
+
+        // 生效中的奖励模式画在棋子下面：成方画方框，成州画横条或竖条，三斜、四斜和成龙画连线
+        if self.show_patterns {
+            for (owner, pattern) in board.active_patterns() {
+                let cells = pattern.cells();
+                let (first, last) = (cell_center(cells[0]), cell_center(cells[cells.len() - 1]));
+                match pattern {
+                    RewardPattern::Square { .. } => {
+                        let frame = egui::Rect::from_two_pos(first, last).expand(cell_size * 0.45);
+                        painter.rect_stroke(frame, 8.0, Stroke::new(3.0, theme.patterns[0]));
+                    }
+                    RewardPattern::Row { .. } | RewardPattern::Col { .. } => {
+                        let bar = egui::Rect::from_two_pos(first, last).expand(cell_size * 0.3);
+                        painter.rect_filled(bar, cell_size * 0.3, theme.patterns[3].gamma_multiply(0.35));
+                    }
+                    RewardPattern::Tri { .. } | RewardPattern::Tetra { .. } | RewardPattern::Dragon { .. } => {
+                        let (width, color) = match pattern {
+                            RewardPattern::Tri { .. } => (4.0, theme.patterns[1]),
+                            RewardPattern::Tetra { .. } => (4.0, theme.patterns[2]),
+                            _ => (6.0, theme.patterns[4]),
+                        };
+                        // 两端各伸出半格，不被端点的棋子完全盖住
+                        let reach = (last - first).normalized() * cell_size * 0.5;
+                        painter.line_segment([first - reach, last + reach], Stroke::new(width, color));
+                    }
+                }
+                if hovered.is_some_and(|cell| cells.contains(&cell)) {
+                    tooltip.push(trf("gui.active_pattern", &[&owner, &pattern]));
+                }
+            }
+        }
+
+        // 吃棋阶段当前玩家可以吃的棋子
+        let capturing = board.get_state().0 == GamePhase::Capture;
+        let capturable: Vec<(usize, usize)> = if capturing {
+            board
+                .legal_actions()
+                .into_iter()
+                .filter_map(|action| match action {
+                    GameAction::Capture { pos, .. } => Some(pos),
+                    _ => None,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         // 绘制棋子
         for row in 0..5 {
             for col in 0..5 {
@@ -1881,42 +1288,26 @@ impl WudaoApp {
                 let y = rect.top() + padding + row as f32 * cell_size;
                 let center = egui::pos2(x, y);
                 
-                match self.board.grid[row][col] {
-                    Cell::Occupied(Player::Black) => {
-                        // 绘制黑色棋子（带有光泽效果）
-                        painter.circle_filled(center, cell_size / 3.0, Color32::from_rgb(40, 40, 40));
-                        painter.circle_filled(center, cell_size / 3.5, Color32::from_rgb(20, 20, 20));
-                        
-                        // 添加高光
-                        let highlight_pos = egui::pos2(x - cell_size/8.0, y - cell_size/8.0);
-                        painter.circle_filled(highlight_pos, cell_size / 10.0, Color32::from_rgba_premultiplied(255, 255, 255, 100));
-                    }
-                    Cell::Occupied(Player::White) => {
-                        // 绘制白色棋子（带有阴影效果）
-                        painter.circle_filled(center, cell_size / 3.0, Color32::from_rgb(230, 230, 230));
-                        painter.circle_stroke(center, cell_size / 3.0, Stroke::new(1.5, Color32::from_rgb(100, 100, 100)));
-                        
-                        // 添加阴影
-                        let shadow_pos = egui::pos2(x + cell_size/10.0, y + cell_size/10.0);
-                        painter.circle_filled(shadow_pos, cell_size / 3.1, Color32::from_rgba_premultiplied(0, 0, 0, 40));
+                match board.cell(row, col) {
+                    // 正在落下或滑入的棋子在后面单独绘制
+                    Cell::Occupied(_) if effects.iter().any(|(effect, _)| matches!(effect,
+                        Effect::Drop { pos, .. } | Effect::Slide { to: pos, .. } if *pos == (row, col))) => {}
+                    Cell::Occupied(player) => {
+                        // 吃棋阶段淡化受保护的棋子，突出可以吃的棋子
+                        let alpha = if capturing && board.is_protected(row, col) { 0.45 } else { 1.0 };
+                        draw_piece(&painter, theme, center, player, cell_size, 1.0, alpha);
                     }
                     Cell::Empty => {
                         // 在空位添加浅色圆点提示
-                        painter.circle_filled(center, 3.0, Color32::from_rgba_premultiplied(0, 0, 0, 50));
+                        painter.circle_filled(center, 3.0, theme.grid.gamma_multiply(0.3));
                     }
                 }
                 
                 // 高亮显示受保护的棋子
-                let is_protected = if let Cell::Occupied(player) = self.board.grid[row][col] {
-                    self.board.reward_pieces
-                        .get(&player)
-                        .map_or(false, |protected| protected.contains(&(row, col)))
-                } else {
-                    false
-                };
+                let is_protected = board.is_protected(row, col);
                 
                 if is_protected {
-                    painter.circle_stroke(center, cell_size / 2.8, Stroke::new(2.5, Color32::GOLD));
+                    painter.circle_stroke(center, cell_size / 2.8, Stroke::new(2.5, theme.protected));
                     
                     // 添加保护标记（皇冠图标）
                     let crown_points = [
@@ -1928,110 +1319,335 @@ impl WudaoApp {
                     ];
                     painter.add(eframe::egui::Shape::line(
                         crown_points.to_vec(),
-                        Stroke::new(1.5, Color32::GOLD)
+                        Stroke::new(1.5, theme.protected)
                     ));
                 }
                 
                 // 高亮显示选中的棋子
                 if self.selected_cell == Some((row, col)) {
-                    painter.circle_stroke(center, cell_size / 2.8, Stroke::new(3.0, Color32::from_rgb(0, 150, 255)));
+                    painter.circle_stroke(center, cell_size / 2.8, Stroke::new(3.0, theme.selected));
                     
                     // 添加脉动动画效果
                     let pulse = (time * 5.0).sin() * 2.0 + 2.0;
-                    painter.circle_stroke(center, cell_size / 2.8 + pulse, Stroke::new(1.0, Color32::from_rgba_premultiplied(0, 150, 255, 100)));
+                    painter.circle_stroke(center, cell_size / 2.8 + pulse, Stroke::new(1.0, theme.selected.gamma_multiply(0.4)));
                 }
                 
                 // 高亮显示可移动的位置（在移动阶段）
-                if self.input_mode == InputMode::MovementTo {
-                    if let Some((from_row, from_col)) = self.selected_cell {
-                        let row_diff = from_row.abs_diff(row);
-                        let col_diff = from_col.abs_diff(col);
-                        let is_adjacent = (row_diff == 1 && col_diff == 0) || (row_diff == 0 && col_diff == 1);
-                        
-                        if is_adjacent && self.board.grid[row][col] == Cell::Empty {
-                            painter.circle_filled(center, 8.0, Color32::from_rgba_premultiplied(0, 255, 0, 100));
-                        }
+                if self.input_mode == InputMode::MovementTo
+                    && let Some((from_row, from_col)) = self.selected_cell
+                {
+                    let row_diff = from_row.abs_diff(row);
+                    let col_diff = from_col.abs_diff(col);
+                    let is_adjacent = (row_diff == 1 && col_diff == 0) || (row_diff == 0 && col_diff == 1);
+                    
+                    // 可走位置画成方块，与受保护棋子的圆环形状不同
+                    if is_adjacent && board.cell(row, col) == Cell::Empty {
+                        let square = egui::Rect::from_center_size(center, egui::vec2(12.0, 12.0));
+                        painter.rect_filled(square, 2.0, theme.move_target.gamma_multiply(0.7));
+                        painter.rect_stroke(square, 2.0, Stroke::new(1.5, theme.move_target));
                     }
                 }
             }
         }
         
-        // 处理点击事件
-        if response.clicked() {
-            if let Some(pos) = response.interact_pointer_pos() {
-                let col = ((pos.x - rect.left() - padding + cell_size / 2.0) / cell_size) as usize;
-                let row = ((pos.y - rect.top() - padding + cell_size / 2.0) / cell_size) as usize;
-                
-                if row < 5 && col < 5 {
-                    self.handle_cell_click(row, col);
+        // 可以吃的棋子套上闪动的红色准星
+        let target = theme.capture;
+        let pulse = (time * 4.0).sin() * 1.5 + 1.5;
+        for &pos in &capturable {
+            let c = cell_center(pos);
+            let r = cell_size / 2.6 + pulse;
+            painter.circle_stroke(c, r, Stroke::new(2.0, target));
+            for dir in [egui::vec2(1.0, 0.0), egui::vec2(-1.0, 0.0), egui::vec2(0.0, 1.0), egui::vec2(0.0, -1.0)] {
+                painter.line_segment([c + dir * (r - 4.0), c + dir * (r + 4.0)], Stroke::new(2.0, target));
+            }
+        }
+
+        // 动画：落子由大到小落下，移动沿直线滑行，被吃的棋子放大淡出，新形成的模式闪烁
+        for (effect, progress) in effects {
+            let eased = 1.0 - (1.0 - progress).powi(2);
+            match effect {
+                Effect::Drop { pos, player } => {
+                    draw_piece(&painter, theme, cell_center(*pos), *player, cell_size, 1.6 - 0.6 * eased, eased);
+                }
+                Effect::Slide { from, to, player } => {
+                    let center = cell_center(*from) + (cell_center(*to) - cell_center(*from)) * eased;
+                    draw_piece(&painter, theme, center, *player, cell_size, 1.0, 1.0);
+                }
+                Effect::Fade { pos, player } => {
+                    draw_piece(&painter, theme, cell_center(*pos), *player, cell_size, 1.0 + 0.4 * eased, 1.0 - eased);
+                }
+                Effect::Flash { cells } => {
+                    let glow = (progress * 3.0 * PI).sin().abs() * (1.0 - progress);
+                    for cell in cells {
+                        let frame = egui::Rect::from_center_size(cell_center(*cell), egui::vec2(cell_size * 0.9, cell_size * 0.9));
+                        painter.rect_filled(frame, 6.0, theme.flash.gamma_multiply(0.5 * glow));
+                        painter.rect_stroke(frame, 6.0, Stroke::new(3.0, theme.flash.gamma_multiply(glow)));
+                    }
                 }
             }
         }
+
+        // 提示的动作：落子和吃子标出目标格，移动标出起点、终点和箭头；圆环用虚线，与选中和保护标记区分
+        if let Some((hash, action)) = &self.hint
+            && *hash == board.position_hash()
+        {
+            let hint_color = theme.hint;
+            let pulse = (time * 4.0).sin() * 2.0 + 2.0;
+            match action {
+                GameAction::Place { pos, .. } | GameAction::Capture { pos, .. } => {
+                    dashed_circle(&painter, cell_center(*pos), cell_size / 2.6 + pulse, Stroke::new(3.0, hint_color));
+                }
+                GameAction::Move { from, to, .. } => {
+                    dashed_circle(&painter, cell_center(*from), cell_size / 2.6, Stroke::new(2.0, hint_color));
+                    dashed_circle(&painter, cell_center(*to), cell_size / 2.6 + pulse, Stroke::new(3.0, hint_color));
+                    painter.arrow(cell_center(*from), cell_center(*to) - cell_center(*from), Stroke::new(3.0, hint_color));
+                }
+                _ => {}
+            }
+        }
+
+        // 标注动作：落子蓝圈、移动箭头、吃子红叉，形成的奖励模式框出所含格子
+        let event_color = theme.annotation;
+        for entry in annotations {
+            match entry {
+                GameAction::Place { pos, .. } => {
+                    painter.circle_stroke(cell_center(*pos), cell_size / 2.6, Stroke::new(3.0, event_color));
+                }
+                GameAction::Move { from, to, .. } => {
+                    painter.circle_stroke(cell_center(*from), 6.0, Stroke::new(2.0, event_color));
+                    painter.arrow(cell_center(*from), cell_center(*to) - cell_center(*from), Stroke::new(3.0, event_color));
+                }
+                GameAction::Capture { pos, .. } => {
+                    let c = cell_center(*pos);
+                    let d = cell_size / 5.0;
+                    let red = Stroke::new(3.0, theme.capture);
+                    painter.line_segment([c + egui::vec2(-d, -d), c + egui::vec2(d, d)], red);
+                    painter.line_segment([c + egui::vec2(-d, d), c + egui::vec2(d, -d)], red);
+                }
+                GameAction::Reward { pattern, .. } => {
+                    for cell in pattern.cells() {
+                        let frame = egui::Rect::from_center_size(cell_center(cell), egui::vec2(cell_size * 0.8, cell_size * 0.8));
+                        painter.rect_stroke(frame, 4.0, Stroke::new(2.5, theme.reward));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // 威胁提示：黑方标记在左、白方标记在右，差一子为实心，差两子为空心
+        if self.show_threats {
+            let threats: Vec<Threat> = [Player::Black, Player::White]
+                .into_iter()
+                .flat_map(|p| board.threats(p))
+                .collect();
+
+            for threat in &threats {
+                let (offset, color) = match threat.player {
+                    Player::Black => (-8.0, theme.black),
+                    Player::White => (8.0, theme.white),
+                };
+                for &(row, col) in &threat.needed {
+                    let center = egui::pos2(
+                        rect.left() + padding + col as f32 * cell_size + offset,
+                        rect.top() + padding + row as f32 * cell_size - 8.0,
+                    );
+                    let diamond = vec![
+                        center + egui::vec2(0.0, -5.0),
+                        center + egui::vec2(5.0, 0.0),
+                        center + egui::vec2(0.0, 5.0),
+                        center + egui::vec2(-5.0, 0.0),
+                    ];
+                    if threat.needed.len() == 1 {
+                        painter.add(eframe::egui::Shape::convex_polygon(diamond, color, Stroke::new(1.0, theme.alert)));
+                    } else {
+                        painter.add(eframe::egui::Shape::closed_line(diamond, Stroke::new(1.5, color)));
+                    }
+                }
+            }
+
+            // 悬停在标记格上时列出相关模式
+            if let Some(cell) = hovered {
+                tooltip.extend(
+                    threats
+                        .iter()
+                        .filter(|t| t.needed.contains(&cell))
+                        .map(|t| trf("gui.threat", &[&t.player, &t.needed.len(), &t.pattern, &t.reward])),
+                );
+            }
+        }
+
+        if !tooltip.is_empty() {
+            response.clone().on_hover_text_at_pointer(tooltip.join("\n"));
+        }
+
+        // 处理点击事件
+        if response.clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let col = ((pos.x - rect.left() - padding + cell_size / 2.0) / cell_size) as usize;
+            let row = ((pos.y - rect.top() - padding + cell_size / 2.0) / cell_size) as usize;
+            
+            if row < 5 && col < 5 {
+                return Some((row, col));
+            }
+        }
+        None
+    }
+}
+
+// 着法记录按回合分组：一方连续的落子、移动和吃子为一个回合，形成奖励模式之后的
+// 额外落子和吃子、奖励模式本身以及超时认输缩进显示在回合的第一个动作之下
+fn history_lines(record: &[GameAction]) -> Vec<HistoryLine> {
+    let mut lines = Vec::new();
+    let mut turn = 0;
+    let mut mover = None;
+    let mut rewarded = false;
+    for (index, action) in record.iter().enumerate() {
+        let (indent, text) = match action {
+            GameAction::Clock { .. } => continue,
+            GameAction::Reward { pattern, .. } => {
+                rewarded = true;
+                (true, format!("{} +{}", pattern, pattern.reward()))
+            }
+            GameAction::Timeout { player } => (true, trf("record.timeout", &[player])),
+            GameAction::Resign { player } => (true, trf("record.resign", &[player])),
+            _ if rewarded && mover == Some(action.player()) => (true, action.notation()),
+            _ => {
+                turn += 1;
+                mover = Some(action.player());
+                rewarded = false;
+                (false, format!("{}. {} {}", turn, action.player(), action.notation()))
+            }
+        };
+        lines.push(HistoryLine { index, turn, indent, text });
+    }
+    lines
+}
+
+// 复制到剪贴板的着法记录文本
+fn history_text(lines: &[HistoryLine]) -> String {
+    lines
+        .iter()
+        .map(|line| if line.indent { format!("    {}", line.text) } else { line.text.clone() })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 绘制一枚棋子，scale 为相对正常大小的缩放，alpha 为不透明度（动画用）
+fn draw_piece(painter: &egui::Painter, theme: &Theme, center: egui::Pos2, player: Player, cell_size: f32, scale: f32, alpha: f32) {
+    let size = cell_size * scale;
+    match player {
+        Player::Black => {
+            // 绘制黑色棋子（带有光泽效果）
+            painter.circle_filled(center, size / 3.0, theme.black_rim.gamma_multiply(alpha));
+            painter.circle_filled(center, size / 3.5, theme.black.gamma_multiply(alpha));
+
+            // 添加高光
+            let highlight_pos = center - egui::vec2(size / 8.0, size / 8.0);
+            painter.circle_filled(highlight_pos, size / 10.0, theme.gloss.gamma_multiply(alpha));
+        }
+        Player::White => {
+            // 绘制白色棋子（带有阴影效果）
+            painter.circle_filled(center, size / 3.0, theme.white.gamma_multiply(alpha));
+            painter.circle_stroke(center, size / 3.0, Stroke::new(1.5, theme.white_outline.gamma_multiply(alpha)));
+
+            // 添加阴影
+            let shadow_pos = center + egui::vec2(size / 10.0, size / 10.0);
+            painter.circle_filled(shadow_pos, size / 3.1, theme.shadow.gamma_multiply(alpha));
+        }
     }
 }
 
+// 虚线圆环
+fn dashed_circle(painter: &egui::Painter, center: egui::Pos2, radius: f32, stroke: Stroke) {
+    let points: Vec<egui::Pos2> = (0..=48)
+        .map(|i| center + egui::Vec2::angled(i as f32 / 48.0 * 2.0 * PI) * radius)
+        .collect();
+    painter.extend(egui::Shape::dashed_line(&points, stroke, 6.0, 4.0));
+}
+
 // 更新 WudaoApp 的 update 方法
 impl eframe::App for WudaoApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // 更新时间用于动画
         self.time += ctx.input(|i| i.unstable_dt);
+        self.animator.prune(self.time);
+
+        self.poll_engine();
+        self.poll_net();
+        self.poll_clock();
+        self.poll_hint();
+        self.autosave();
         
         // 设置窗口背景色
         ctx.set_visuals(eframe::egui::Visuals {
-            window_fill: Color32::from_rgb(245, 235, 220),
-            panel_fill: Color32::from_rgb(245, 235, 220),
+            window_fill: self.theme.background,
+            panel_fill: self.theme.background,
             ..Default::default()
         });
         
+        self.draw_file_dialog(ctx);
+        self.draw_resume_dialog(ctx);
+
+        if self.show_history {
+            self.draw_history(ctx);
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading(RichText::new("五道方游戏").color(Color32::from_rgb(120, 70, 30)).font(FontId::proportional(28.0)));
+            ui.heading(RichText::new(tr("gui.title")).color(self.theme.title).font(FontId::proportional(28.0)));
             
             // 游戏状态显示区域
             ui.add_space(10.0);
             let (phase, player) = self.board.get_state();
+
+            if self.net.is_none() && self.replay.is_none() {
+                self.draw_clocks(ui);
+                ui.add_space(10.0);
+            }
             
             // 创建状态面板
             egui::Frame::group(ui.style())
-                .fill(Color32::from_rgb(250, 245, 235))
-                .stroke(Stroke::new(1.0, Color32::from_rgb(180, 150, 120)))
+                .fill(self.theme.panel)
+                .stroke(Stroke::new(1.0, self.theme.panel_stroke))
                 .rounding(5.0)
                 .show(ui, |ui| {
                     ui.horizontal(|ui| {
-                        ui.label(RichText::new("当前阶段:").font(FontId::proportional(16.0)).color(Color32::from_rgb(100, 60, 20)));
-                        ui.label(RichText::new(format!("{}", phase)).font(FontId::proportional(16.0)).color(Color32::from_rgb(80, 40, 10)));
+                        ui.label(RichText::new(tr("gui.phase")).font(FontId::proportional(16.0)).color(self.theme.label));
+                        ui.label(RichText::new(format!("{}", phase)).font(FontId::proportional(16.0)).color(self.theme.value));
                         
                         ui.add_space(20.0);
                         
-                        ui.label(RichText::new("当前玩家:").font(FontId::proportional(16.0)).color(Color32::from_rgb(100, 60, 20)));
+                        ui.label(RichText::new(tr("gui.player")).font(FontId::proportional(16.0)).color(self.theme.label));
                         ui.label(RichText::new(format!("{}", player)).font(FontId::proportional(16.0)).color(match player {
-                            Player::Black => Color32::BLACK,
-                            Player::White => Color32::from_rgb(80, 80, 80),
+                            Player::Black => self.theme.black_label,
+                            Player::White => self.theme.white_label,
                         }));
                     });
                     
                     // 显示额外的游戏状态信息
                     match phase {
-                        GamePhase::Placement if self.board.extra_moves > 0 => {
+                        GamePhase::Placement if self.board.extra_moves() > 0 => {
                             ui.horizontal(|ui| {
-                                ui.label(RichText::new("额外落子次数:").font(FontId::proportional(14.0)));
-                                ui.label(RichText::new(format!("{}", self.board.extra_moves)).font(FontId::proportional(14.0)).color(Color32::DARK_GREEN));
+                                ui.label(RichText::new(tr("gui.extra")).font(FontId::proportional(14.0)));
+                                ui.label(RichText::new(format!("{}", self.board.extra_moves())).font(FontId::proportional(14.0)).color(self.theme.reward));
                             });
                         }
                         GamePhase::Capture => {
-                            let remaining = self.board.capture_remaining
-                                .get(&player)
-                                .copied()
-                                .unwrap_or(0);
+                            let remaining = self.board.capture_remaining(player);
+                            let capturable = self.board.legal_actions().len();
                             ui.horizontal(|ui| {
-                                ui.label(RichText::new("剩余吃子数量:").font(FontId::proportional(14.0)));
-                                ui.label(RichText::new(format!("{}", remaining)).font(FontId::proportional(14.0)).color(Color32::DARK_RED));
+                                ui.label(RichText::new(tr("gui.captures_left")).font(FontId::proportional(14.0)));
+                                // 每剩一次吃子显示一个红点
+                                ui.label(RichText::new(format!("{} {}", remaining, "●".repeat(remaining as usize))).font(FontId::proportional(14.0)).color(self.theme.capture));
+                                ui.add_space(10.0);
+                                ui.label(RichText::new(trf("gui.capturable", &[&capturable])).font(FontId::proportional(14.0)).color(self.theme.muted));
                             });
                         }
                         GamePhase::Movement => {
                             if self.input_mode == InputMode::MovementFrom {
-                                ui.label(RichText::new("请选择要移动的棋子").font(FontId::proportional(14.0)).color(Color32::DARK_BLUE));
+                                ui.label(RichText::new(tr("gui.choose_piece")).font(FontId::proportional(14.0)).color(self.theme.message));
                             } else if self.input_mode == InputMode::MovementTo {
-                                ui.label(RichText::new("请选择目标位置").font(FontId::proportional(14.0)).color(Color32::DARK_BLUE));
+                                ui.label(RichText::new(tr("gui.choose_target")).font(FontId::proportional(14.0)).color(self.theme.message));
                             }
                         }
                         _ => {}
@@ -2040,43 +1656,186 @@ impl eframe::App for WudaoApp {
             
             ui.add_space(10.0);
             
+            // 文件操作
+            ui.horizontal(|ui| {
+                if ui.button(RichText::new(tr("gui.save")).font(FontId::proportional(14.0))).clicked() {
+                    self.save_game();
+                }
+                if ui.button(RichText::new(tr("gui.save_as")).font(FontId::proportional(14.0))).clicked() {
+                    self.file_dialog = Some(FileDialog::SaveAs {
+                        path: self.save_path.clone().unwrap_or_else(|| "wudao_game_record.json".to_string()),
+                        kind: self.save_kind,
+                    });
+                }
+                if ui.button(RichText::new(tr("gui.open")).font(FontId::proportional(14.0))).clicked() {
+                    self.file_dialog = Some(FileDialog::Open {
+                        path: self.save_path.clone().unwrap_or_else(|| "wudao_game_record.json".to_string()),
+                    });
+                }
+                if let Some(path) = &self.save_path {
+                    ui.label(RichText::new(format!("{} ({})", path, self.save_kind)).font(FontId::proportional(12.0)).color(self.theme.muted));
+                }
+            });
+
             // 操作按钮区域
             ui.horizontal(|ui| {
-                if ui.button(RichText::new("游戏规则").font(FontId::proportional(14.0))).clicked() {
+                if ui.button(RichText::new(tr("gui.rules")).font(FontId::proportional(14.0))).clicked() {
                     self.show_help = !self.show_help;
                 }
                 
-                if ui.button(RichText::new("认输").font(FontId::proportional(14.0))).clicked() {
-                    self.message = format!("{} 认输，游戏结束！", player);
+                if ui.button(RichText::new(tr("gui.resign")).font(FontId::proportional(14.0))).clicked() && !self.game_over {
+                    // 联机时由本方认输，而不是当前行棋方
+                    let loser = match self.net.as_mut() {
+                        Some(net) => {
+                            net.resign();
+                            net.local_player()
+                        }
+                        None => player,
+                    };
+                    self.board.record_resign(loser);
+                    self.message = trf("gui.resigned", &[&loser]);
                     self.game_over = true;
+                    self.clock.stop(Instant::now());
                 }
                 
-                if ui.button(RichText::new("新游戏").font(FontId::proportional(14.0))).clicked() {
-                    *self = Self::new();
+                if ui.button(RichText::new(tr("gui.new_game")).font(FontId::proportional(14.0))).clicked() {
+                    if self.net.as_ref().is_some_and(|n| n.role() == net::Role::Client) {
+                        self.message = tr("gui.new_game_host_only").to_string();
+                    } else {
+                        self.start_game(Board::new());
+                    }
                 }
                 
                 // 添加撤销按钮（如果支持的话）
-                if ui.button(RichText::new("悔棋").font(FontId::proportional(14.0))).clicked() {
-                    self.message = "悔棋功能尚未实现".to_string();
+                if ui.button(RichText::new(tr("gui.undo")).font(FontId::proportional(14.0))).clicked() {
+                    self.message = tr("gui.undo_unavailable").to_string();
+                }
+
+                if ui.button(RichText::new(tr("gui.hint")).font(FontId::proportional(14.0))).clicked() {
+                    self.request_hint();
+                }
+
+                if ui.button(RichText::new(tr("gui.online")).font(FontId::proportional(14.0))).clicked() {
+                    self.show_net = !self.show_net;
+                }
+
+                if ui.button(RichText::new(tr("gui.replay")).font(FontId::proportional(14.0))).clicked() {
+                    self.show_replay = !self.show_replay;
+                }
+
+                ui.checkbox(&mut self.show_threats, RichText::new(tr("gui.threats")).font(FontId::proportional(14.0)));
+
+                ui.checkbox(&mut self.show_patterns, RichText::new(tr("gui.patterns")).font(FontId::proportional(14.0)));
+
+                if ui.checkbox(&mut self.show_history, RichText::new(tr("gui.history")).font(FontId::proportional(14.0))).changed() && !self.show_history {
+                    self.history_view = None;
+                }
+
+                let mut analyzing = self.analyzer.is_some();
+                if ui.checkbox(&mut analyzing, RichText::new(tr("gui.analysis")).font(FontId::proportional(14.0))).changed() {
+                    self.analyzer = analyzing.then(analysis::Analyzer::spawn);
+                }
+
+                let mut show_book = self.show_book;
+                if ui.checkbox(&mut show_book, RichText::new(tr("gui.book")).font(FontId::proportional(14.0))).changed() {
+                    if show_book && self.book.is_none() {
+                        match book::Book::load(&book::default_path()) {
+                            Ok(book) => self.book = Some(book),
+                            Err(e) => {
+                                self.message = trf("book.load_failed", &[&e]);
+                                show_book = false;
+                            }
+                        }
+                    }
+                    self.show_book = show_book;
+                }
+
+                let mut time_control = self.time_control;
+                egui::ComboBox::from_id_source("time_control")
+                    .selected_text(RichText::new(time_control.to_string()).font(FontId::proportional(14.0)))
+                    .show_ui(ui, |ui| {
+                        for preset in clock::TimeControl::PRESETS {
+                            ui.selectable_value(&mut time_control, preset, preset.to_string());
+                        }
+                    });
+                if time_control != self.time_control {
+                    self.time_control = time_control;
+                    // 尚未行棋时立即生效，否则从下一局开始
+                    if self.board.get_game_record().is_empty() {
+                        self.clock = clock::GameClock::new(time_control, self.board.current_player(), Instant::now());
+                        self.message = trf("gui.time_control", &[&time_control]);
+                    } else {
+                        self.message = trf("gui.time_control_next", &[&time_control]);
+                    }
+                }
+
+                // 动画速度，关闭后立即停止正在播放的动画
+                let mut speed = self.animator.speed();
+                egui::ComboBox::from_id_source("animation")
+                    .selected_text(RichText::new(trf("gui.animation", &[&speed])).font(FontId::proportional(14.0)))
+                    .show_ui(ui, |ui| {
+                        for option in AnimationSpeed::ALL {
+                            ui.selectable_value(&mut speed, option, option.to_string());
+                        }
+                    });
+                if speed != self.animator.speed() {
+                    self.animator.set_speed(speed);
+                }
+
+                // 配色主题，选择后保存，下次启动时沿用
+                let mut selected = self.theme;
+                egui::ComboBox::from_id_source("theme")
+                    .selected_text(RichText::new(trf("gui.theme", &[&selected.name()])).font(FontId::proportional(14.0)))
+                    .show_ui(ui, |ui| {
+                        for option in theme::THEMES {
+                            ui.selectable_value(&mut selected, option, option.name());
+                        }
+                    });
+                if selected != self.theme {
+                    self.theme = selected;
+                    if let Err(e) = theme::save(selected) {
+                        self.message = trf("theme.save_failed", &[&e]);
+                    }
+                }
+
+                // 界面语言，切换后立即生效（已显示的消息不再翻译）
+                let mut lang = i18n::lang();
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(RichText::new(lang.native_name()).font(FontId::proportional(14.0)))
+                    .show_ui(ui, |ui| {
+                        for option in i18n::Lang::ALL {
+                            ui.selectable_value(&mut lang, option, option.native_name());
+                        }
+                    });
+                if lang != i18n::lang() {
+                    i18n::set_lang(lang);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Title(tr("gui.title").to_string()));
                 }
             });
+
+            if self.show_net {
+                ui.add_space(10.0);
+                self.draw_net_panel(ui);
+            }
+
+            if self.show_replay {
+                ui.add_space(10.0);
+                self.draw_replay_panel(ui);
+            }
             
             ui.add_space(10.0);
             
             // 帮助提示
             if self.show_help {
                 egui::Frame::group(ui.style())
-                    .fill(Color32::from_rgba_premultiplied(255, 255, 240, 200))
-                    .stroke(Stroke::new(1.0, Color32::from_rgb(220, 200, 100)))
+                    .fill(self.theme.panel)
+                    .stroke(Stroke::new(1.0, self.theme.panel_stroke))
                     .rounding(5.0)
                     .show(ui, |ui| {
-                        ui.collapsing(RichText::new("游戏规则").font(FontId::proportional(16.0)).color(Color32::from_rgb(120, 70, 30)), |ui| {
-                            ui.label(RichText::new("• 游戏分为三个阶段: 落子阶段、吃棋阶段、走子阶段").font(FontId::proportional(14.0)));
-                            ui.label(RichText::new("• 落子阶段: 玩家轮流在5x5棋盘上放置棋子").font(FontId::proportional(14.0)));
-                            ui.label(RichText::new("• 形成特定模式可获得奖励: 成方(+1子)、成三斜(+1子)、成四斜(+1子)、成州(+2子)、成龙(+2子)").font(FontId::proportional(14.0)));
-                            ui.label(RichText::new("• 棋盘满后进入吃棋阶段: 后落子的玩家先吃棋，轮流吃掉对方棋子").font(FontId::proportional(14.0)));
-                            ui.label(RichText::new("• 吃棋完成后进入走子阶段: 玩家轮流移动自己的棋子").font(FontId::proportional(14.0)));
-                            ui.label(RichText::new("• 胜利条件: 对方棋子少于3个或无法移动时获胜").font(FontId::proportional(14.0)));
+                        ui.collapsing(RichText::new(tr("gui.rules")).font(FontId::proportional(16.0)).color(self.theme.title), |ui| {
+                            for rule in RULES {
+                                ui.label(RichText::new(format!("• {}", tr(rule))).font(FontId::proportional(14.0)));
+                            }
                         });
                     });
             }
@@ -2086,42 +1845,97 @@ impl eframe::App for WudaoApp {
             // 显示消息
             if !self.message.is_empty() {
                 egui::Frame::group(ui.style())
-                    .fill(Color32::from_rgba_premultiplied(240, 248, 255, 200))
-                    .stroke(Stroke::new(1.0, Color32::from_rgb(150, 180, 220)))
+                    .fill(self.theme.panel)
+                    .stroke(Stroke::new(1.0, self.theme.panel_stroke))
                     .rounding(5.0)
                     .show(ui, |ui| {
-                        ui.label(RichText::new(&self.message).font(FontId::proportional(14.0)).color(Color32::from_rgb(50, 80, 120)));
+                        ui.label(RichText::new(&self.message).font(FontId::proportional(14.0)).color(self.theme.message));
                     });
             }
             
             ui.add_space(10.0);
+
+            if self.analyzer.is_some() {
+                let board = match &self.replay {
+                    Some(replay) => replay.replayer.get_current_board().clone(),
+                    None => self.board.clone(),
+                };
+                self.draw_analysis(ui, &board);
+                ui.add_space(10.0);
+            }
+
+            if self.show_book {
+                let board = match &self.replay {
+                    Some(replay) => replay.replayer.get_current_board().clone(),
+                    None => self.board.clone(),
+                };
+                let clicked = self.draw_book(ui, &board);
+                // 回放时只浏览，不落子
+                if let (Some((row, col)), None) = (clicked, &self.replay)
+                    && !self.game_over
+                {
+                    self.handle_cell_click(row, col);
+                }
+                ui.add_space(10.0);
+            }
+
+            if self.replay.is_some() {
+                self.draw_replay(ui);
+                return;
+            }
+
+            // 查看历史局面时只读，不响应点击
+            if let Some((index, board)) = &self.history_view {
+                let turn = history_lines(self.board.get_game_record())
+                    .iter()
+                    .find(|line| line.index == *index)
+                    .map_or(0, |line| line.turn);
+                let mut back = false;
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(trf("history.viewing", &[&turn])).font(FontId::proportional(14.0)).color(self.theme.message));
+                    back = ui.button(RichText::new(tr("history.return_live")).font(FontId::proportional(14.0))).clicked();
+                });
+                // 联机同步可能整体替换棋谱，序号不一定还有效
+                let annotations = self.board.get_game_record().get(*index).map_or(&[][..], std::slice::from_ref);
+                ui.vertical_centered(|ui| {
+                    self.draw_board(ui, board, annotations, &[], self.time);
+                });
+                if back {
+                    self.history_view = None;
+                }
+                return;
+            }
             
             // 检查游戏是否结束
             if self.game_over {
-                ui.heading(RichText::new("游戏结束!").color(Color32::from_rgb(180, 40, 40)).font(FontId::proportional(24.0)));
+                ui.heading(RichText::new(tr("gui.game_over")).color(self.theme.alert).font(FontId::proportional(24.0)));
                 return;
             }
             
             // 显示棋盘
-            ui.vertical_centered(|ui| {
-                self.draw_board(ui, self.time);
-            });
+            let clicked = ui.vertical_centered(|ui| {
+                let effects = self.animator.effects(self.time);
+                self.draw_board(ui, &self.board, &[], &effects, self.time)
+            }).inner;
+            if let Some((row, col)) = clicked {
+                self.handle_cell_click(row, col);
+            }
             
             // 添加玩家提示
             ui.add_space(10.0);
             ui.horizontal(|ui| {
-                ui.label(RichText::new("●").color(Color32::BLACK).font(FontId::proportional(20.0)));
-                ui.label(RichText::new("黑方").font(FontId::proportional(14.0)));
+                ui.label(RichText::new("●").color(self.theme.black_label).font(FontId::proportional(20.0)));
+                ui.label(RichText::new(Player::Black.to_string()).font(FontId::proportional(14.0)));
                 
                 ui.add_space(20.0);
                 
-                ui.label(RichText::new("○").color(Color32::from_rgb(80, 80, 80)).font(FontId::proportional(20.0)));
-                ui.label(RichText::new("白方").font(FontId::proportional(14.0)));
+                ui.label(RichText::new("○").color(self.theme.white_label).font(FontId::proportional(20.0)));
+                ui.label(RichText::new(Player::White.to_string()).font(FontId::proportional(14.0)));
                 
                 ui.add_space(20.0);
                 
-                ui.label(RichText::new("⛁").color(Color32::GOLD).font(FontId::proportional(20.0)));
-                ui.label(RichText::new("受保护棋子").font(FontId::proportional(14.0)));
+                ui.label(RichText::new("⛁").color(self.theme.protected).font(FontId::proportional(20.0)));
+                ui.label(RichText::new(tr("gui.protected")).font(FontId::proportional(14.0)));
             });
         });
         
@@ -0,0 +1,690 @@
+// 局域网联机对战：一方创建房间（主机），另一方加入（客户端）
+//
+// 主机持有权威棋盘并裁决所有动作，客户端只发送动作、接收局面。
+// 双方通过 TCP 以 JSON 行交换 NetMessage。每条局面消息都带有局面哈希，
+// 客户端发送动作时附上自己看到的局面哈希，哈希不一致时主机拒绝该动作并重新下发局面；
+// 断线重连时客户端同样带上最后的局面哈希，不一致才需要下发完整局面。
+
+use crate::i18n::{tr, trf};
+use crate::{Board, GameAction, Player};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// 协议版本，双方不一致时拒绝连接
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// 默认端口
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 监听线程检查是否需要退出的间隔
+const ACCEPT_POLL: Duration = Duration::from_millis(20);
+
+// 主机执黑，客户端执白
+const HOST_PLAYER: Player = Player::Black;
+
+/// 完整局面：局面字符串 + 棋谱
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardState {
+    /// 局面字符串，见 Board::to_position_string
+    pub position: String,
+    /// 完整棋谱
+    pub record: Vec<GameAction>,
+    /// 局面哈希，用于校验
+    pub hash: u64,
+}
+
+impl BoardState {
+    /// 记录棋盘的当前局面
+    pub fn from_board(board: &Board) -> Self {
+        BoardState {
+            position: board.to_position_string(),
+            record: board.game_record.clone(),
+            hash: board.position_hash(),
+        }
+    }
+
+    /// 还原棋盘并校验哈希
+    pub fn to_board(&self) -> Result<Board, &'static str> {
+        let mut board = Board::from_position_string(&self.position)?;
+        if board.position_hash() != self.hash {
+            return Err(tr("net.hash_mismatch"));
+        }
+        board.game_record = self.record.clone();
+        Ok(board)
+    }
+}
+
+/// 双方交换的消息，每条消息序列化为一行 JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NetMessage {
+    /// 客户端 -> 主机：请求加入，重连时带上最后的局面哈希
+    Hello {
+        /// 协议版本
+        version: u32,
+        /// 客户端最后看到的局面哈希，首次连接时为 None
+        last_hash: Option<u64>,
+    },
+    /// 主机 -> 客户端：分配执子方，局面不一致时附带完整局面
+    Welcome {
+        /// 分配给客户端的执子方
+        player: Player,
+        /// 客户端局面与主机不一致时的完整局面
+        state: Option<BoardState>,
+    },
+    /// 客户端 -> 主机：执行动作，hash 为客户端看到的局面
+    Action {
+        /// 要执行的动作
+        action: GameAction,
+        /// 客户端执行动作前的局面哈希
+        hash: u64,
+    },
+    /// 主机 -> 客户端：局面更新，last 为导致更新的动作
+    State {
+        /// 主机的当前局面
+        state: BoardState,
+        /// 导致局面更新的动作，同步时为 None
+        last: Option<GameAction>,
+    },
+    /// 客户端 -> 主机：请求重新下发局面
+    SyncRequest,
+    /// 主机 -> 客户端：动作被拒绝或连接被拒绝
+    Rejected {
+        /// 拒绝的原因
+        reason: String,
+    },
+    /// 任一方认输
+    Resign {
+        /// 认输的一方
+        player: Player,
+    },
+}
+
+/// 联机事件，由 NetSession::poll 返回给界面
+#[derive(Debug, Clone)]
+pub enum NetEvent {
+    /// 连接建立，参数为本方执子
+    Connected(Player),
+    /// 与对方的连接已断开
+    Disconnected,
+    /// 局面已更新，参数为导致更新的动作（同步时为 None）
+    Updated(Option<GameAction>),
+    /// 动作或连接被拒绝，参数为原因
+    Rejected(String),
+    /// 对方认输，参数为认输的一方
+    Resigned(Player),
+}
+
+/// 本方在联机对局中的角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// 创建房间的一方，持有权威棋盘
+    Host,
+    /// 加入房间的一方
+    Client,
+}
+
+enum Incoming {
+    Connected(u64, TcpStream),
+    Message(u64, NetMessage),
+    Disconnected(u64),
+}
+
+/// 一局联机对战的连接，由界面每帧调用 poll 处理收到的消息
+pub struct NetSession {
+    role: Role,
+    local_player: Player,
+    local_addr: Option<SocketAddr>,
+    remote_addr: Option<SocketAddr>,
+    incoming: Receiver<Incoming>,
+    sender: Sender<Incoming>,
+    peer: Option<(u64, TcpStream)>,
+    handshake_done: bool,
+    next_id: u64,
+    // 主机的监听线程和它的退出标志，会话销毁时停止监听并释放端口
+    acceptor: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl NetSession {
+    /// 创建房间，在 addr 上监听（端口 0 表示由系统分配）
+    pub fn host(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        // 非阻塞监听，以便定期检查退出标志
+        listener.set_nonblocking(true)?;
+        let (sender, incoming) = mpsc::channel();
+
+        let tx = sender.clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = thread::spawn(move || {
+            // 每个连接一个编号，用于区分已被替换或拒绝的连接
+            let mut id = 0;
+            while !stopped.load(Ordering::Relaxed) {
+                let stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_POLL);
+                        continue;
+                    }
+                    Err(_) => continue,
+                };
+                id += 1;
+                stream.set_nonblocking(false).ok();
+                stream.set_nodelay(true).ok();
+                let Ok(writer) = stream.try_clone() else {
+                    continue;
+                };
+                if tx.send(Incoming::Connected(id, writer)).is_err() {
+                    break;
+                }
+                spawn_reader(id, stream, tx.clone());
+            }
+        });
+
+        Ok(NetSession {
+            role: Role::Host,
+            local_player: HOST_PLAYER,
+            local_addr: Some(local_addr),
+            remote_addr: None,
+            incoming,
+            sender,
+            peer: None,
+            handshake_done: false,
+            next_id: 0,
+            acceptor: Some((stop, handle)),
+        })
+    }
+
+    /// 加入房间
+    pub fn join(addr: &str) -> io::Result<Self> {
+        let remote = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr("net.bad_address")))?;
+        let (sender, incoming) = mpsc::channel();
+        let mut session = NetSession {
+            role: Role::Client,
+            local_player: HOST_PLAYER.opponent(),
+            local_addr: None,
+            remote_addr: Some(remote),
+            incoming,
+            sender,
+            peer: None,
+            handshake_done: false,
+            next_id: 0,
+            acceptor: None,
+        };
+        session.connect(None)?;
+        Ok(session)
+    }
+
+    /// 断线后重新连接主机，带上当前局面哈希以便主机判断是否需要重新下发局面
+    pub fn reconnect(&mut self, board: &Board) -> io::Result<()> {
+        if self.role != Role::Client {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, tr("net.reconnect_client_only")));
+        }
+        self.disconnect();
+        self.connect(Some(board.position_hash()))
+    }
+
+    fn connect(&mut self, last_hash: Option<u64>) -> io::Result<()> {
+        let remote = self
+            .remote_addr
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, tr("net.no_host_address")))?;
+        let stream = TcpStream::connect_timeout(&remote, CONNECT_TIMEOUT)?;
+        stream.set_nodelay(true)?;
+        self.local_addr = stream.local_addr().ok();
+
+        // 每次连接使用新的编号，旧连接的消息会被忽略
+        self.next_id += 1;
+        let id = self.next_id;
+        spawn_reader(id, stream.try_clone()?, self.sender.clone());
+        self.peer = Some((id, stream));
+        self.handshake_done = false;
+        self.send(&NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            last_hash,
+        });
+        Ok(())
+    }
+
+    /// 本方角色
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// 本方执子
+    pub fn local_player(&self) -> Player {
+        self.local_player
+    }
+
+    /// 本地监听（主机）或连接（客户端）的地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// 是否已与对方完成握手
+    pub fn is_connected(&self) -> bool {
+        self.peer.is_some() && self.handshake_done
+    }
+
+    /// 断开与对方的连接
+    pub fn disconnect(&mut self) {
+        if let Some((_, stream)) = self.peer.take() {
+            stream.shutdown(Shutdown::Both).ok();
+        }
+        self.handshake_done = false;
+    }
+
+    fn send(&mut self, message: &NetMessage) {
+        let Some((_, stream)) = self.peer.as_mut() else {
+            return;
+        };
+        if write_message(stream, message).is_err() {
+            // 写入失败视为断线，关闭连接后读线程会报告 Disconnected
+            stream.shutdown(Shutdown::Both).ok();
+        }
+    }
+
+    /// 处理收到的网络消息，主机在此裁决对方的动作
+    pub fn poll(&mut self, board: &mut Board) -> Vec<NetEvent> {
+        let mut events = Vec::new();
+
+        while let Ok(incoming) = self.incoming.try_recv() {
+            match incoming {
+                Incoming::Connected(id, mut stream) => {
+                    if self.peer.is_some() {
+                        write_message(
+                            &mut stream,
+                            &NetMessage::Rejected {
+                                reason: tr("net.room_full").to_string(),
+                            },
+                        )
+                        .ok();
+                        stream.shutdown(Shutdown::Both).ok();
+                    } else {
+                        self.peer = Some((id, stream));
+                        self.handshake_done = false;
+                    }
+                }
+                Incoming::Disconnected(id) => {
+                    if self.peer.as_ref().is_some_and(|(peer, _)| *peer == id) {
+                        self.disconnect();
+                        events.push(NetEvent::Disconnected);
+                    }
+                }
+                Incoming::Message(id, message) => {
+                    if self.peer.as_ref().is_some_and(|(peer, _)| *peer == id) {
+                        self.handle_message(message, board, &mut events);
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    fn handle_message(&mut self, message: NetMessage, board: &mut Board, events: &mut Vec<NetEvent>) {
+        match (self.role, message) {
+            (Role::Host, NetMessage::Hello { version, last_hash }) => {
+                if version != PROTOCOL_VERSION {
+                    self.send(&NetMessage::Rejected {
+                        reason: trf("net.version_mismatch", &[&PROTOCOL_VERSION, &version]),
+                    });
+                    self.disconnect();
+                    return;
+                }
+                let state = (last_hash != Some(board.position_hash()))
+                    .then(|| BoardState::from_board(board));
+                self.send(&NetMessage::Welcome {
+                    player: self.local_player.opponent(),
+                    state,
+                });
+                self.handshake_done = true;
+                events.push(NetEvent::Connected(self.local_player));
+            }
+            (Role::Host, NetMessage::Action { action, hash }) => {
+                if hash != board.position_hash() {
+                    self.send(&NetMessage::Rejected {
+                        reason: tr("net.resynced").to_string(),
+                    });
+                    self.send_state(board, None);
+                    return;
+                }
+                if let Err(e) = self.check_remote_action(board, &action) {
+                    self.send(&NetMessage::Rejected {
+                        reason: e.to_string(),
+                    });
+                    return;
+                }
+                // 合法动作执行失败时局面仍已改变（判负），同样需要下发
+                if let Err(e) = board.apply_action(&action) {
+                    self.send(&NetMessage::Rejected {
+                        reason: e.to_string(),
+                    });
+                    events.push(NetEvent::Rejected(e.to_string()));
+                }
+                self.send_state(board, Some(action.clone()));
+                events.push(NetEvent::Updated(Some(action)));
+            }
+            (Role::Host, NetMessage::SyncRequest) => self.send_state(board, None),
+            (Role::Client, NetMessage::Welcome { player, state }) => {
+                self.local_player = player;
+                self.handshake_done = true;
+                events.push(NetEvent::Connected(player));
+                if let Some(state) = state {
+                    self.apply_state(&state, board, None, events);
+                }
+            }
+            (Role::Client, NetMessage::State { state, last }) => {
+                self.apply_state(&state, board, last, events);
+            }
+            (Role::Client, NetMessage::Rejected { reason }) => {
+                events.push(NetEvent::Rejected(reason));
+            }
+            // 只接受对方替自己认输，并记入棋谱
+            (_, NetMessage::Resign { player })
+                if player == self.local_player.opponent() && board.check_winner().is_none() =>
+            {
+                board.record_resign(player);
+                events.push(NetEvent::Resigned(player));
+            }
+            _ => {}
+        }
+    }
+
+    fn check_remote_action(&self, board: &Board, action: &GameAction) -> Result<(), &'static str> {
+        let remote = self.local_player.opponent();
+        if board.current_player != remote {
+            return Err(tr("net.not_your_turn"));
+        }
+        if !board.legal_actions().contains(action) {
+            return Err(tr("net.illegal_action"));
+        }
+        Ok(())
+    }
+
+    fn apply_state(
+        &mut self,
+        state: &BoardState,
+        board: &mut Board,
+        last: Option<GameAction>,
+        events: &mut Vec<NetEvent>,
+    ) {
+        match state.to_board() {
+            Ok(b) => {
+                // 能在本地重现主机执行的动作时沿用本地的结果，界面可以取到动作产生的事件
+                let replayed = last.as_ref().and_then(|action| {
+                    let mut next = board.clone();
+                    let _ = next.apply_action(action);
+                    (next.position_hash() == b.position_hash() && next.game_record == b.game_record).then_some(next)
+                });
+                *board = replayed.unwrap_or(b);
+                events.push(NetEvent::Updated(last));
+            }
+            Err(_) => self.send(&NetMessage::SyncRequest),
+        }
+    }
+
+    /// 主机向客户端下发当前局面
+    pub fn send_state(&mut self, board: &Board, last: Option<GameAction>) {
+        if self.role == Role::Host && self.is_connected() {
+            self.send(&NetMessage::State {
+                state: BoardState::from_board(board),
+                last,
+            });
+        }
+    }
+
+    /// 提交本方动作：主机直接执行并下发局面，客户端发送给主机等待裁决
+    pub fn submit(&mut self, board: &mut Board, action: &GameAction) -> Result<u32, String> {
+        if !self.is_connected() {
+            return Err(tr("net.no_opponent").to_string());
+        }
+        if board.current_player != self.local_player {
+            return Err(tr("net.not_your_turn").to_string());
+        }
+
+        match self.role {
+            Role::Host => {
+                let before = board.game_record.len();
+                let result = board.apply_action(action).map_err(String::from);
+                if board.game_record.len() != before {
+                    self.send_state(board, Some(action.clone()));
+                }
+                result
+            }
+            Role::Client => {
+                self.send(&NetMessage::Action {
+                    action: action.clone(),
+                    hash: board.position_hash(),
+                });
+                Ok(0)
+            }
+        }
+    }
+
+    /// 通知对方本方认输
+    pub fn resign(&mut self) {
+        self.send(&NetMessage::Resign {
+            player: self.local_player,
+        });
+    }
+}
+
+impl Drop for NetSession {
+    fn drop(&mut self) {
+        self.disconnect();
+        if let Some((stop, handle)) = self.acceptor.take() {
+            stop.store(true, Ordering::Relaxed);
+            handle.join().ok();
+        }
+    }
+}
+
+fn write_message(stream: &mut TcpStream, message: &NetMessage) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    stream.flush()
+}
+
+// 读线程：逐行解析消息，连接关闭或出错时报告 Disconnected
+fn spawn_reader(id: u64, stream: TcpStream, tx: Sender<Incoming>) {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            let Ok(message) = serde_json::from_str::<NetMessage>(&line) else {
+                continue;
+            };
+            if tx.send(Incoming::Message(id, message)).is_err() {
+                return;
+            }
+        }
+        tx.send(Incoming::Disconnected(id)).ok();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    // 交替轮询双方，直到 done 对收到的事件返回 true，超时则测试失败
+    fn pump(
+        host: &mut NetSession,
+        host_board: &mut Board,
+        client: &mut NetSession,
+        client_board: &mut Board,
+        mut done: impl FnMut(&[NetEvent], &[NetEvent]) -> bool,
+    ) -> (Vec<NetEvent>, Vec<NetEvent>) {
+        let (mut host_events, mut client_events) = (Vec::new(), Vec::new());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done(&host_events, &client_events) {
+            assert!(Instant::now() < deadline, "timed out: host {host_events:?}, client {client_events:?}");
+            host_events.extend(host.poll(host_board));
+            client_events.extend(client.poll(client_board));
+            thread::sleep(Duration::from_millis(5));
+        }
+        (host_events, client_events)
+    }
+
+    fn has(events: &[NetEvent], pred: impl Fn(&NetEvent) -> bool) -> bool {
+        events.iter().any(pred)
+    }
+
+    fn connected(events: &[NetEvent]) -> bool {
+        has(events, |e| matches!(e, NetEvent::Connected(_)))
+    }
+
+    fn same_position(a: &Board, b: &Board) -> bool {
+        a.position_hash() == b.position_hash() && a.game_record == b.game_record
+    }
+
+    // 建立一对已握手的连接
+    fn pair() -> (NetSession, Board, NetSession, Board) {
+        let mut host = NetSession::host("127.0.0.1:0").unwrap();
+        let addr = host.local_addr().unwrap().to_string();
+        let mut client = NetSession::join(&addr).unwrap();
+        let (mut host_board, mut client_board) = (Board::new(), Board::new());
+        pump(&mut host, &mut host_board, &mut client, &mut client_board, |h, c| connected(h) && connected(c));
+        assert!(host.is_connected() && client.is_connected());
+        assert_eq!(client.local_player(), HOST_PLAYER.opponent());
+        (host, host_board, client, client_board)
+    }
+
+    #[test]
+    fn actions_round_trip() {
+        let (mut host, mut host_board, mut client, mut client_board) = pair();
+
+        let action = host_board.legal_actions()[0].clone();
+        host.submit(&mut host_board, &action).unwrap();
+        let (_, events) = pump(&mut host, &mut host_board, &mut client, &mut client_board, |_, c| {
+            has(c, |e| matches!(e, NetEvent::Updated(Some(_))))
+        });
+        assert!(matches!(&events[..], [NetEvent::Updated(Some(a))] if *a == action));
+        assert!(same_position(&host_board, &client_board));
+
+        // 客户端的动作由主机裁决后再下发
+        let reply = client_board.legal_actions()[0].clone();
+        client.submit(&mut client_board, &reply).unwrap();
+        assert_eq!(client_board.game_record.len(), 1);
+        let (host_events, _) = pump(&mut host, &mut host_board, &mut client, &mut client_board, |h, c| {
+            has(h, |e| matches!(e, NetEvent::Updated(Some(_)))) && has(c, |e| matches!(e, NetEvent::Updated(Some(_))))
+        });
+        assert!(matches!(&host_events[..], [NetEvent::Updated(Some(a))] if *a == reply));
+        assert_eq!(host_board.game_record, vec![action, reply]);
+        assert!(same_position(&host_board, &client_board));
+    }
+
+    #[test]
+    fn action_on_stale_position_is_rejected_and_resynced() {
+        let (mut host, mut host_board, mut client, mut client_board) = pair();
+
+        // 主机的局面在客户端不知情时发生了变化
+        let action = host_board.legal_actions()[0].clone();
+        host_board.apply_action(&action).unwrap();
+        let reply = host_board.legal_actions()[0].clone();
+        client.send(&NetMessage::Action {
+            action: reply,
+            hash: client_board.position_hash(),
+        });
+
+        let (host_events, client_events) = pump(&mut host, &mut host_board, &mut client, &mut client_board, |_, c| {
+            has(c, |e| matches!(e, NetEvent::Updated(None)))
+        });
+        assert!(host_events.is_empty());
+        assert!(matches!(&client_events[0], NetEvent::Rejected(reason) if reason == tr("net.resynced")));
+        assert_eq!(host_board.game_record, vec![action]);
+        assert!(same_position(&host_board, &client_board));
+    }
+
+    #[test]
+    fn reconnect_sends_position_only_when_hash_differs() {
+        let (mut host, mut host_board, mut client, mut client_board) = pair();
+        let action = host_board.legal_actions()[0].clone();
+        host.submit(&mut host_board, &action).unwrap();
+        pump(&mut host, &mut host_board, &mut client, &mut client_board, |_, c| {
+            has(c, |e| matches!(e, NetEvent::Updated(_)))
+        });
+
+        // 局面一致：重连后只握手，不下发局面
+        client.disconnect();
+        pump(&mut host, &mut host_board, &mut client, &mut client_board, |h, _| {
+            has(h, |e| matches!(e, NetEvent::Disconnected))
+        });
+        client.reconnect(&client_board).unwrap();
+        let (_, events) = pump(&mut host, &mut host_board, &mut client, &mut client_board, |h, c| {
+            connected(h) && connected(c)
+        });
+        assert!(!has(&events, |e| matches!(e, NetEvent::Updated(_))));
+
+        // 局面不一致：重连时主机下发完整局面
+        client.disconnect();
+        pump(&mut host, &mut host_board, &mut client, &mut client_board, |h, _| {
+            has(h, |e| matches!(e, NetEvent::Disconnected))
+        });
+        client_board = Board::new();
+        client.reconnect(&client_board).unwrap();
+        pump(&mut host, &mut host_board, &mut client, &mut client_board, |_, c| {
+            connected(c) && has(c, |e| matches!(e, NetEvent::Updated(None)))
+        });
+        assert!(same_position(&host_board, &client_board));
+    }
+
+    #[test]
+    fn second_client_is_rejected_when_room_is_full() {
+        let (mut host, mut host_board, mut client, mut client_board) = pair();
+        let addr = host.local_addr().unwrap().to_string();
+        let mut other = NetSession::join(&addr).unwrap();
+        let mut other_board = Board::new();
+
+        let (host_events, events) = pump(&mut host, &mut host_board, &mut other, &mut other_board, |_, o| {
+            has(o, |e| matches!(e, NetEvent::Rejected(_)))
+        });
+        assert!(host_events.is_empty());
+        assert!(matches!(&events[0], NetEvent::Rejected(reason) if reason == tr("net.room_full")));
+        assert!(!other.is_connected());
+
+        // 原来的连接不受影响
+        assert!(client.poll(&mut client_board).is_empty());
+        assert!(host.is_connected() && client.is_connected());
+    }
+
+    #[test]
+    fn port_is_released_when_the_host_is_dropped() {
+        let (host, _, client, _) = pair();
+        let addr = host.local_addr().unwrap().to_string();
+        drop(client);
+        drop(host);
+
+        // 在同一地址上重新创建房间，新房间可以正常加入
+        let mut host = NetSession::host(&addr).unwrap();
+        let mut client = NetSession::join(&addr).unwrap();
+        let (mut host_board, mut client_board) = (Board::new(), Board::new());
+        pump(&mut host, &mut host_board, &mut client, &mut client_board, |h, c| connected(h) && connected(c));
+    }
+
+    #[test]
+    fn resignation_is_recorded_only_for_the_sender() {
+        let (mut host, mut host_board, mut client, mut client_board) = pair();
+
+        // 客户端冒充主机认输，主机忽略
+        client.send(&NetMessage::Resign { player: HOST_PLAYER });
+        client.resign();
+        let (host_events, _) = pump(&mut host, &mut host_board, &mut client, &mut client_board, |h, _| {
+            has(h, |e| matches!(e, NetEvent::Resigned(_)))
+        });
+        assert!(matches!(&host_events[..], [NetEvent::Resigned(p)] if *p == HOST_PLAYER.opponent()));
+        assert_eq!(host_board.game_record, vec![GameAction::Resign { player: HOST_PLAYER.opponent() }]);
+        assert_eq!(host_board.check_winner(), Some(HOST_PLAYER));
+    }
+}
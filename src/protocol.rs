@@ -0,0 +1,580 @@
+// 五道方文本引擎协议（参考 UCI），每行一条命令，经由标准输入/输出传输
+//
+// 界面/对战平台 -> 引擎：
+//   isready                          引擎回复 readyok
+//   newgame                          开始新对局
+//   rules <名称>                     设置规则，目前只支持 standard
+//   position startpos|<局面> [moves <动作>...]
+//                                    设置局面，局面字符串见 Board::to_position_string
+//   go [movetime <毫秒>] [depth <层数>]
+//                                    开始搜索
+//   stop                             立即停止搜索并给出结果
+//   quit                             退出
+//
+// 引擎 -> 界面/对战平台：
+//   readyok
+//   info depth <层数> score <分数> nodes <节点数> time <毫秒> pv <动作>...
+//   info string <任意文本>
+//   bestaction <动作>|none
+//
+// 加载了开局库时，落子阶段的局面若在开局库中有足够多的对局，go 直接回复开局库着法（先输出 info string book）。
+//
+// 动作记号见 GameAction::notation：落子 "2,3"，吃棋 "x2,3"，移动 "2,3-2,4"
+
+use crate::ai::{self, SearchLimits};
+use crate::book::Book;
+use crate::i18n::{tr, trf};
+use crate::{Board, GameAction, Player};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 当前支持的规则名称
+pub const RULES_STANDARD: &str = "standard";
+
+// 未指定 movetime 和 depth 时的默认思考时间
+const DEFAULT_MOVETIME: Duration = Duration::from_millis(1000);
+
+// ===== 引擎端 =====
+
+struct RunningSearch {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl RunningSearch {
+    fn finish(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().ok();
+    }
+}
+
+/// 运行引擎主循环，直到收到 quit 或输入结束
+pub fn run_engine<R: BufRead, W: Write + Send + 'static>(input: R, output: W, book: Option<Book>) {
+    let output = Arc::new(Mutex::new(output));
+    let mut board = Board::new();
+    let mut search: Option<RunningSearch> = None;
+
+    let send = |line: &str| {
+        let mut out = output.lock().unwrap();
+        writeln!(out, "{}", line).ok();
+        out.flush().ok();
+    };
+
+    for line in input.lines() {
+        let Ok(line) = line else { break };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((&command, args)) = tokens.split_first() else {
+            continue;
+        };
+
+        match command {
+            "isready" => send("readyok"),
+            "newgame" => {
+                if let Some(s) = search.take() {
+                    s.finish();
+                }
+                board = Board::new();
+            }
+            "rules" => {
+                let name = args.first().copied().unwrap_or(RULES_STANDARD);
+                if name != RULES_STANDARD {
+                    send(&format!("info string {}", trf("engine.unsupported_rules", &[&name])));
+                }
+            }
+            "position" => match parse_position(args) {
+                Ok(b) => board = b,
+                Err(e) => send(&format!("info string {}", trf("engine.error", &[&e]))),
+            },
+            "go" => {
+                if let Some(s) = search.take() {
+                    s.finish();
+                }
+                if let Some(action) = book.as_ref().and_then(|b| b.best_move(&board)) {
+                    send("info string book");
+                    send(&format!("bestaction {}", action.notation()));
+                    continue;
+                }
+                let limits = parse_go(args);
+                let stop = Arc::new(AtomicBool::new(false));
+                let root = board.clone();
+                let out = Arc::clone(&output);
+                let flag = Arc::clone(&stop);
+                let handle = thread::spawn(move || {
+                    let result = ai::search(&root, limits, &flag, |info| {
+                        let mut out = out.lock().unwrap();
+                        writeln!(out, "{}", format_info(info)).ok();
+                        out.flush().ok();
+                    });
+                    let best = result
+                        .best
+                        .map_or_else(|| "none".to_string(), |a| a.notation());
+                    let mut out = out.lock().unwrap();
+                    writeln!(out, "bestaction {}", best).ok();
+                    out.flush().ok();
+                });
+                search = Some(RunningSearch { stop, handle });
+            }
+            "stop" => {
+                if let Some(s) = search.take() {
+                    s.finish();
+                }
+            }
+            "quit" => break,
+            _ => send(&format!("info string {}", trf("engine.unknown_command", &[&command]))),
+        }
+    }
+
+    if let Some(s) = search.take() {
+        s.finish();
+    }
+}
+
+/// 解析 position 命令的参数
+pub fn parse_position(args: &[&str]) -> Result<Board, &'static str> {
+    let (&start, rest) = args.split_first().ok_or(tr("engine.missing_position"))?;
+    let mut board = if start == "startpos" {
+        Board::new()
+    } else {
+        Board::from_position_string(start)?
+    };
+
+    if let Some((&keyword, moves)) = rest.split_first() {
+        if keyword != "moves" {
+            return Err(tr("engine.expected_moves"));
+        }
+        for m in moves {
+            let action = board.parse_action(m)?;
+            board.apply_action(&action)?;
+        }
+    }
+
+    Ok(board)
+}
+
+fn parse_go(args: &[&str]) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+    let mut iter = args.iter();
+    while let Some(&key) = iter.next() {
+        let value = iter.next().and_then(|v| v.parse::<u64>().ok());
+        match (key, value) {
+            ("movetime", Some(ms)) => limits.movetime = Some(Duration::from_millis(ms)),
+            ("depth", Some(d)) => limits.depth = Some(d as u32),
+            _ => {}
+        }
+    }
+    if limits.movetime.is_none() && limits.depth.is_none() {
+        limits.movetime = Some(DEFAULT_MOVETIME);
+    }
+    limits
+}
+
+fn format_info(info: &ai::SearchInfo) -> String {
+    let pv: Vec<String> = info.pv.iter().map(|a| a.notation()).collect();
+    format!(
+        "info depth {} score {} nodes {} time {} pv {}",
+        info.depth,
+        info.score,
+        info.nodes,
+        info.elapsed.as_millis(),
+        pv.join(" ")
+    )
+}
+
+// ===== 界面/对战平台端：驱动外部引擎进程 =====
+
+/// 外部引擎进程，通过标准输入输出以文本协议通信
+pub struct ExternalEngine {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    stale: usize, // 超时的搜索中尚未收到的 bestaction 数
+}
+
+impl ExternalEngine {
+    /// 启动引擎进程并等待其就绪
+    pub fn spawn(command: &str, args: &[String]) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| trf("engine.spawn_failed", &[&command, &e]))?;
+
+        let stdin = child.stdin.take().ok_or(tr("engine.no_stdin"))?;
+        let stdout = child.stdout.take().ok_or(tr("engine.no_stdout"))?;
+
+        // 单独的线程读取引擎输出，便于等待时设置超时
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = ExternalEngine {
+            name: command.to_string(),
+            child,
+            stdin,
+            lines,
+            stale: 0,
+        };
+        engine.ready()?;
+        Ok(engine)
+    }
+
+    /// 按命令行字符串启动引擎，例如 "wudaofang engine"
+    pub fn spawn_command_line(command_line: &str) -> Result<Self, String> {
+        let mut parts = command_line.split_whitespace();
+        let command = parts.next().ok_or(tr("engine.empty_command"))?;
+        let args: Vec<String> = parts.map(str::to_string).collect();
+        Self::spawn(command, &args)
+    }
+
+    /// 引擎名称（启动命令）
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{}", line)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| trf("engine.io_failed", &[&self.name, &e]))
+    }
+
+    // 读取一行输出，超时或引擎退出时返回错误
+    fn recv(&mut self, deadline: Instant) -> Result<String, String> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(trf("engine.timeout", &[&self.name])),
+            Err(RecvTimeoutError::Disconnected) => Err(trf("engine.exited", &[&self.name])),
+        }
+    }
+
+    /// 等待引擎回复 readyok
+    pub fn ready(&mut self) -> Result<(), String> {
+        self.send("isready")?;
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let line = self.recv(deadline)?;
+            self.skip_stale(&line);
+            if line.trim() == "readyok" {
+                return Ok(());
+            }
+        }
+    }
+
+    // 超时搜索迟到的 bestaction：计入已丢弃的回复并返回 true
+    fn skip_stale(&mut self, line: &str) -> bool {
+        if self.stale > 0 && line.trim().starts_with("bestaction") {
+            self.stale -= 1;
+            return true;
+        }
+        false
+    }
+
+    /// 开始新对局并设置规则
+    pub fn new_game(&mut self) -> Result<(), String> {
+        self.send("newgame")?;
+        self.send(&format!("rules {}", RULES_STANDARD))?;
+        self.ready()
+    }
+
+    /// 设置要搜索的局面
+    pub fn set_position(&mut self, board: &Board) -> Result<(), String> {
+        self.send(&format!("position {}", board.to_position_string()))
+    }
+
+    /// 开始搜索并等待 bestaction，引擎认为无棋可走时返回 None
+    ///
+    /// 超时的搜索会让引擎停下，它迟到的 bestaction 在之后的搜索中丢弃，不会被当成新搜索的结果
+    pub fn go(&mut self, movetime: Duration) -> Result<Option<String>, String> {
+        while let Ok(line) = self.lines.try_recv() {
+            self.skip_stale(&line);
+        }
+        self.send(&format!("go movetime {}", movetime.as_millis()))?;
+
+        // 给引擎留出额外的响应时间
+        let deadline = Instant::now() + movetime + Duration::from_secs(5);
+        loop {
+            let line = match self.recv(deadline) {
+                Ok(line) => line,
+                Err(e) => {
+                    self.send("stop").ok();
+                    self.stale += 1;
+                    return Err(e);
+                }
+            };
+            if self.skip_stale(&line) {
+                continue;
+            }
+            if let Some(best) = line.trim().strip_prefix("bestaction") {
+                let best = best.trim();
+                return Ok((best != "none" && !best.is_empty()).then(|| best.to_string()));
+            }
+        }
+    }
+
+    /// 让引擎在给定局面上选出一个动作，并检查其合法性
+    pub fn best_action(&mut self, board: &Board, movetime: Duration) -> Result<GameAction, String> {
+        self.set_position(board)?;
+        let best = self
+            .go(movetime)?
+            .ok_or_else(|| trf("engine.no_action", &[&self.name]))?;
+        let action = board
+            .parse_action(&best)
+            .map_err(|e| trf("engine.invalid_action", &[&self.name, &best, &e]))?;
+        if !board.legal_actions().contains(&action) {
+            return Err(trf("engine.illegal_action", &[&self.name, &best]));
+        }
+        Ok(action)
+    }
+}
+
+impl Drop for ExternalEngine {
+    fn drop(&mut self) {
+        self.send("quit").ok();
+        thread::sleep(Duration::from_millis(50));
+        if let Ok(None) = self.child.try_wait() {
+            self.child.kill().ok();
+        }
+        self.child.wait().ok();
+    }
+}
+
+/// 在后台线程中运行外部引擎，供界面在不阻塞的情况下请求动作
+pub struct EngineWorker {
+    requests: Sender<(u64, Board, Duration)>,
+    replies: Receiver<(u64, Result<GameAction, String>)>,
+    generation: u64,
+    pending: bool,
+}
+
+impl EngineWorker {
+    /// 在后台线程中开始新对局，之后由 request 提交局面
+    pub fn spawn(mut engine: ExternalEngine) -> Self {
+        let (requests, request_rx) = mpsc::channel::<(u64, Board, Duration)>();
+        let (reply_tx, replies) = mpsc::channel();
+        thread::spawn(move || {
+            if let Err(e) = engine.new_game() {
+                reply_tx.send((0, Err(e))).ok();
+                return;
+            }
+            for (generation, board, movetime) in request_rx {
+                let reply = engine.best_action(&board, movetime);
+                if reply_tx.send((generation, reply)).is_err() {
+                    break;
+                }
+            }
+        });
+        EngineWorker {
+            requests,
+            replies,
+            generation: 0,
+            pending: false,
+        }
+    }
+
+    /// 是否有尚未返回的请求
+    pub fn is_thinking(&self) -> bool {
+        self.pending
+    }
+
+    /// 请求引擎为 board 搜索 movetime 时间，结果由 try_recv 取得
+    pub fn request(&mut self, board: &Board, movetime: Duration) {
+        if self.requests.send((self.generation, board.clone(), movetime)).is_ok() {
+            self.pending = true;
+        }
+    }
+
+    /// 丢弃正在进行的请求（例如开始新对局时）
+    pub fn cancel(&mut self) {
+        self.generation += 1;
+        self.pending = false;
+    }
+
+    /// 取得最近一次请求的结果，尚未完成时返回 None
+    pub fn try_recv(&mut self) -> Option<Result<GameAction, String>> {
+        while let Ok((generation, reply)) = self.replies.try_recv() {
+            if generation == self.generation {
+                self.pending = false;
+                return Some(reply);
+            }
+        }
+        None
+    }
+}
+
+// ===== 对战平台：两个外部引擎对局 =====
+
+/// 对局结果
+#[derive(Debug, Clone)]
+pub struct MatchResult {
+    /// 胜方，和棋时为 None
+    pub winner: Option<Player>,
+    /// 对局棋谱
+    pub record: Vec<GameAction>,
+    /// 对局结束的原因
+    pub reason: String,
+}
+
+/// 让两个引擎下一局，超过 max_actions 步判和
+pub fn play_match(
+    black: &mut ExternalEngine,
+    white: &mut ExternalEngine,
+    movetime: Duration,
+    max_actions: usize,
+) -> Result<MatchResult, String> {
+    black.new_game()?;
+    white.new_game()?;
+
+    let mut board = Board::new();
+    let mut actions = 0;
+
+    loop {
+        if let Some(winner) = board.check_winner() {
+            return Ok(MatchResult {
+                winner: Some(winner),
+                record: board.game_record.clone(),
+                reason: tr("result.pieces_or_blocked").to_string(),
+            });
+        }
+        if actions >= max_actions {
+            return Ok(MatchResult {
+                winner: None,
+                record: board.game_record.clone(),
+                reason: trf("arena.too_long", &[&max_actions]),
+            });
+        }
+        if board.legal_actions().is_empty() {
+            return Ok(MatchResult {
+                winner: None,
+                record: board.game_record.clone(),
+                reason: tr("arena.no_legal_actions").to_string(),
+            });
+        }
+
+        let player = board.current_player;
+        let engine = match player {
+            Player::Black => &mut *black,
+            Player::White => &mut *white,
+        };
+        let action = match engine.best_action(&board, movetime) {
+            Ok(a) => a,
+            Err(e) => {
+                return Ok(MatchResult {
+                    winner: Some(player.opponent()),
+                    record: board.game_record.clone(),
+                    reason: e,
+                });
+            }
+        };
+
+        if let Err(e) = board.apply_action(&action) {
+            // 合法动作执行失败说明该动作导致对方无法走棋，行动方判负
+            return Ok(MatchResult {
+                winner: Some(player.opponent()),
+                record: board.game_record.clone(),
+                reason: e.to_string(),
+            });
+        }
+        actions += 1;
+    }
+}
+
+/// 命令行入口：`wudaofang arena <引擎A> <引擎B> [局数] [每步毫秒]`
+/// 引擎命令带参数时用引号括起，例如 arena "wudaofang engine" ./my_bot
+/// 每局交换先后手，结果输出到标准输出
+pub fn run_arena(args: &[String]) -> Result<(), String> {
+    let [engine_a, engine_b, rest @ ..] = args else {
+        return Err(tr("arena.usage").to_string());
+    };
+    let games: usize = rest.first().and_then(|s| s.parse().ok()).unwrap_or(2);
+    let movetime = Duration::from_millis(rest.get(1).and_then(|s| s.parse().ok()).unwrap_or(500));
+
+    let mut a = ExternalEngine::spawn_command_line(engine_a)?;
+    let mut b = ExternalEngine::spawn_command_line(engine_b)?;
+    let (mut a_wins, mut b_wins, mut draws) = (0, 0, 0);
+
+    for game in 0..games {
+        let a_is_black = game % 2 == 0;
+        let result = if a_is_black {
+            play_match(&mut a, &mut b, movetime, 1000)?
+        } else {
+            play_match(&mut b, &mut a, movetime, 1000)?
+        };
+
+        let a_player = if a_is_black { Player::Black } else { Player::White };
+        let outcome = match result.winner {
+            Some(w) if w == a_player => {
+                a_wins += 1;
+                trf("arena.wins", &[&a.name()])
+            }
+            Some(_) => {
+                b_wins += 1;
+                trf("arena.wins", &[&b.name()])
+            }
+            None => {
+                draws += 1;
+                tr("arena.draw").to_string()
+            }
+        };
+        println!(
+            "{}",
+            trf("arena.game", &[&(game + 1), &outcome, &result.reason, &result.record.len()])
+        );
+    }
+
+    println!(
+        "{}",
+        trf("arena.total", &[&a.name(), &a_wins, &b.name(), &b_wins, &draws])
+    );
+    io::stdout().flush().ok();
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    // 第一次搜索不回复，收到 stop 后才送出迟到的结果；之后的搜索立即回复
+    const SLOW_ENGINE: &str = r#"
+        n=0
+        while read -r line; do
+            case "$line" in
+                isready) echo readyok ;;
+                go*) n=$((n + 1)); [ "$n" -gt 1 ] && echo "bestaction 1,1" ;;
+                stop) echo "bestaction 0,0" ;;
+                quit) exit 0 ;;
+            esac
+        done
+    "#;
+
+    #[test]
+    fn late_reply_from_timed_out_search_is_discarded() {
+        let args = vec!["-c".to_string(), SLOW_ENGINE.to_string()];
+        let mut engine = ExternalEngine::spawn("sh", &args).unwrap();
+        assert!(engine.go(Duration::ZERO).is_err());
+        assert_eq!(engine.go(Duration::ZERO).unwrap().as_deref(), Some("1,1"));
+    }
+
+    #[test]
+    fn late_reply_read_while_starting_a_new_game_is_discarded() {
+        let args = vec!["-c".to_string(), SLOW_ENGINE.to_string()];
+        let mut engine = ExternalEngine::spawn("sh", &args).unwrap();
+        assert!(engine.go(Duration::ZERO).is_err());
+        // 迟到的结果排在 readyok 之前，由 new_game 读走
+        engine.new_game().unwrap();
+        assert_eq!(engine.go(Duration::ZERO).unwrap().as_deref(), Some("1,1"));
+    }
+}
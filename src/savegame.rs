@@ -0,0 +1,126 @@
+// 存档文件
+//
+// 两种格式：
+// - 棋谱：Vec<GameAction> 的 JSON 数组，与命令行版本保存的 wudao_game_record.json 相同，
+//   打开时从开局重放到最后一步；
+// - 局面快照：局面字符串 + 棋谱，可以保存任意中途局面（包括不是从开局下出来的局面）。
+// 打开文件时按内容自动识别格式。
+
+use crate::i18n::{tr, trf};
+use crate::{Board, GameAction};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+// 快照格式版本
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// 存档类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveKind {
+    /// 棋谱：只保存动作序列，读取时从初始局面重放
+    Record,
+    /// 快照：保存局面字符串和棋谱，可以保存无法重放出的局面
+    Snapshot,
+}
+
+impl std::fmt::Display for SaveKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SaveKind::Record => write!(f, "{}", tr("save.record")),
+            SaveKind::Snapshot => write!(f, "{}", tr("save.snapshot")),
+        }
+    }
+}
+
+/// 快照存档的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// 快照格式版本
+    pub version: u32,
+    /// 局面字符串，见 Board::to_position_string
+    pub position: String,
+    /// 棋谱
+    pub record: Vec<GameAction>,
+}
+
+impl Snapshot {
+    /// 记录棋盘的当前局面和棋谱
+    pub fn from_board(board: &Board) -> Self {
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            position: board.to_position_string(),
+            record: board.game_record.clone(),
+        }
+    }
+
+    /// 还原棋盘，版本不符或局面无法解析时返回错误
+    pub fn to_board(&self) -> Result<Board, String> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(trf("save.unsupported_version", &[&self.version]));
+        }
+        let mut board = Board::from_position_string(&self.position)?;
+        board.game_record = self.record.clone();
+        // 快照的局面字符串不包含超时和认输信息，从棋谱中恢复
+        if let Some(GameAction::Timeout { player } | GameAction::Resign { player }) = self.record.last() {
+            board.forfeited = Some(*player);
+        }
+        Ok(board)
+    }
+}
+
+/// 从开局重放棋谱，返回最后的局面。棋谱中的奖励记录由重放重新生成，计时记录原样保留
+pub fn board_from_record(record: &[GameAction]) -> Result<Board, String> {
+    replay_record(Board::new(), record)
+}
+
+/// 在 board 之后接着重放棋谱，用于从快照局面继续；出错时的序号从 record 的第一条算起
+pub fn replay_record(mut board: Board, record: &[GameAction]) -> Result<Board, String> {
+    // 重放过程中的事件没有意义，重放完成后才开始记录
+    board.set_event_recording(false);
+    for (i, action) in record.iter().enumerate() {
+        match action {
+            GameAction::Reward { .. } => {}
+            GameAction::Clock { .. } => board.record_action(action.clone()),
+            GameAction::Timeout { player } => board.record_timeout(*player),
+            GameAction::Resign { player } => board.record_resign(*player),
+            _ => {
+                if board.check_winner().is_some() || !board.legal_actions().contains(action) {
+                    return Err(trf("record.invalid_entry", &[&(i + 1), &action.notation()]));
+                }
+                // 合法动作执行失败时局面已经改变（行动方判负），继续重放即可
+                let _ = board.apply_action(action);
+            }
+        }
+    }
+    board.set_event_recording(true);
+    Ok(board)
+}
+
+/// 按 kind 保存到 path
+pub fn save(path: &Path, board: &Board, kind: SaveKind) -> Result<(), String> {
+    let json = match kind {
+        SaveKind::Record => serde_json::to_string_pretty(&board.game_record),
+        SaveKind::Snapshot => serde_json::to_string_pretty(&Snapshot::from_board(board)),
+    }
+    .map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| trf("io.write_failed", &[&path.display(), &e]))
+}
+
+/// 打开棋谱或快照，返回棋盘和文件格式
+pub fn load(path: &Path) -> Result<(Board, SaveKind), String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| trf("io.read_failed", &[&path.display(), &e]))?;
+    let value: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| trf("save.bad_file", &[&e]))?;
+
+    if value.is_array() {
+        let record: Vec<GameAction> =
+            serde_json::from_value(value).map_err(|e| trf("save.bad_record", &[&e]))?;
+        Ok((board_from_record(&record)?, SaveKind::Record))
+    } else {
+        let snapshot: Snapshot =
+            serde_json::from_value(value).map_err(|e| trf("save.bad_snapshot", &[&e]))?;
+        Ok((snapshot.to_board()?, SaveKind::Snapshot))
+    }
+}
@@ -0,0 +1,632 @@
+// 多房间对局服务器：JSON over HTTP，事件通过 Server-Sent Events 推送
+//
+// 接口：
+//   GET  /rooms                  列出所有房间
+//   POST /rooms                  创建房间
+//   GET  /rooms/<id>             房间当前局面
+//   POST /rooms/<id>/join        入座，返回 {"player": ..., "token": ...}，先入座者执黑
+//   POST /rooms/<id>/action      执行动作，请求体 {"token": ..., "action": "2,3"}
+//   POST /rooms/<id>/resign      认输，请求体 {"token": ...}
+//   GET  /rooms/<id>/events      订阅房间事件（text/event-stream，每条 data 为一个 JSON 事件）
+//   GET  /                       观战首页，列出所有房间
+//   GET  /rooms/<id>/watch       观战页面，只读地实时显示局面、受保护棋子和棋谱
+//
+// 动作记号见 GameAction::notation。服务器裁决行棋顺序与规则，对局结束后棋谱保存到棋谱目录。
+
+use crate::i18n::{tr, trf};
+use crate::{Board, GameAction, GameEvent, GamePhase, Player, RewardPattern};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tiny_http::{Header, Method, Request, Response};
+
+/// 默认监听地址
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+/// 默认的棋谱目录
+pub const DEFAULT_RECORDS_DIR: &str = "records";
+
+// 事件流的心跳间隔，用于及时发现已断开的订阅者
+const KEEPALIVE: Duration = Duration::from_secs(15);
+const MAX_BODY: u64 = 64 * 1024;
+
+// 观战页面
+const INDEX_HTML: &str = include_str!("spectator_index.html");
+const WATCH_HTML: &str = include_str!("spectator.html");
+
+/// 棋谱条目，供观战页面直接显示
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordEntry {
+    /// 行动方
+    pub player: Player,
+    /// 显示的文字：动作记号、奖励模式名称或认输
+    pub text: String,
+    /// 是否为奖励或认输（观战页面以不同样式显示）
+    pub reward: bool,
+}
+
+impl RecordEntry {
+    fn from_action(action: &GameAction) -> Self {
+        match action {
+            GameAction::Reward { player, pattern } => RecordEntry {
+                player: *player,
+                text: pattern.to_string(),
+                reward: true,
+            },
+            GameAction::Resign { player } => RecordEntry {
+                player: *player,
+                text: tr("server.resigned").to_string(),
+                reward: true,
+            },
+            GameAction::Place { player, .. }
+            | GameAction::Capture { player, .. }
+            | GameAction::Move { player, .. }
+            | GameAction::Clock { player, .. }
+            | GameAction::Timeout { player } => RecordEntry {
+                player: *player,
+                text: action.notation(),
+                reward: false,
+            },
+        }
+    }
+}
+
+/// 房间事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RoomEvent {
+    /// 订阅后的第一条事件：完整棋谱和对局结果
+    Snapshot {
+        /// 已有的棋谱
+        record: Vec<RecordEntry>,
+        /// 对局已结束时的结果
+        result: Option<GameResult>,
+    },
+    /// 有玩家入座
+    Joined {
+        /// 入座的一方
+        player: Player,
+    },
+    /// 执行了一个落子、吃子或移动
+    Action {
+        /// 执行的动作
+        action: GameAction,
+        /// 动作记号
+        notation: String,
+    },
+    /// 形成奖励模式
+    Reward {
+        /// 形成模式的一方
+        player: Player,
+        /// 奖励模式
+        pattern: RewardPattern,
+        /// 奖励模式的名称
+        text: String,
+    },
+    /// 动作执行后的局面
+    State {
+        /// 局面字符串
+        position: String,
+        /// 局面哈希
+        hash: u64,
+        /// 当前阶段
+        phase: GamePhase,
+        /// 当前行棋方
+        current_player: Player,
+        /// 双方受保护的棋子
+        protected: Vec<(usize, usize)>,
+    },
+    /// 对局结束
+    GameEnded {
+        /// 胜方，和棋时为 None
+        winner: Option<Player>,
+        /// 结束的原因
+        reason: String,
+    },
+}
+
+/// 对局结果
+#[derive(Debug, Clone, Serialize)]
+pub struct GameResult {
+    /// 胜方，和棋时为 None
+    pub winner: Option<Player>,
+    /// 结束的原因
+    pub reason: String,
+    /// 保存的棋谱文件，保存失败时为 None
+    pub record_file: Option<String>,
+}
+
+struct Room {
+    board: Board,
+    tokens: HashMap<Player, String>,
+    subscribers: Vec<Sender<RoomEvent>>,
+    result: Option<GameResult>,
+}
+
+#[derive(Serialize)]
+struct RoomView<'a> {
+    id: u64,
+    seats: Vec<Player>,
+    phase: &'a GamePhase,
+    current_player: Player,
+    position: String,
+    hash: u64,
+    protected: Vec<(usize, usize)>,
+    record: &'a [GameAction],
+    result: &'a Option<GameResult>,
+}
+
+#[derive(Serialize)]
+struct RoomSummary {
+    id: u64,
+    seats: Vec<Player>,
+    actions: usize,
+    finished: bool,
+}
+
+#[derive(Deserialize)]
+struct ActionRequest {
+    token: String,
+    action: String,
+}
+
+#[derive(Deserialize)]
+struct TokenRequest {
+    token: String,
+}
+
+struct ServerState {
+    rooms: BTreeMap<u64, Room>,
+    next_id: u64,
+    records_dir: PathBuf,
+}
+
+// 请求处理的错误：HTTP 状态码 + 错误信息
+type HttpError = (u16, String);
+
+impl Room {
+    fn new() -> Self {
+        Room {
+            board: Board::new(),
+            tokens: HashMap::new(),
+            subscribers: Vec::new(),
+            result: None,
+        }
+    }
+
+    fn seats(&self) -> Vec<Player> {
+        [Player::Black, Player::White]
+            .into_iter()
+            .filter(|p| self.tokens.contains_key(p))
+            .collect()
+    }
+
+    fn view(&self, id: u64) -> RoomView<'_> {
+        RoomView {
+            id,
+            seats: self.seats(),
+            phase: &self.board.phase,
+            current_player: self.board.current_player,
+            position: self.board.to_position_string(),
+            hash: self.board.position_hash(),
+            protected: self.protected(),
+            record: &self.board.game_record,
+            result: &self.result,
+        }
+    }
+
+    // 双方受保护的棋子
+    fn protected(&self) -> Vec<(usize, usize)> {
+        let mut pieces: Vec<(usize, usize)> =
+            self.board.reward_pieces.values().flatten().copied().collect();
+        pieces.sort();
+        pieces
+    }
+
+    fn state_event(&self) -> RoomEvent {
+        RoomEvent::State {
+            position: self.board.to_position_string(),
+            hash: self.board.position_hash(),
+            phase: self.board.phase.clone(),
+            current_player: self.board.current_player,
+            protected: self.protected(),
+        }
+    }
+
+    fn snapshot_event(&self) -> RoomEvent {
+        RoomEvent::Snapshot {
+            record: self
+                .board
+                .game_record
+                .iter()
+                .map(RecordEntry::from_action)
+                .collect(),
+            result: self.result.clone(),
+        }
+    }
+
+    fn player_for_token(&self, token: &str) -> Result<Player, HttpError> {
+        self.tokens
+            .iter()
+            .find(|(_, t)| t.as_str() == token)
+            .map(|(p, _)| *p)
+            .ok_or((403, tr("server.bad_token").to_string()))
+    }
+
+    // 向所有订阅者推送事件，顺便清理已断开的订阅者
+    fn broadcast(&mut self, event: RoomEvent) {
+        self.subscribers.retain(|s| s.send(event.clone()).is_ok());
+    }
+
+    // 推送动作产生的事件，最后附上新的局面
+    fn broadcast_events(&mut self) {
+        for event in self.board.take_events() {
+            let action = match event {
+                GameEvent::PiecePlaced { player, pos } => GameAction::Place { player, pos },
+                GameEvent::PieceCaptured { player, pos } => GameAction::Capture { player, pos },
+                GameEvent::PieceMoved { player, from, to } => GameAction::Move { player, from, to },
+                GameEvent::PatternFormed { player, pattern, .. } => {
+                    let text = pattern.to_string();
+                    self.broadcast(RoomEvent::Reward { player, pattern, text });
+                    continue;
+                }
+                // 阶段和行棋方的变化包含在随后的局面中，对局结束由 finish 推送
+                GameEvent::PhaseChanged { .. }
+                | GameEvent::CaptureSkipped { .. }
+                | GameEvent::TurnChanged { .. }
+                | GameEvent::GameEnded { .. } => {
+                    continue;
+                }
+            };
+            self.broadcast(RoomEvent::Action {
+                notation: action.notation(),
+                action,
+            });
+        }
+        let state = self.state_event();
+        self.broadcast(state);
+    }
+
+    fn finish(&mut self, id: u64, winner: Option<Player>, reason: String, records_dir: &Path) {
+        let record_file = match save_record(id, &self.board.game_record, records_dir) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                eprintln!("{}", trf("server.save_failed", &[&id, &e]));
+                None
+            }
+        };
+        self.result = Some(GameResult {
+            winner,
+            reason: reason.clone(),
+            record_file,
+        });
+        self.broadcast(RoomEvent::GameEnded { winner, reason });
+    }
+}
+
+fn save_record(id: u64, record: &[GameAction], dir: &Path) -> Result<String, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = dir.join(format!("room-{}-{}.json", id, timestamp));
+    let serialized = serde_json::to_string_pretty(record).map_err(|e| e.to_string())?;
+    fs::write(&path, serialized).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn new_token() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+/// 运行中的服务器
+pub struct ServerHandle {
+    addr: SocketAddr,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    /// 实际监听的地址（以端口 0 启动时可从这里得到分配的端口）
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 阻塞直到服务器停止
+    pub fn join(mut self) {
+        if let Some(t) = self.thread.take() {
+            t.join().ok();
+        }
+    }
+}
+
+/// 启动服务器（端口 0 表示由系统分配），每个请求在单独的线程中处理
+pub fn start(addr: &str, records_dir: PathBuf) -> Result<ServerHandle, String> {
+    let server = tiny_http::Server::http(addr).map_err(|e| e.to_string())?;
+    let addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or(tr("server.bad_address"))?;
+
+    let state = Arc::new(Mutex::new(ServerState {
+        rooms: BTreeMap::new(),
+        next_id: 1,
+        records_dir,
+    }));
+
+    let thread = thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let state = Arc::clone(&state);
+            thread::spawn(move || handle_request(request, &state));
+        }
+    });
+
+    Ok(ServerHandle {
+        addr,
+        thread: Some(thread),
+    })
+}
+
+/// 命令行入口：`wudaofang server [地址] [棋谱目录]`
+pub fn run_server(args: &[String]) -> Result<(), String> {
+    let addr = args.first().map_or(DEFAULT_ADDR, String::as_str);
+    let records_dir = PathBuf::from(args.get(1).map_or(DEFAULT_RECORDS_DIR, String::as_str));
+    let handle = start(addr, records_dir.clone())?;
+    println!(
+        "{}",
+        trf("server.started", &[&handle.addr(), &records_dir.display()])
+    );
+    handle.join();
+    Ok(())
+}
+
+fn handle_request(mut request: Request, state: &Mutex<ServerState>) {
+    let path: Vec<String> = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or("")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+    let method = request.method().clone();
+
+    // 事件流需要长期占用连接，单独处理
+    if let (Method::Get, ["rooms", id, "events"]) = (&method, path.as_slice()) {
+        stream_events(request, state, id);
+        return;
+    }
+
+    if method == Method::Get {
+        let page = match path.as_slice() {
+            [] => Some(INDEX_HTML),
+            ["rooms", _, "watch"] => Some(WATCH_HTML),
+            _ => None,
+        };
+        if let Some(page) = page {
+            respond_html(request, page);
+            return;
+        }
+    }
+
+    let mut body = String::new();
+    if request
+        .as_reader()
+        .take(MAX_BODY)
+        .read_to_string(&mut body)
+        .is_err()
+    {
+        respond(request, Err((400, tr("server.bad_body").to_string())));
+        return;
+    }
+
+    let mut state = state.lock().unwrap();
+    let result = match (&method, path.as_slice()) {
+        (Method::Get, ["rooms"]) => list_rooms(&state),
+        (Method::Post, ["rooms"]) => create_room(&mut state),
+        (Method::Get, ["rooms", id]) => {
+            room_id(id).and_then(|id| with_room(&mut state, id, |room, _| to_json(&room.view(id))))
+        }
+        (Method::Post, ["rooms", id, "join"]) => {
+            room_id(id).and_then(|id| with_room(&mut state, id, |room, _| join_room(room)))
+        }
+        (Method::Post, ["rooms", id, "action"]) => room_id(id).and_then(|id| {
+            with_room(&mut state, id, |room, dir| play_action(room, id, &body, dir))
+        }),
+        (Method::Post, ["rooms", id, "resign"]) => room_id(id).and_then(|id| {
+            with_room(&mut state, id, |room, dir| resign(room, id, &body, dir))
+        }),
+        _ => Err((404, tr("server.not_found").to_string())),
+    };
+    drop(state);
+
+    respond(request, result);
+}
+
+fn respond(request: Request, result: Result<String, HttpError>) {
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err((status, message)) => (status, serde_json::json!({ "error": message }).to_string()),
+    };
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(json_header());
+    request.respond(response).ok();
+}
+
+fn respond_html(request: Request, html: &str) {
+    let header =
+        Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..]).unwrap();
+    request
+        .respond(Response::from_string(html).with_header(header))
+        .ok();
+}
+
+fn json_header() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"application/json; charset=utf-8"[..]).unwrap()
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, HttpError> {
+    serde_json::to_string(value).map_err(|e| (500, e.to_string()))
+}
+
+fn room_id(id: &str) -> Result<u64, HttpError> {
+    id.parse().map_err(|_| (404, tr("server.no_room").to_string()))
+}
+
+fn with_room(
+    state: &mut ServerState,
+    id: u64,
+    f: impl FnOnce(&mut Room, &Path) -> Result<String, HttpError>,
+) -> Result<String, HttpError> {
+    let ServerState {
+        rooms, records_dir, ..
+    } = state;
+    let room = rooms.get_mut(&id).ok_or((404, tr("server.no_room").to_string()))?;
+    f(room, records_dir)
+}
+
+fn list_rooms(state: &ServerState) -> Result<String, HttpError> {
+    let rooms: Vec<RoomSummary> = state
+        .rooms
+        .iter()
+        .map(|(&id, room)| RoomSummary {
+            id,
+            seats: room.seats(),
+            actions: room.board.game_record.len(),
+            finished: room.result.is_some(),
+        })
+        .collect();
+    to_json(&rooms)
+}
+
+fn create_room(state: &mut ServerState) -> Result<String, HttpError> {
+    let id = state.next_id;
+    state.next_id += 1;
+    state.rooms.insert(id, Room::new());
+    to_json(&serde_json::json!({ "id": id }))
+}
+
+fn join_room(room: &mut Room) -> Result<String, HttpError> {
+    let player = [Player::Black, Player::White]
+        .into_iter()
+        .find(|p| !room.tokens.contains_key(p))
+        .ok_or((409, tr("net.room_full").to_string()))?;
+    let token = new_token();
+    room.tokens.insert(player, token.clone());
+    room.broadcast(RoomEvent::Joined { player });
+    to_json(&serde_json::json!({ "player": player, "token": token }))
+}
+
+fn play_action(room: &mut Room, id: u64, body: &str, dir: &Path) -> Result<String, HttpError> {
+    let request: ActionRequest =
+        serde_json::from_str(body).map_err(|e| (400, trf("server.bad_body_detail", &[&e])))?;
+    let player = room.player_for_token(&request.token)?;
+
+    if room.result.is_some() {
+        return Err((409, tr("game.finished").to_string()));
+    }
+    if room.tokens.len() < 2 {
+        return Err((409, tr("server.waiting").to_string()));
+    }
+    if room.board.current_player != player {
+        return Err((409, tr("net.not_your_turn").to_string()));
+    }
+
+    let action = room
+        .board
+        .parse_action(&request.action)
+        .map_err(|e| (400, e.to_string()))?;
+    if !room.board.legal_actions().contains(&action) {
+        return Err((400, tr("net.illegal_action").to_string()));
+    }
+
+    let outcome = room.board.apply_action(&action);
+    room.broadcast_events();
+
+    // 合法动作执行失败说明该动作导致对方无法走棋，行动方判负
+    if let Err(e) = outcome {
+        room.finish(id, Some(player.opponent()), e.to_string(), dir);
+    } else if let Some(winner) = room.board.check_winner() {
+        room.finish(id, Some(winner), tr("result.pieces_or_blocked").to_string(), dir);
+    }
+
+    to_json(&room.view(id))
+}
+
+fn resign(room: &mut Room, id: u64, body: &str, dir: &Path) -> Result<String, HttpError> {
+    let request: TokenRequest =
+        serde_json::from_str(body).map_err(|e| (400, trf("server.bad_body_detail", &[&e])))?;
+    let player = room.player_for_token(&request.token)?;
+    if room.result.is_some() {
+        return Err((409, tr("game.finished").to_string()));
+    }
+    room.board.record_resign(player);
+    room.finish(id, Some(player.opponent()), trf("record.resign", &[&player]), dir);
+    to_json(&room.view(id))
+}
+
+fn stream_events(request: Request, state: &Mutex<ServerState>, id: &str) {
+    let (tx, rx) = mpsc::channel();
+    let initial = {
+        let mut state = state.lock().unwrap();
+        let room = room_id(id).and_then(|id| {
+            state
+                .rooms
+                .get_mut(&id)
+                .ok_or((404, tr("server.no_room").to_string()))
+        });
+        match room {
+            Ok(room) => {
+                room.subscribers.push(tx);
+                [room.snapshot_event(), room.state_event()]
+            }
+            Err(e) => {
+                drop(state);
+                respond(request, Err(e));
+                return;
+            }
+        }
+    };
+
+    let mut writer = request.into_writer();
+    let header = "HTTP/1.1 200 OK\r\n\
+                  Content-Type: text/event-stream; charset=utf-8\r\n\
+                  Cache-Control: no-cache\r\n\
+                  Access-Control-Allow-Origin: *\r\n\
+                  Connection: close\r\n\r\n";
+    if writer.write_all(header.as_bytes()).is_err() {
+        return;
+    }
+    for event in &initial {
+        if write_event(&mut writer, event).is_err() {
+            return;
+        }
+    }
+
+    loop {
+        let sent = match rx.recv_timeout(KEEPALIVE) {
+            Ok(event) => write_event(&mut writer, &event),
+            Err(RecvTimeoutError::Timeout) => writer
+                .write_all(b": ping\n\n")
+                .and_then(|_| writer.flush()),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if sent.is_err() {
+            break;
+        }
+    }
+}
+
+fn write_event(writer: &mut dyn Write, event: &RoomEvent) -> std::io::Result<()> {
+    let json = serde_json::to_string(event)?;
+    write!(writer, "data: {}\n\n", json)?;
+    writer.flush()
+}
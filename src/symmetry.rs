@@ -0,0 +1,203 @@
+// 棋盘对称
+//
+// 5x5 棋盘有 8 种对称变换：4 种旋转，以及先左右翻转再旋转的 4 种。
+// 每种奖励模式在变换下都映射为同类模式（行和列互换，两条对角线互换），
+// 所以互相对称的局面在规则上完全等价。
+// 规范形式取 8 个变换后局面字符串最小的那个，用于在数据库中查找对称的局面。
+
+use crate::{Board, Cell, GameAction, RewardPattern};
+
+/// 对称变换的数量
+pub const SYMMETRIES: usize = 8;
+
+/// 对格子做第 sym 种变换：0-3 为顺时针旋转 sym×90°，4-7 为先左右翻转再旋转 (sym-4)×90°
+pub fn transform_cell(sym: usize, (r, c): (usize, usize)) -> (usize, usize) {
+    let (mut r, mut c) = if sym >= 4 { (r, 4 - c) } else { (r, c) };
+    for _ in 0..sym % 4 {
+        (r, c) = (c, 4 - r);
+    }
+    (r, c)
+}
+
+/// 逆变换：翻转类变换的逆是它自己
+pub fn inverse(sym: usize) -> usize {
+    if sym >= 4 { sym } else { (4 - sym) % 4 }
+}
+
+/// 变换奖励模式，结果仍是奖励模式表中的模式
+pub fn transform_pattern(sym: usize, pattern: &RewardPattern) -> RewardPattern {
+    let mut cells: Vec<_> = pattern.cells().into_iter().map(|p| transform_cell(sym, p)).collect();
+    cells.sort();
+    RewardPattern::catalog()
+        .into_iter()
+        .find(|candidate| {
+            let mut other = candidate.cells();
+            other.sort();
+            other == cells
+        })
+        .expect("奖励模式在对称变换下应映射为另一个奖励模式")
+}
+
+/// 变换动作中的坐标和奖励模式
+pub fn transform_action(sym: usize, action: &GameAction) -> GameAction {
+    match action {
+        GameAction::Place { player, pos } => GameAction::Place {
+            player: *player,
+            pos: transform_cell(sym, *pos),
+        },
+        GameAction::Capture { player, pos } => GameAction::Capture {
+            player: *player,
+            pos: transform_cell(sym, *pos),
+        },
+        GameAction::Move { player, from, to } => GameAction::Move {
+            player: *player,
+            from: transform_cell(sym, *from),
+            to: transform_cell(sym, *to),
+        },
+        GameAction::Reward { player, pattern } => GameAction::Reward {
+            player: *player,
+            pattern: transform_pattern(sym, pattern),
+        },
+        _ => action.clone(),
+    }
+}
+
+impl Board {
+    /// 变换后的棋盘（包括棋谱）
+    pub fn transformed(&self, sym: usize) -> Board {
+        let mut board = self.clone();
+
+        board.grid = [[Cell::Empty; 5]; 5];
+        for r in 0..5 {
+            for c in 0..5 {
+                let (tr, tc) = transform_cell(sym, (r, c));
+                board.grid[tr][tc] = self.grid[r][c];
+            }
+        }
+
+        board.triggered_squares.clear();
+        board.triggered_tris.clear();
+        board.triggered_tetras.clear();
+        board.triggered_rows.clear();
+        board.triggered_cols.clear();
+        board.triggered_dragons.clear();
+        for pattern in RewardPattern::catalog() {
+            if self.is_triggered(&pattern) {
+                match transform_pattern(sym, &pattern) {
+                    RewardPattern::Square { top_left: (r, c) } => board.triggered_squares.insert([r, c]),
+                    RewardPattern::Tri { id } => board.triggered_tris.insert(id),
+                    RewardPattern::Tetra { id } => board.triggered_tetras.insert(id),
+                    RewardPattern::Row { index } => board.triggered_rows.insert(index),
+                    RewardPattern::Col { index } => board.triggered_cols.insert(index),
+                    RewardPattern::Dragon { id } => board.triggered_dragons.insert(id),
+                };
+            }
+        }
+
+        board.game_record = self.game_record.iter().map(|a| transform_action(sym, a)).collect();
+        board.update_reward_pieces();
+        board
+    }
+
+    /// 规范局面字符串，以及把本局面变换为规范形式所用的变换
+    pub fn canonical(&self) -> (String, usize) {
+        // 只比较局面，不必变换棋谱
+        let position = Board::from_position_string(&self.to_position_string())
+            .expect("局面字符串应能还原");
+        (0..SYMMETRIES)
+            .map(|sym| (position.transformed(sym).to_position_string(), sym))
+            .min()
+            .expect("至少有一种变换")
+    }
+
+    /// 保持局面不变的变换（总包含恒等变换 0）
+    pub fn stabilizer(&self) -> Vec<usize> {
+        let position = self.to_position_string();
+        let board = Board::from_position_string(&position).expect("局面字符串应能还原");
+        (0..SYMMETRIES)
+            .filter(|&sym| board.transformed(sym).to_position_string() == position)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按固定规则走若干步得到的局面，尽量包含奖励和吃子
+    fn sample_positions() -> Vec<Board> {
+        let mut positions = Vec::new();
+        for seed in 1..6 {
+            let mut board = Board::new();
+            for step in 0..(6 + seed * 5) {
+                let actions = board.legal_actions();
+                if actions.is_empty() {
+                    break;
+                }
+                let action = actions[(step * 7 + seed * 3) % actions.len()].clone();
+                if board.apply_action(&action).is_err() {
+                    break;
+                }
+            }
+            positions.push(board);
+        }
+        positions
+    }
+
+    // 模式的种类，行和列在变换下互换，算作同一类
+    fn kind(pattern: &RewardPattern) -> usize {
+        match pattern {
+            RewardPattern::Square { .. } => 0,
+            RewardPattern::Tri { .. } => 1,
+            RewardPattern::Tetra { .. } => 2,
+            RewardPattern::Row { .. } | RewardPattern::Col { .. } => 3,
+            RewardPattern::Dragon { .. } => 4,
+        }
+    }
+
+    #[test]
+    fn every_symmetry_maps_the_catalog_onto_itself() {
+        let catalog = RewardPattern::catalog();
+        for sym in 0..SYMMETRIES {
+            let mut images: Vec<RewardPattern> = Vec::new();
+            for pattern in &catalog {
+                let image = transform_pattern(sym, pattern);
+                assert_eq!(kind(&image), kind(pattern), "{pattern:?} -> {image:?}");
+                assert!(!images.contains(&image), "symmetry {sym} maps two patterns onto {image:?}");
+                images.push(image);
+            }
+            assert_eq!(images.len(), catalog.len());
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_each_symmetry() {
+        for sym in 0..SYMMETRIES {
+            let back = inverse(sym);
+            for r in 0..5 {
+                for c in 0..5 {
+                    assert_eq!(transform_cell(back, transform_cell(sym, (r, c))), (r, c));
+                }
+            }
+            for pattern in RewardPattern::catalog() {
+                assert_eq!(transform_pattern(back, &transform_pattern(sym, &pattern)), pattern);
+            }
+            for board in sample_positions() {
+                let restored = board.transformed(sym).transformed(back);
+                assert_eq!(restored.to_position_string(), board.to_position_string());
+                assert_eq!(restored.game_record, board.game_record);
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_form_is_shared_by_all_symmetric_copies() {
+        for board in sample_positions() {
+            let (canonical, sym) = board.canonical();
+            assert_eq!(board.transformed(sym).to_position_string(), canonical);
+            for other in 0..SYMMETRIES {
+                assert_eq!(board.transformed(other).canonical().0, canonical);
+            }
+        }
+    }
+}
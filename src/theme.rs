@@ -0,0 +1,260 @@
+// 界面配色
+//
+// 棋盘和状态面板的颜色都取自当前主题。受保护棋子（圆环加皇冠）、可走位置（方块）、
+// 提示（虚线圆环）和可吃棋子（准星）的标记形状各不相同，不依赖颜色也能分辨。
+// 选择的主题保存在本地数据目录的 theme 文件中，下次启动时沿用。
+
+use eframe::egui::Color32;
+use std::fs;
+use std::path::PathBuf;
+
+use wudaofang::autosave;
+use wudaofang::i18n::{tr, trf};
+
+const THEME_FILE: &str = "theme";
+
+pub struct Theme {
+    pub id: &'static str, // 保存到文件中的标识，不随界面语言变化
+    name: &'static str,   // 名称的消息键
+    // 窗口和状态面板
+    pub background: Color32,
+    pub title: Color32,
+    pub label: Color32,
+    pub value: Color32,
+    pub panel: Color32,
+    pub panel_stroke: Color32,
+    pub message: Color32,
+    pub alert: Color32,
+    pub muted: Color32,        // 次要文字和不在走的时钟
+    pub note: Color32,         // 棋谱中附加的奖励、超时、认输说明
+    pub panel_active: Color32, // 行棋方时钟的底色
+    pub black_label: Color32,
+    pub white_label: Color32,
+    // 棋盘
+    pub board: Color32,
+    pub grid: Color32,
+    pub coords: Color32,
+    pub black: Color32,
+    pub black_rim: Color32,
+    pub white: Color32,
+    pub white_outline: Color32,
+    pub gloss: Color32,  // 黑子高光
+    pub shadow: Color32, // 白子阴影
+    // 标记
+    pub protected: Color32,
+    pub selected: Color32,
+    pub move_target: Color32,
+    pub hint: Color32,
+    pub capture: Color32,
+    pub annotation: Color32,
+    pub reward: Color32,
+    pub flash: Color32,
+    // 生效中的奖励模式：成方、三斜、四斜、成州、成龙
+    pub patterns: [Color32; 5],
+}
+
+// 默认的木纹棋盘
+const WOOD: Theme = Theme {
+    id: "wood",
+    name: "theme.wood",
+    background: Color32::from_rgb(245, 235, 220),
+    title: Color32::from_rgb(120, 70, 30),
+    label: Color32::from_rgb(100, 60, 20),
+    value: Color32::from_rgb(80, 40, 10),
+    panel: Color32::from_rgb(250, 245, 235),
+    panel_stroke: Color32::from_rgb(180, 150, 120),
+    message: Color32::from_rgb(50, 80, 120),
+    alert: Color32::from_rgb(180, 40, 40),
+    muted: Color32::from_rgb(150, 130, 110),
+    note: Color32::from_rgb(0, 100, 0),
+    panel_active: Color32::from_rgb(255, 250, 225),
+    black_label: Color32::BLACK,
+    white_label: Color32::from_rgb(80, 80, 80),
+    board: Color32::from_rgb(188, 143, 101),
+    grid: Color32::from_rgb(80, 50, 20),
+    coords: Color32::from_rgb(50, 30, 10),
+    black: Color32::from_rgb(20, 20, 20),
+    black_rim: Color32::from_rgb(40, 40, 40),
+    white: Color32::from_rgb(230, 230, 230),
+    white_outline: Color32::from_rgb(100, 100, 100),
+    gloss: Color32::from_rgba_premultiplied(255, 255, 255, 100),
+    shadow: Color32::from_rgba_premultiplied(0, 0, 0, 40),
+    protected: Color32::GOLD,
+    selected: Color32::from_rgb(0, 150, 255),
+    move_target: Color32::from_rgb(0, 160, 0),
+    hint: Color32::from_rgb(255, 140, 0),
+    capture: Color32::from_rgb(210, 40, 40),
+    annotation: Color32::from_rgb(30, 90, 200),
+    reward: Color32::from_rgb(0, 150, 60),
+    flash: Color32::GOLD,
+    patterns: [
+        Color32::from_rgb(230, 120, 20),
+        Color32::from_rgb(30, 150, 140),
+        Color32::from_rgb(120, 60, 180),
+        Color32::from_rgb(40, 90, 200),
+        Color32::from_rgb(200, 30, 60),
+    ],
+};
+
+// 青灰色石质棋盘
+const SLATE: Theme = Theme {
+    id: "slate",
+    name: "theme.slate",
+    background: Color32::from_rgb(226, 232, 238),
+    title: Color32::from_rgb(40, 60, 90),
+    label: Color32::from_rgb(50, 70, 100),
+    value: Color32::from_rgb(20, 35, 60),
+    panel: Color32::from_rgb(240, 244, 248),
+    panel_stroke: Color32::from_rgb(140, 160, 185),
+    message: Color32::from_rgb(30, 70, 120),
+    alert: Color32::from_rgb(170, 30, 50),
+    muted: Color32::from_rgb(120, 135, 150),
+    note: Color32::from_rgb(20, 110, 70),
+    panel_active: Color32::from_rgb(225, 238, 252),
+    black_label: Color32::from_rgb(25, 28, 35),
+    white_label: Color32::from_rgb(90, 95, 105),
+    board: Color32::from_rgb(150, 170, 180),
+    grid: Color32::from_rgb(45, 60, 75),
+    coords: Color32::from_rgb(25, 35, 50),
+    black: Color32::from_rgb(25, 28, 35),
+    black_rim: Color32::from_rgb(50, 55, 65),
+    white: Color32::from_rgb(245, 245, 240),
+    white_outline: Color32::from_rgb(90, 95, 105),
+    gloss: Color32::from_rgba_premultiplied(255, 255, 255, 100),
+    shadow: Color32::from_rgba_premultiplied(0, 0, 0, 50),
+    protected: Color32::from_rgb(240, 200, 40),
+    selected: Color32::from_rgb(0, 110, 220),
+    move_target: Color32::from_rgb(20, 140, 70),
+    hint: Color32::from_rgb(240, 120, 0),
+    capture: Color32::from_rgb(200, 30, 50),
+    annotation: Color32::from_rgb(20, 70, 170),
+    reward: Color32::from_rgb(0, 130, 80),
+    flash: Color32::from_rgb(255, 230, 120),
+    patterns: [
+        Color32::from_rgb(220, 110, 30),
+        Color32::from_rgb(0, 130, 130),
+        Color32::from_rgb(110, 60, 170),
+        Color32::from_rgb(30, 80, 190),
+        Color32::from_rgb(190, 30, 70),
+    ],
+};
+
+// 高对比度：白底黑线，标记使用饱和的颜色和更粗的轮廓
+const HIGH_CONTRAST: Theme = Theme {
+    id: "high-contrast",
+    name: "theme.high_contrast",
+    background: Color32::WHITE,
+    title: Color32::BLACK,
+    label: Color32::BLACK,
+    value: Color32::BLACK,
+    panel: Color32::WHITE,
+    panel_stroke: Color32::BLACK,
+    message: Color32::from_rgb(0, 0, 160),
+    alert: Color32::from_rgb(200, 0, 0),
+    muted: Color32::from_rgb(80, 80, 80),
+    note: Color32::from_rgb(0, 110, 0),
+    panel_active: Color32::from_rgb(255, 255, 180),
+    black_label: Color32::BLACK,
+    white_label: Color32::from_rgb(60, 60, 60),
+    board: Color32::from_rgb(255, 255, 235),
+    grid: Color32::BLACK,
+    coords: Color32::BLACK,
+    black: Color32::BLACK,
+    black_rim: Color32::BLACK,
+    white: Color32::WHITE,
+    white_outline: Color32::BLACK,
+    gloss: Color32::from_rgba_premultiplied(255, 255, 255, 100),
+    shadow: Color32::from_rgba_premultiplied(0, 0, 0, 40),
+    protected: Color32::from_rgb(200, 0, 200),
+    selected: Color32::from_rgb(0, 80, 255),
+    move_target: Color32::from_rgb(0, 150, 0),
+    hint: Color32::from_rgb(255, 110, 0),
+    capture: Color32::from_rgb(230, 0, 0),
+    annotation: Color32::from_rgb(0, 0, 200),
+    reward: Color32::from_rgb(0, 140, 0),
+    flash: Color32::from_rgb(255, 200, 0),
+    patterns: [
+        Color32::from_rgb(255, 110, 0),
+        Color32::from_rgb(0, 150, 150),
+        Color32::from_rgb(150, 0, 200),
+        Color32::from_rgb(0, 60, 255),
+        Color32::from_rgb(220, 0, 0),
+    ],
+};
+
+// 色盲友好：Okabe-Ito 配色，避免只靠红绿区分
+const COLORBLIND: Theme = Theme {
+    id: "colorblind",
+    name: "theme.colorblind",
+    background: Color32::from_rgb(240, 238, 232),
+    title: Color32::from_rgb(60, 60, 60),
+    label: Color32::from_rgb(50, 50, 50),
+    value: Color32::from_rgb(20, 20, 20),
+    panel: Color32::from_rgb(250, 249, 245),
+    panel_stroke: Color32::from_rgb(150, 150, 150),
+    message: Color32::from_rgb(0, 90, 150),
+    alert: Color32::from_rgb(213, 94, 0),
+    muted: Color32::from_rgb(120, 120, 120),
+    note: Color32::from_rgb(0, 158, 115),
+    panel_active: Color32::from_rgb(255, 247, 210),
+    black_label: Color32::from_rgb(20, 20, 20),
+    white_label: Color32::from_rgb(80, 80, 80),
+    board: Color32::from_rgb(215, 205, 185),
+    grid: Color32::from_rgb(70, 60, 50),
+    coords: Color32::from_rgb(40, 35, 30),
+    black: Color32::from_rgb(20, 20, 20),
+    black_rim: Color32::from_rgb(45, 45, 45),
+    white: Color32::from_rgb(245, 245, 245),
+    white_outline: Color32::from_rgb(80, 80, 80),
+    gloss: Color32::from_rgba_premultiplied(255, 255, 255, 100),
+    shadow: Color32::from_rgba_premultiplied(0, 0, 0, 40),
+    protected: Color32::from_rgb(0, 114, 178),
+    selected: Color32::from_rgb(86, 180, 233),
+    move_target: Color32::from_rgb(230, 159, 0),
+    hint: Color32::from_rgb(204, 121, 167),
+    capture: Color32::from_rgb(213, 94, 0),
+    annotation: Color32::from_rgb(0, 114, 178),
+    reward: Color32::from_rgb(0, 158, 115),
+    flash: Color32::from_rgb(240, 228, 66),
+    patterns: [
+        Color32::from_rgb(230, 159, 0),
+        Color32::from_rgb(86, 180, 233),
+        Color32::from_rgb(204, 121, 167),
+        Color32::from_rgb(0, 114, 178),
+        Color32::from_rgb(213, 94, 0),
+    ],
+};
+
+pub const THEMES: [&Theme; 4] = [&WOOD, &SLATE, &HIGH_CONTRAST, &COLORBLIND];
+
+impl Theme {
+    pub fn name(&self) -> &'static str {
+        tr(self.name)
+    }
+}
+
+impl PartialEq for Theme {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+fn theme_path() -> Option<PathBuf> {
+    autosave::data_dir().map(|dir| dir.join(THEME_FILE))
+}
+
+// 上次选择的主题，没有保存过或无法识别时使用木纹主题
+pub fn load() -> &'static Theme {
+    let saved = theme_path().and_then(|path| fs::read_to_string(path).ok());
+    saved
+        .and_then(|id| THEMES.into_iter().find(|theme| theme.id == id.trim()))
+        .unwrap_or(&WOOD)
+}
+
+pub fn save(theme: &Theme) -> Result<(), String> {
+    let path = theme_path().ok_or_else(|| tr("io.no_data_dir").to_string())?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| trf("io.create_failed", &[&dir.display(), &e]))?;
+    }
+    fs::write(&path, theme.id).map_err(|e| trf("io.write_failed", &[&path.display(), &e]))
+}
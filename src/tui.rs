@@ -0,0 +1,481 @@
+// 全屏终端界面
+//
+// 供 SSH 会话和没有图形环境的服务器使用：方向键移动光标，回车或空格落子/吃子/选择和移动棋子，
+// 右侧显示着法记录，底部状态栏显示阶段、行棋方和提示信息。
+// 与图形界面一样支持人人对局、与内置引擎对局、提示、认输、保存以及自动存档和继续未完成的对局。
+//
+// 用法: tui [--ai black|white] [--movetime 毫秒] [--save 文件]
+
+use crate::ai::{self, SearchLimits};
+use crate::i18n::{tr, trf};
+use crate::savegame::{self, SaveKind};
+use crate::{Board, Cell, GameAction, GameEvent, GamePhase, GameReplayer, Player, autosave, book, db};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{Frame, Terminal};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+const BOARD_COLOR: Color = Color::Rgb(222, 184, 135);
+const GRID_COLOR: Color = Color::Rgb(139, 69, 19);
+const PROTECTED_COLOR: Color = Color::Rgb(255, 215, 0);
+const CURSOR_COLOR: Color = Color::Rgb(100, 149, 237);
+const SELECTED_COLOR: Color = Color::Rgb(60, 179, 113);
+const HINT_COLOR: Color = Color::Rgb(255, 140, 0);
+
+// 引擎每步的默认思考时间
+const TUI_MOVETIME: u64 = 1000;
+// 提示的搜索时间
+const HINT_MOVETIME: Duration = Duration::from_millis(800);
+
+// 后台搜索的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Job {
+    Engine,
+    Hint,
+}
+
+struct TuiApp {
+    board: Board,
+    cursor: (usize, usize),
+    selected: Option<(usize, usize)>,
+    message: String,
+    game_over: bool,
+    ai: Option<Player>,
+    movetime: Duration,
+    book: Option<Arc<book::Book>>,
+    save_path: String,
+    // 后台搜索及其对应局面的哈希
+    search: Option<(Job, u64, Receiver<Option<GameAction>>)>,
+    hint: Option<(u64, GameAction)>,
+    resume: Option<Board>,
+    archived: bool,
+    quit: bool,
+}
+
+// 退出时（包括出错和 panic）恢复终端
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// 命令行入口：`wudaofang tui [--ai black|white] [--movetime 毫秒] [--save 文件]`
+pub fn run_tui(args: &[String]) -> Result<(), String> {
+    let (mut options, _) = db::parse_options(args, &[])?;
+    let ai = match options.remove("ai").as_deref() {
+        None | Some("none") => None,
+        Some("black") => Some(Player::Black),
+        Some("white") => Some(Player::White),
+        Some(other) => return Err(trf("tui.bad_ai", &[&other])),
+    };
+    let movetime = match options.remove("movetime") {
+        Some(ms) => Duration::from_millis(ms.parse().map_err(|_| trf("arg.integer", &[&"movetime"]))?),
+        None => Duration::from_millis(TUI_MOVETIME),
+    };
+
+    let mut app = TuiApp {
+        board: Board::new(),
+        cursor: (2, 2),
+        selected: None,
+        message: String::new(),
+        game_over: false,
+        ai,
+        movetime,
+        book: book::Book::load(&book::default_path()).ok().map(Arc::new),
+        save_path: options.remove("save").unwrap_or_else(|| "wudao_game_record.json".to_string()),
+        search: None,
+        hint: None,
+        resume: None,
+        archived: false,
+        quit: false,
+    };
+    match autosave::load() {
+        Some(Ok(board)) => {
+            app.message = trf("tui.resume_prompt", &[&board.game_record.len()]);
+            app.resume = Some(board);
+        }
+        Some(Err(e)) => app.message = trf("autosave.load_failed", &[&e]),
+        None => {}
+    }
+
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    let _guard = TerminalGuard;
+    execute!(io::stdout(), EnterAlternateScreen).map_err(|e| e.to_string())?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout())).map_err(|e| e.to_string())?;
+
+    while !app.quit {
+        app.poll_search();
+        app.request_engine();
+        terminal.draw(|frame| app.draw(frame)).map_err(|e| e.to_string())?;
+
+        // 等待按键时定期返回，以便收取后台搜索的结果
+        if event::poll(Duration::from_millis(100)).map_err(|e| e.to_string())?
+            && let Event::Key(key) = event::read().map_err(|e| e.to_string())?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key.code);
+        }
+    }
+    Ok(())
+}
+
+impl TuiApp {
+    fn start_game(&mut self, board: Board) {
+        self.board = board;
+        self.selected = None;
+        self.search = None;
+        self.hint = None;
+        self.resume = None;
+        self.game_over = false;
+        self.message = String::new();
+        self.check_game_over();
+        // 打开的是已结束的对局时不再重复归档
+        self.archived = self.game_over;
+        if self.board.game_record.is_empty() {
+            autosave::clear();
+        }
+    }
+
+    fn is_engine_turn(&self) -> bool {
+        self.ai == Some(self.board.current_player)
+    }
+
+    fn check_game_over(&mut self) {
+        if let Some(winner) = self.board.check_winner() {
+            self.message = trf("gui.game_over_winner", &[&winner]);
+            self.game_over = true;
+        }
+    }
+
+    // 执行动作，合法动作执行失败时局面已经改变，行动方判负
+    fn play(&mut self, action: GameAction) {
+        let legal = self.board.legal_actions().contains(&action);
+        self.board.take_events();
+        match self.board.apply_action(&action) {
+            Ok(_) => {
+                let rewards: Vec<String> = self
+                    .board
+                    .take_events()
+                    .into_iter()
+                    .filter_map(|event| match event {
+                        GameEvent::PatternFormed { pattern, .. } => Some(trf("record.formed", &[&pattern])),
+                        GameEvent::CaptureSkipped { player, remaining } => {
+                            Some(trf("capture.skipped", &[&player, &remaining]))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                self.message = rewards.into_iter().fold(
+                    format!("{} {}", action.player(), action.notation()),
+                    |text, reward| trf("list.join", &[&text, &reward]),
+                );
+                self.selected = None;
+                self.hint = None;
+                self.check_game_over();
+            }
+            Err(e) if legal => {
+                self.message = trf("cli.lost_by_error", &[&e, &action.player().opponent()]);
+                self.game_over = true;
+            }
+            Err(e) => self.message = trf("cli.action_failed", &[&e]),
+        }
+        self.autosave();
+    }
+
+    // 每个动作后自动存档，对局结束后归档
+    fn autosave(&mut self) {
+        if self.game_over {
+            if !self.archived {
+                self.archived = true;
+                match autosave::archive(&self.board) {
+                    Ok(path) => self.message += &trf("gui.archived", &[&path.display()]),
+                    Err(e) => self.message += &trf("tui.archive_failed", &[&e]),
+                }
+                autosave::clear();
+            }
+        } else if let Err(e) = autosave::save(&self.board) {
+            self.message = trf("gui.autosave_failed", &[&e]);
+        }
+    }
+
+    fn spawn_search(&mut self, job: Job, movetime: Duration) {
+        let board = self.board.clone();
+        let book = if job == Job::Engine { self.book.clone() } else { None };
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let action = book.and_then(|b| b.best_move(&board)).or_else(|| {
+                let limits = SearchLimits {
+                    movetime: Some(movetime),
+                    depth: None,
+                };
+                ai::search(&board, limits, &AtomicBool::new(false), |_| {}).best
+            });
+            let _ = tx.send(action);
+        });
+        self.search = Some((job, self.board.position_hash(), rx));
+    }
+
+    fn request_engine(&mut self) {
+        if self.is_engine_turn() && !self.game_over && self.resume.is_none() && self.search.is_none() {
+            self.spawn_search(Job::Engine, self.movetime);
+        }
+    }
+
+    fn request_hint(&mut self) {
+        if self.game_over {
+            self.message = tr("gui.game_finished").to_string();
+        } else if self.is_engine_turn() {
+            self.message = tr("gui.not_our_turn").to_string();
+        } else if self.search.is_none() {
+            self.spawn_search(Job::Hint, HINT_MOVETIME);
+            self.message = tr("gui.hint_thinking").to_string();
+        }
+    }
+
+    // 收取后台搜索结果，局面已经变化的结果直接丢弃
+    fn poll_search(&mut self) {
+        let Some((job, hash, rx)) = &self.search else {
+            return;
+        };
+        let (job, hash) = (*job, *hash);
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => None,
+        };
+        self.search = None;
+        if hash != self.board.position_hash() {
+            return;
+        }
+
+        match (job, result) {
+            (Job::Engine, Some(action)) => self.play(action),
+            (Job::Hint, Some(action)) => {
+                let explanation = ai::explain(&self.board, &action);
+                self.message = trf("cli.hint", &[&action.notation(), &explanation]);
+                self.hint = Some((hash, action));
+            }
+            (_, None) => self.message = tr("hint.no_action").to_string(),
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let (r, c) = self.cursor;
+        match code {
+            KeyCode::Up => self.cursor = (r.saturating_sub(1), c),
+            KeyCode::Down => self.cursor = ((r + 1).min(4), c),
+            KeyCode::Left => self.cursor = (r, c.saturating_sub(1)),
+            KeyCode::Right => self.cursor = (r, (c + 1).min(4)),
+            KeyCode::Enter | KeyCode::Char(' ') => self.select(),
+            KeyCode::Esc => self.selected = None,
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('n') => self.start_game(Board::new()),
+            KeyCode::Char('r') => {
+                if let Some(board) = self.resume.take() {
+                    self.start_game(board);
+                    self.message = tr("gui.resumed").to_string();
+                }
+            }
+            KeyCode::Char('?') => self.request_hint(),
+            KeyCode::Char('f') if !self.game_over && self.resume.is_none() => {
+                let player = self.board.current_player;
+                self.board.record_resign(player);
+                self.check_game_over();
+                self.message = trf("tui.resigned", &[&player, &player.opponent()]);
+                self.autosave();
+            }
+            KeyCode::Char('s') => {
+                self.message = match savegame::save(Path::new(&self.save_path), &self.board, SaveKind::Record) {
+                    Ok(()) => trf("cli.saved", &[&self.save_path]),
+                    Err(e) => e,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    // 在光标处落子、吃子，或选择和移动棋子
+    fn select(&mut self) {
+        if self.resume.is_some() {
+            self.message = tr("tui.resume_first").to_string();
+            return;
+        }
+        if self.game_over {
+            self.message = tr("tui.game_over").to_string();
+            return;
+        }
+        if self.is_engine_turn() {
+            self.message = tr("gui.wait_engine").to_string();
+            return;
+        }
+
+        let player = self.board.current_player;
+        let pos = self.cursor;
+        match self.board.phase {
+            GamePhase::Placement => self.play(GameAction::Place { player, pos }),
+            GamePhase::Capture => self.play(GameAction::Capture { player, pos }),
+            GamePhase::Movement => match (self.selected, self.board.grid[pos.0][pos.1]) {
+                (_, Cell::Occupied(p)) if p == player => {
+                    self.selected = (self.selected != Some(pos)).then_some(pos);
+                }
+                (Some(from), _) => self.play(GameAction::Move { player, from, to: pos }),
+                (None, _) => self.message = tr("tui.select_own").to_string(),
+            },
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(13), Constraint::Length(5)])
+            .split(frame.size());
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(30), Constraint::Min(20)])
+            .split(rows[0]);
+
+        frame.render_widget(self.board_widget(), columns[0]);
+
+        let (items, count) = self.history_items();
+        let mut state = ListState::default();
+        if count > 0 {
+            state.select(Some(count - 1));
+        }
+        let history = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(" {} ", tr("tui.history"))))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+        frame.render_stateful_widget(history, columns[1], &mut state);
+
+        frame.render_widget(self.status_widget(), rows[1]);
+    }
+
+    fn board_widget(&self) -> Paragraph<'static> {
+        let board_style = Style::default().bg(BOARD_COLOR).fg(GRID_COLOR);
+        let hint_cells: Vec<(usize, usize)> = match &self.hint {
+            Some((_, GameAction::Move { from, to, .. })) => vec![*from, *to],
+            Some((_, GameAction::Place { pos, .. } | GameAction::Capture { pos, .. })) => vec![*pos],
+            _ => vec![],
+        };
+
+        let mut lines = vec![Line::from(""), Line::from("      0   1   2   3   4")];
+        for r in 0..5 {
+            let mut spans = vec![Span::raw(format!("  {} ", r))];
+            for c in 0..5 {
+                let protected = match self.board.grid[r][c] {
+                    Cell::Occupied(p) => self.board.reward_pieces.get(&p).is_some_and(|s| s.contains(&(r, c))),
+                    Cell::Empty => false,
+                };
+                let (piece, piece_color) = match self.board.grid[r][c] {
+                    Cell::Occupied(Player::Black) => ("●", Color::Black),
+                    Cell::Occupied(Player::White) => ("●", Color::White),
+                    Cell::Empty => ("┼", GRID_COLOR),
+                };
+
+                let mut style = board_style;
+                if hint_cells.contains(&(r, c)) {
+                    style = style.bg(HINT_COLOR);
+                }
+                if self.selected == Some((r, c)) {
+                    style = style.bg(SELECTED_COLOR);
+                }
+                if self.cursor == (r, c) {
+                    style = style.bg(CURSOR_COLOR);
+                }
+
+                // 受保护的棋子用金色括号标出，与图形界面的金色圆环对应
+                let (left, right) = if protected { ("[", "]") } else { (" ", " ") };
+                let bracket = style.fg(PROTECTED_COLOR).add_modifier(Modifier::BOLD);
+                spans.push(Span::styled(left, bracket));
+                spans.push(Span::styled(piece, style.fg(piece_color).add_modifier(Modifier::BOLD)));
+                spans.push(Span::styled(right, bracket));
+                if c < 4 {
+                    spans.push(Span::styled("─", board_style));
+                }
+            }
+            lines.push(Line::from(spans));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("[●]", Style::default().fg(PROTECTED_COLOR)),
+            Span::raw(format!(" {}", tr("gui.protected"))),
+        ]));
+
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(" {} ", tr("tui.title"))))
+    }
+
+    // 着法记录：每步一行，奖励和认输等记录缩进显示在该步之下
+    fn history_items(&self) -> (Vec<ListItem<'static>>, usize) {
+        let record = &self.board.game_record;
+        let replayer = GameReplayer::new(record.clone());
+        // 第一步之前的记录（例如开局就认输）不属于任何一步
+        let leading = record.iter().take_while(|a| !a.is_play()).count();
+        let steps = std::iter::once(&record[..leading]).chain(replayer.steps());
+
+        let mut items = Vec::new();
+        for (i, step) in steps.enumerate() {
+            for action in step.iter() {
+                let text = match action {
+                    GameAction::Reward { pattern, .. } => format!("      {}", trf("record.formed", &[pattern])),
+                    GameAction::Timeout { .. } => format!("      {}", tr("reason.timeout")),
+                    GameAction::Resign { .. } => format!("      {}", tr("reason.resign")),
+                    GameAction::Clock { .. } => continue,
+                    _ => format!("{:>4}. {} {}", i, action.player(), action.notation()),
+                };
+                items.push(ListItem::new(text));
+            }
+        }
+        let count = items.len();
+        (items, count)
+    }
+
+    fn status_widget(&self) -> Paragraph<'static> {
+        let board = &self.board;
+        let player = board.current_player;
+        let detail = match board.phase {
+            GamePhase::Placement if board.extra_moves > 0 => trf("tui.extra", &[&board.extra_moves]),
+            GamePhase::Capture => {
+                trf("tui.captures_left", &[&board.capture_remaining.get(&player).copied().unwrap_or(0)])
+            }
+            GamePhase::Movement if self.selected.is_some() => tr("tui.choose_target").to_string(),
+            _ => String::new(),
+        };
+        let turn = if self.game_over {
+            tr("game.finished").to_string()
+        } else if self.is_engine_turn() {
+            trf("tui.engine_thinking", &[&player])
+        } else {
+            trf("tui.to_move", &[&player])
+        };
+        let pieces = trf(
+            "tui.pieces",
+            &[&board.player_pieces(Player::Black).len(), &board.player_pieces(Player::White).len()],
+        );
+        let status = format!("{} | {} | {} | {}", board.phase, turn, detail, pieces);
+
+        let lines = vec![
+            Line::from(Span::styled(status, Style::default().add_modifier(Modifier::BOLD))),
+            Line::from(self.message.clone()),
+            Line::from(Span::styled(
+                tr("tui.keys"),
+                Style::default().fg(Color::DarkGray),
+            )),
+        ];
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL))
+    }
+}
@@ -0,0 +1,221 @@
+// 对局服务器的集成测试：在系统分配的端口上启动服务器，通过原始 HTTP 请求访问
+
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use wudaofang::GameAction;
+use wudaofang::server::{self, ServerHandle};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// 每个测试使用单独的棋谱目录
+fn records_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("wudaofang-server-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    dir
+}
+
+fn start(dir: &Path) -> ServerHandle {
+    server::start("127.0.0.1:0", dir.to_path_buf()).unwrap()
+}
+
+fn connect(addr: SocketAddr) -> TcpStream {
+    let stream = TcpStream::connect_timeout(&addr, TIMEOUT).unwrap();
+    stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+    stream
+}
+
+// 发送一个请求，返回状态码和 JSON 响应体
+fn request(addr: SocketAddr, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let mut stream = connect(addr);
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+fn create_room(addr: SocketAddr) -> u64 {
+    let (status, body) = request(addr, "POST", "/rooms", None);
+    assert_eq!(status, 200);
+    body["id"].as_u64().unwrap()
+}
+
+// 入座，返回执子方和令牌
+fn join(addr: SocketAddr, room: u64) -> (String, String) {
+    let (status, body) = request(addr, "POST", &format!("/rooms/{room}/join"), None);
+    assert_eq!(status, 200, "{body}");
+    (body["player"].as_str().unwrap().to_string(), body["token"].as_str().unwrap().to_string())
+}
+
+fn play(addr: SocketAddr, room: u64, token: &str, action: &str) -> (u16, Value) {
+    request(
+        addr,
+        "POST",
+        &format!("/rooms/{room}/action"),
+        Some(json!({ "token": token, "action": action })),
+    )
+}
+
+// 订阅房间事件，跳过响应头
+fn subscribe(addr: SocketAddr, room: u64) -> BufReader<TcpStream> {
+    let mut stream = connect(addr);
+    write!(stream, "GET /rooms/{room}/events HTTP/1.1\r\nHost: {addr}\r\n\r\n").unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert!(line.starts_with("HTTP/1.1 200"), "{line}");
+    while line != "\r\n" {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+    }
+    reader
+}
+
+// 读取下一条事件，忽略心跳
+fn next_event(reader: &mut BufReader<TcpStream>) -> Value {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        assert!(reader.read_line(&mut line).unwrap() > 0, "event stream closed");
+        if let Some(data) = line.strip_prefix("data: ") {
+            return serde_json::from_str(data.trim_end()).unwrap();
+        }
+    }
+}
+
+#[test]
+fn rooms_are_created_and_seated_in_order() {
+    let dir = records_dir("seats");
+    let addr = start(&dir).addr();
+
+    let first = create_room(addr);
+    let second = create_room(addr);
+    assert_ne!(first, second);
+
+    assert_eq!(join(addr, first).0, "Black");
+    assert_eq!(join(addr, first).0, "White");
+    let (status, _) = request(addr, "POST", &format!("/rooms/{first}/join"), None);
+    assert_eq!(status, 409);
+
+    let (status, rooms) = request(addr, "GET", "/rooms", None);
+    assert_eq!(status, 200);
+    assert_eq!(rooms[0]["seats"], json!(["Black", "White"]));
+    assert_eq!(rooms[1]["seats"], json!([]));
+
+    let (status, _) = request(addr, "GET", "/rooms/99", None);
+    assert_eq!(status, 404);
+}
+
+#[test]
+fn moves_are_checked_for_turn_and_legality() {
+    let dir = records_dir("moves");
+    let addr = start(&dir).addr();
+    let room = create_room(addr);
+    let (_, black) = join(addr, room);
+
+    // 对手未入座时不能走棋
+    let (status, _) = play(addr, room, &black, "2,2");
+    assert_eq!(status, 409);
+    let (_, white) = join(addr, room);
+
+    let (status, _) = play(addr, room, &white, "2,2");
+    assert_eq!(status, 409);
+    let (status, _) = play(addr, room, "bad-token", "2,2");
+    assert_eq!(status, 403);
+
+    let (status, view) = play(addr, room, &black, "2,2");
+    assert_eq!(status, 200);
+    assert_eq!(view["current_player"], "White");
+    assert_eq!(view["record"].as_array().unwrap().len(), 1);
+
+    // 已有棋子的位置和无法解析的动作都是非法动作
+    let (status, _) = play(addr, room, &white, "2,2");
+    assert_eq!(status, 400);
+    let (status, _) = play(addr, room, &white, "9,9");
+    assert_eq!(status, 400);
+    let (status, _) = play(addr, room, &white, "2,3");
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn event_stream_starts_with_snapshot_and_follows_actions() {
+    let dir = records_dir("events");
+    let addr = start(&dir).addr();
+    let room = create_room(addr);
+    let (_, black) = join(addr, room);
+
+    let mut events = subscribe(addr, room);
+    let snapshot = next_event(&mut events);
+    assert_eq!(snapshot["type"], "snapshot");
+    assert_eq!(snapshot["record"], json!([]));
+    assert_eq!(snapshot["result"], Value::Null);
+    let state = next_event(&mut events);
+    assert_eq!(state["type"], "state");
+    assert_eq!(state["current_player"], "Black");
+
+    join(addr, room);
+    let joined = next_event(&mut events);
+    assert_eq!(joined["type"], "joined");
+    assert_eq!(joined["player"], "White");
+
+    let (_, view) = play(addr, room, &black, "2,2");
+    let action = next_event(&mut events);
+    assert_eq!(action["type"], "action");
+    assert_eq!(action["notation"], "2,2");
+    let state = next_event(&mut events);
+    assert_eq!(state["type"], "state");
+    assert_eq!(state["current_player"], "White");
+    assert_eq!(state["hash"], view["hash"]);
+    assert_eq!(state["position"], view["position"]);
+}
+
+#[test]
+fn resignation_ends_the_game_and_saves_the_record() {
+    let dir = records_dir("resign");
+    let addr = start(&dir).addr();
+    let room = create_room(addr);
+    let (_, black) = join(addr, room);
+    let (_, white) = join(addr, room);
+    play(addr, room, &black, "2,2");
+
+    let mut events = subscribe(addr, room);
+    next_event(&mut events);
+    next_event(&mut events);
+
+    let path = format!("/rooms/{room}/resign");
+    let (status, view) = request(addr, "POST", &path, Some(json!({ "token": white })));
+    assert_eq!(status, 200);
+    assert_eq!(view["result"]["winner"], "Black");
+
+    let ended = next_event(&mut events);
+    assert_eq!(ended["type"], "game_ended");
+    assert_eq!(ended["winner"], "Black");
+
+    // 对局结束后不能再走棋或认输
+    let (status, _) = play(addr, room, &black, "2,3");
+    assert_eq!(status, 409);
+    let (status, _) = request(addr, "POST", &path, Some(json!({ "token": black })));
+    assert_eq!(status, 409);
+
+    // 棋谱写入棋谱目录，包含认输
+    let file = PathBuf::from(view["result"]["record_file"].as_str().unwrap());
+    assert!(file.starts_with(&dir));
+    let record: Vec<GameAction> = serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(record.len(), 2);
+    assert!(matches!(record[1], GameAction::Resign { .. }));
+    fs::remove_dir_all(&dir).ok();
+}